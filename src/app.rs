@@ -1,27 +1,145 @@
-use std::time::{Duration, SystemTime};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 
 use anyhow::{Ok, Result as AnyResult};
-use crossterm::event::{self, Event::Key, KeyCode::Char, KeyModifiers};
+use crossterm::event::{self, Event::Key, Event::Mouse, KeyEvent, MouseEvent};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Paragraph, Tabs},
 };
 
 use crate::{
-    colors::{BACKGROUND, VBLACK, VGREY},
+    config::{config_dir, Config},
+    dedicated::{default_command, DedicatedServer, DEDICATED_PROFILE},
+    dev_link::DevWatcher,
+    disk_usage::DiskUsage,
+    game_fs::{DryRunFs, GameFs, RealFs},
+    hooks::{ChangeHooks, Hooks},
+    install::GameInstall,
+    journal::{Journal, PendingOperation},
+    journal_view::{render_pending, JournalView},
+    keymap::{Action, KeyContext, Keymap},
+    log_viewer::LogViewer,
     mainmenu::MainMenu,
-    mods::Mods,
+    master_server::MASTER_SERVER_URL,
+    mod_store::STORE_NAME,
+    mods::{Mods, GAME_PATH},
+    mouse::{is_left_click, ClickTracker, Hitboxes},
+    notifications::{NotificationCenter, Notifier},
+    package_cache::{PackageCache, DEFAULT_LIMIT},
+    servers::Servers,
+    theme::{set_theme, theme, ColorDepth, Theme},
+    thunderstore::PACKAGE_INDEX_URL,
+    utils::Utils,
 };
 
+const TAB_DIVIDER: &str = " | ";
+
 pub trait TabUi {
     fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect);
-    fn render_binds(&self) -> Vec<&'static str>;
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String>;
     fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()>;
-    fn on_focus(&mut self) -> AnyResult<()>;
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()>;
+
+    /// picks up what finished in the background, called every frame while the tab is shown
+    fn tick(&mut self, _state: &mut GlobalState) -> AnyResult<()> {
+        Ok(())
+    }
+
+    /// picks up sizes that were just measured, only views that show them have to
+    fn on_disk_usage(&mut self, _state: &GlobalState) {}
+
+    /// the keys of a view the tab opened over itself, they come before the global ones
+    fn overlay(&self) -> Option<KeyContext> {
+        None
+    }
+
+    /// whether the tab is waiting on something in the background
+    #[cfg(test)]
+    fn is_busy(&self) -> bool {
+        false
+    }
 }
 
 pub struct GlobalState {
     pub is_typing: bool,
+    pub notifier: Notifier,
+    pub keymap: Keymap,
+    pub clicks: ClickTracker,
+    pub install: GameInstall,
+    pub journal: Journal,
+    /// waits for the user to confirm when `preview_changes` is on
+    pub pending: Option<PendingOperation>,
+    pub preview_changes: bool,
+    /// where packed mods go
+    pub pack_dir: PathBuf,
+    /// downloaded thunderstore zips, shared by every profile
+    pub package_cache: PackageCache,
+    /// the master server url or a json file with a server list
+    pub master_server: String,
+    /// the thunderstore package index url or a json file
+    pub package_index: String,
+    /// named queries of the packages view
+    pub saved_filters: PathBuf,
+    pub dedicated: DedicatedServer,
+    /// what every profile takes on disk, measured in the background after changes
+    pub disk_usage: DiskUsage,
+    pub hooks: Hooks,
+}
+
+impl GlobalState {
+    /// changes the game dir as one undoable transaction, or holds it back to be previewed first,
+    /// returns whether it already ran so the tab knows to reload
+    pub fn apply(
+        &mut self,
+        description: impl Into<String>,
+        op: impl Fn(&GameInstall) -> AnyResult<()> + 'static,
+    ) -> AnyResult<bool> {
+        self.apply_with(description, ChangeHooks::default(), op)
+    }
+
+    /// like [`GlobalState::apply`] with hooks around the change, they only run once it does
+    pub fn apply_with(
+        &mut self,
+        description: impl Into<String>,
+        hooks: ChangeHooks,
+        op: impl Fn(&GameInstall) -> AnyResult<()> + 'static,
+    ) -> AnyResult<bool> {
+        let description = description.into();
+        if self.preview_changes {
+            let mut pending = Journal::prepare(&self.install, description, op)?;
+            pending.hooks = hooks;
+            self.pending = Some(pending);
+            return Ok(false);
+        }
+
+        self.run_between(hooks, |journal, install| {
+            journal.run(install, &description, op)
+        })?;
+        Ok(true)
+    }
+
+    /// runs an operation held back by a preview, with the hooks it was asked with
+    pub fn confirm(&mut self, pending: PendingOperation) -> AnyResult<()> {
+        let hooks = pending.hooks.clone();
+        self.run_between(hooks, |journal, install| journal.confirm(install, pending))
+    }
+
+    fn run_between(
+        &mut self,
+        hooks: ChangeHooks,
+        run: impl FnOnce(&mut Journal, &GameInstall) -> AnyResult<()>,
+    ) -> AnyResult<()> {
+        if let Some(details) = hooks.before {
+            self.hooks.before(details)?;
+        }
+        run(&mut self.journal, &self.install)?;
+        self.disk_usage.measure(&self.install);
+        if let Some(details) = hooks.after {
+            self.hooks.after(details);
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -31,6 +149,7 @@ pub enum Tab {
     Main,
     Mods,
     Utils,
+    Servers,
     Max, // doesn't have a label
 }
 
@@ -40,6 +159,7 @@ impl std::fmt::Display for Tab {
             Tab::Main => "main",
             Tab::Mods => "mods",
             Tab::Utils => "utils",
+            Tab::Servers => "servers",
             Tab::Max => unreachable!("should be called on this"),
         }
         .to_uppercase();
@@ -48,9 +168,9 @@ impl std::fmt::Display for Tab {
     }
 }
 
-impl Into<u32> for Tab {
-    fn into(self) -> u32 {
-        self as u32
+impl From<Tab> for u32 {
+    fn from(tab: Tab) -> Self {
+        tab as u32
     }
 }
 
@@ -61,7 +181,7 @@ impl TryFrom<u32> for Tab {
         if Tab::Max as u32 <= value {
             Err(value)
         } else {
-            Result::Ok(unsafe { std::mem::transmute(value) })
+            Result::Ok(unsafe { std::mem::transmute::<u32, Tab>(value) })
         }
     }
 }
@@ -70,18 +190,176 @@ pub struct App {
     pub should_quit: bool,
     tab: Tab,
     tabs: Vec<Box<dyn TabUi>>,
-    last_error: Option<(SystemTime, anyhow::Error)>,
+    notifications: NotificationCenter,
+    log_viewer: LogViewer,
+    journal: JournalView,
+    dev_watcher: DevWatcher,
+    /// where each header tab was drawn
+    header: Hitboxes<Tab>,
     state: GlobalState,
 }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(dry_run: bool, offline: bool) -> Self {
+        let config = Config::load();
+        let dry_run = dry_run || config.as_ref().is_ok_and(|config| config.dry_run);
+        let offline = offline || config.as_ref().is_ok_and(|config| config.offline);
+
+        match config {
+            Result::Ok(config) => Self::with_config(Config {
+                dry_run,
+                offline,
+                ..config
+            }),
+            Err(err) => {
+                let app = Self::with_config(Config {
+                    dry_run,
+                    offline,
+                    ..Config::default()
+                });
+                app.state
+                    .notifier
+                    .error(&err.context("using the default config"));
+                app
+            }
+        }
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let notifications = NotificationCenter::default();
+        let notifier = notifications.notifier();
+
+        let keymap = Keymap::with_overrides(&config.keymap).unwrap_or_else(|err| {
+            notifier.error(&err.context("using the default keymap"));
+            Keymap::default()
+        });
+        keymap
+            .conflicts()
+            .into_iter()
+            .for_each(|conflict| notifier.warn(conflict));
+
+        let depth = config
+            .color_depth
+            .as_deref()
+            .map(str::parse::<ColorDepth>)
+            .transpose()
+            .unwrap_or_else(|err| {
+                notifier.error(&err.context("detecting the color depth instead"));
+                None
+            })
+            .unwrap_or_else(ColorDepth::detect);
+        let theme = Theme::load(config.theme.as_deref().unwrap_or("dark")).unwrap_or_else(|err| {
+            notifier.error(&err.context("using the dark theme"));
+            Theme::default()
+        });
+        set_theme(theme.downsample(depth));
+
+        let fs: Arc<dyn GameFs> = if config.dry_run {
+            notifier.info("dry run, nothing in the game dir will be changed");
+            Arc::new(DryRunFs::new(Arc::new(RealFs)))
+        } else {
+            Arc::new(RealFs)
+        };
+        let mut install = GameInstall::new(
+            config.game_path.unwrap_or_else(|| PathBuf::from(GAME_PATH)),
+            fs,
+        );
+        if let Some(profile) = config.profile {
+            install.profile = profile;
+        }
+        if config.shared_mods {
+            install.mod_store = Some(
+                config
+                    .mod_store
+                    .clone()
+                    .unwrap_or_else(|| install.root.join(STORE_NAME)),
+            );
+        }
+        let dedicated = DedicatedServer {
+            install: GameInstall {
+                root: config
+                    .dedicated_path
+                    .clone()
+                    .unwrap_or_else(|| install.root.clone()),
+                ..install.with_profile(
+                    config
+                        .dedicated_profile
+                        .as_deref()
+                        .unwrap_or(DEDICATED_PROFILE),
+                )
+            },
+            command: config
+                .dedicated_command
+                .clone()
+                .unwrap_or_else(default_command),
+        };
+
+        Journal::empty_trash(&install);
+        let mut disk_usage = DiskUsage::default();
+        disk_usage.measure(&install);
+        // hooks could change anything so a dry run leaves them out too
+        let hooks = Hooks::new(
+            if config.dry_run {
+                Vec::new()
+            } else {
+                config.hooks.clone()
+            },
+            notifier.clone(),
+        );
+
         Self {
             should_quit: false,
             tab: Tab::default(),
-            tabs: vec![Box::new(MainMenu::default()), Box::new(Mods::default())],
-            last_error: None,
-            state: GlobalState { is_typing: false },
+            tabs: vec![
+                Box::new(MainMenu::default()),
+                Box::new(Mods::default()),
+                Box::new(Utils::default()),
+                Box::new(Servers::default()),
+            ],
+            state: GlobalState {
+                is_typing: false,
+                notifier,
+                keymap,
+                clicks: ClickTracker::default(),
+                install,
+                journal: Journal::default(),
+                pending: None,
+                preview_changes: config.preview_changes,
+                pack_dir: config
+                    .pack_dir
+                    .clone()
+                    .or_else(dirs::download_dir)
+                    .unwrap_or_else(|| PathBuf::from(".")),
+                package_cache: PackageCache {
+                    limit: config.package_cache_limit.unwrap_or(DEFAULT_LIMIT) * 1024 * 1024,
+                    offline: config.offline,
+                    ..PackageCache::new(config.package_cache.clone().unwrap_or_else(|| {
+                        dirs::cache_dir()
+                            .unwrap_or_else(std::env::temp_dir)
+                            .join("drydoct/packages")
+                    }))
+                },
+                master_server: config
+                    .master_server
+                    .clone()
+                    .unwrap_or_else(|| MASTER_SERVER_URL.to_owned()),
+                package_index: config
+                    .package_index
+                    .clone()
+                    .unwrap_or_else(|| PACKAGE_INDEX_URL.to_owned()),
+                saved_filters: config
+                    .saved_filters
+                    .clone()
+                    .unwrap_or_else(|| config_dir().join("filters.json")),
+                dedicated,
+                disk_usage,
+                hooks,
+            },
+            notifications,
+            log_viewer: LogViewer::default(),
+            journal: JournalView::default(),
+            dev_watcher: DevWatcher::default(),
+            header: Hitboxes::default(),
         }
     }
 
@@ -108,18 +386,28 @@ impl App {
                 Constraint::Length(1),
             ])
             .split(max_size);
+
+        // mirrors how `Tabs` lays out its titles, one cell of padding on each side then the divider
+        let titles = [Tab::Main, Tab::Mods, Tab::Utils, Tab::Servers].map(|t| (t, format!("{t}")));
+        self.header.clear();
+        titles.iter().fold(layout[0].x, |x, (tab, title)| {
+            let width = title.len() as u16 + 2;
+            self.header.push(Rect::new(x, layout[0].y, width, 1), *tab);
+            x + width + TAB_DIVIDER.len() as u16
+        });
+
         frame.render_widget(
             Tabs::new(
-                vec![Tab::Main, Tab::Mods, Tab::Utils]
+                titles
                     .into_iter()
-                    .map(|t| format!("{t}"))
+                    .map(|(_, title)| title)
                     .collect::<Vec<String>>(),
             )
             .block(Block::default().borders(Borders::NONE))
-            .style(Style::new().bg(VBLACK).fg(VGREY))
-            .highlight_style(Style::default().red().bg(VBLACK))
+            .style(Style::new().bg(theme().surface).fg(theme().border))
+            .highlight_style(Style::default().fg(theme().selected).bg(theme().surface))
             .select(self.tab as usize)
-            .divider(" | "),
+            .divider(TAB_DIVIDER),
             layout[0],
         );
 
@@ -128,98 +416,308 @@ impl App {
             .expect("label not implemented for implemented tab ui wtf?!")
             .render_ui(frame, layout[1]);
 
-        let mut binds = vec!["Tabs - (1-9)", "Quit - q"];
-        binds.extend(
-            self.tabs
-                .get(self.tab as usize)
-                .expect("tab should ready")
-                .render_binds(),
-        );
+        if self.notifications.show_history {
+            self.notifications.render_history(frame, layout[1]);
+        }
+        if self.journal.show {
+            self.journal
+                .render(frame, layout[1], &self.state.journal, &self.state.install);
+        }
+        if self.log_viewer.show {
+            self.log_viewer.render(frame, layout[1]);
+        }
+        self.notifications.render_toasts(frame, layout[1]);
+        if let Some(pending) = &self.state.pending {
+            render_pending(frame, layout[1], pending, &self.state.install);
+        }
+
+        let keymap = &self.state.keymap;
+        let mut binds = vec![keymap.tab_hint(self.tabs.len())];
+        binds.extend(keymap.hints(KeyContext::Global));
+        if self.state.pending.is_some() {
+            binds.extend(keymap.hints(KeyContext::Confirm));
+        } else if self.log_viewer.show {
+            binds.extend(self.log_viewer.render_binds(keymap));
+        } else if self.journal.show {
+            binds.extend(self.journal.render_binds(keymap));
+        } else if self.notifications.show_history {
+            binds.extend(self.notifications.render_binds(keymap));
+        } else {
+            binds.extend(
+                self.tabs
+                    .get(self.tab as usize)
+                    .expect("tab should ready")
+                    .render_binds(keymap),
+            );
+        }
         frame.render_widget(
-            Tabs::new(
-                binds
-                    .into_iter()
-                    .map(|t| format!("{t}"))
-                    .collect::<Vec<String>>(),
-            )
-            .block(Block::default().borders(Borders::NONE))
-            .style(Style::new().bg(VBLACK))
-            .divider("  "),
+            Tabs::new(binds)
+                .block(Block::default().borders(Borders::NONE))
+                .style(Style::new().bg(theme().surface).fg(theme().text))
+                .divider("  "),
             layout[2],
         );
+        let history_len = self.notifications.history().count();
+        let mut status = self
+            .notifications
+            .history()
+            .next()
+            .map(|latest| {
+                Line::from(vec![
+                    Span::styled(format!("[{}] ", latest.level), latest.level.style()),
+                    Span::raw(latest.message.as_str()),
+                    Span::raw(format!(" ({history_len} in history)")).fg(theme().muted),
+                ])
+            })
+            .unwrap_or_default();
+        if self.state.install.fs.is_dry_run() {
+            status.spans.insert(
+                0,
+                Span::styled("DRY RUN ", Style::default().fg(theme().warn)),
+            );
+        }
+        if self.state.package_cache.offline {
+            status.spans.insert(
+                0,
+                Span::styled("OFFLINE ", Style::default().fg(theme().warn)),
+            );
+        }
         frame.render_widget(
-            Paragraph::new(
-                self.last_error
-                    .as_ref()
-                    .map(|err| err.1.to_string())
-                    .unwrap_or_else(|| "".to_owned()),
-            )
-            .style(Style::default().bg(BACKGROUND))
-            .red(),
+            Paragraph::new(status).style(Style::default().bg(theme().background).fg(theme().text)),
             layout[3],
         );
     }
 
     pub fn update(&mut self) -> AnyResult<()> {
+        self.tick();
+
         if event::poll(Duration::from_millis(10))? {
-            let crosstermevent = event::read()?;
-            if let Key(key) = crosstermevent {
-                if key.kind == event::KeyEventKind::Press && !self.state.is_typing {
-                    match key.code {
-                        Char(keycode) => {
-                            let tab = keycode as u8 - b'1';
-                            self.tabs.get(tab as usize).map(|_| {
-                                (tab as u32)
-                                    .try_into()
-                                    .expect("label not implemented for implemented tab ui wtf?!")
-                            })
-                        }
-                        _ => None,
-                    }
-                    .map(|t| self.switch_tabs(t));
+            self.handle_event(event::read()?)?;
+        }
 
-                    match key.code {
-                        Char('c') if key.modifiers == KeyModifiers::CONTROL => {
-                            self.should_quit = true
+        Ok(())
+    }
+
+    /// pulls in notifications and logs that arrived since the last frame
+    pub fn tick(&mut self) {
+        for change in self.state.install.fs.take_changes() {
+            let change = self.state.install.shorten(&change.to_string());
+            self.state.notifier.info(format!("dry run, would {change}"));
+        }
+        self.poll_dev_links();
+        self.poll_disk_usage();
+        if let Err(err) = self.tabs[self.tab as usize].tick(&mut self.state) {
+            self.state.notifier.error(&err);
+        }
+        self.notifications.collect();
+        self.log_viewer.refresh();
+    }
+
+    /// shows the sizes once they're measured
+    fn poll_disk_usage(&mut self) {
+        match self.state.disk_usage.poll(&self.state.install) {
+            Result::Ok(false) => {}
+            Result::Ok(true) => self.tabs[self.tab as usize].on_disk_usage(&self.state),
+            Err(err) => self.state.notifier.error(&err),
+        }
+    }
+
+    /// whether anything is still being worked on in the background
+    #[cfg(test)]
+    pub fn is_busy(&self) -> bool {
+        self.state.disk_usage.is_measuring()
+            || self.state.hooks.is_running()
+            || self.tabs[self.tab as usize].is_busy()
+    }
+
+    /// revalidates dev linked mods whose folders changed and shows the new state, copies are
+    /// synced through the journal
+    fn poll_dev_links(&mut self) {
+        let reloads = self.dev_watcher.tick(&self.state.install);
+        if reloads.is_empty() {
+            return;
+        }
+
+        for reload in reloads {
+            let description = format!("sync {} from its dev link", reload.dir);
+            let synced = match &reload.result {
+                Result::Ok(_) => {
+                    self.state
+                        .journal
+                        .run(&self.state.install, &description, |install| {
+                            reload.sync(install)
+                        })
+                }
+                Err(_) => Ok(()),
+            };
+            match (reload.result, synced) {
+                (Result::Ok(mod_json), Result::Ok(())) => self
+                    .state
+                    .notifier
+                    .info(format!("reloaded {} from its dev link", mod_json.name)),
+                (Err(err), _) | (_, Err(err)) => self
+                    .state
+                    .notifier
+                    .error(&err.context(format!("{} changed", reload.dir))),
+            }
+        }
+        // a reload would throw away what is being typed
+        if !self.state.is_typing {
+            self.refresh_tab();
+        }
+    }
+
+    pub fn handle_event(&mut self, crosstermevent: event::Event) -> AnyResult<()> {
+        if let Mouse(mouse) = crosstermevent {
+            self.state.clicks.register(&mouse);
+            if self.handle_mouse(mouse) {
+                return Ok(());
+            }
+        }
+        if let Key(key) = crosstermevent {
+            if key.kind == event::KeyEventKind::Press && self.state.pending.is_some() {
+                self.handle_pending(key);
+                return Ok(());
+            }
+            if key.kind == event::KeyEventKind::Press && !self.state.is_typing {
+                let action = match self.tabs[self.tab as usize].overlay() {
+                    Some(context) if self.state.keymap.action(context, &key).is_some() => None,
+                    _ => self.state.keymap.action(KeyContext::Global, &key),
+                };
+
+                if !self.log_viewer.is_typing() {
+                    match action {
+                        Some(Action::ToggleNotifications) => {
+                            self.notifications.toggle_history();
+                            return Ok(());
+                        }
+                        Some(Action::ToggleLogs) => {
+                            self.log_viewer.toggle();
+                            return Ok(());
+                        }
+                        Some(Action::ToggleJournal) => {
+                            self.journal.toggle();
+                            return Ok(());
                         }
-                        Char('q') | event::KeyCode::Esc => self.should_quit = true,
                         _ => {}
                     }
                 }
+
+                // overlays get the keys in the order they are drawn, topmost first
+                let transactions = self.state.journal.len();
+                let consumed = self
+                    .log_viewer
+                    .handle_key(key, &self.state.keymap)
+                    .and_then(|consumed| {
+                        Ok(consumed || self.journal.handle_key(key, &mut self.state)?)
+                    })
+                    .and_then(|consumed| {
+                        Ok(consumed || self.notifications.handle_key(key, &self.state.keymap)?)
+                    });
+                if self.state.journal.len() != transactions {
+                    self.refresh_tab();
+                }
+                match consumed {
+                    Result::Ok(true) => return Ok(()),
+                    Result::Ok(false) => {}
+                    Err(err) => {
+                        self.state.notifier.error(&err);
+                        return Ok(());
+                    }
+                }
+
+                match action {
+                    Some(Action::GoToTab(tab)) if tab < self.tabs.len() => self.switch_tabs(
+                        (tab as u32)
+                            .try_into()
+                            .expect("label not implemented for implemented tab ui wtf?!"),
+                    ),
+                    Some(Action::Quit) => self.should_quit = true,
+                    _ => {}
+                }
             }
+        }
+
+        if let Err(err) = self
+            .tabs
+            .get_mut(self.tab as usize)
+            .expect("label not implemented for implemented tab ui wtf?!")
+            .update(crosstermevent, &mut self.state)
+        {
+            self.state.notifier.error(&err)
+        }
+
+        Ok(())
+    }
+
+    /// returns true if the event was consumed before reaching the tab
+    fn handle_mouse(&mut self, mouse: MouseEvent) -> bool {
+        // overlays swallow the mouse so clicks don't land on the tab under them
+        if self.state.pending.is_some() {
+            return true;
+        }
+        if self.log_viewer.show {
+            self.log_viewer.handle_mouse(mouse);
+            return true;
+        }
+        if self.journal.show {
+            self.journal.handle_mouse(mouse, &self.state.journal);
+            return true;
+        }
+        if self.notifications.show_history {
+            self.notifications.handle_mouse(mouse);
+            return true;
+        }
 
-            if let Err(err) = self
-                .tabs
-                .get_mut(self.tab as usize)
-                .expect("label not implemented for implemented tab ui wtf?!")
-                .update(crosstermevent, &mut self.state)
-            {
-                self.last_error = Some((SystemTime::now() + Duration::from_secs(2), err))
+        if is_left_click(&mouse) && !self.state.is_typing {
+            if let Some(tab) = self.header.hit(&mouse) {
+                self.switch_tabs(tab);
+                return true;
             }
+        }
+
+        false
+    }
 
-            if self
-                .last_error
-                .as_ref()
-                .is_some_and(|last_error| last_error.0 < SystemTime::now())
-            {
-                self.last_error = None;
+    /// applies or drops the pending operation, the tab reloads either way since it may
+    /// already show the change
+    fn handle_pending(&mut self, key: KeyEvent) {
+        let pending = match self.state.keymap.action(KeyContext::Confirm, &key) {
+            Some(Action::Confirm) => self.state.pending.take(),
+            Some(Action::Close) => {
+                self.state.pending = None;
+                None
             }
+            _ => return,
         };
 
-        Ok(())
+        if let Some(pending) = pending {
+            let description = pending.description.clone();
+            match self.state.confirm(pending) {
+                Result::Ok(()) => self
+                    .state
+                    .notifier
+                    .info(format!("applied \"{description}\"")),
+                Err(err) => self.state.notifier.error(&err),
+            }
+        }
+        self.refresh_tab();
     }
 
-    fn switch_tabs(&mut self, new_tab: Tab) {
-        self.tab = new_tab;
-        self.state.is_typing = false;
-
+    fn refresh_tab(&mut self) {
         if let Err(err) = self
             .tabs
             .get_mut(self.tab as usize)
             .expect("label not implemented for implemented tab ui wtf?!")
-            .on_focus()
+            .on_focus(&mut self.state)
         {
-            self.last_error = Some((SystemTime::now() + Duration::from_secs(2), err))
+            self.state.notifier.error(&err)
         }
     }
+
+    fn switch_tabs(&mut self, new_tab: Tab) {
+        self.tab = new_tab;
+        self.state.is_typing = false;
+        self.refresh_tab();
+    }
 }
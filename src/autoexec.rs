@@ -0,0 +1,295 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Ok, Result as AnyResult};
+use serde::Deserialize;
+
+use crate::{install::GameInstall, mods_views::InstalledModPartial};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoExecKind {
+    Client,
+    Server,
+}
+
+impl AutoExecKind {
+    /// where northstar's core mods keep the file
+    pub fn path(&self, install: &GameInstall) -> PathBuf {
+        let (core_mod, file) = match self {
+            AutoExecKind::Client => ("Northstar.Client", "autoexec_ns_client.cfg"),
+            AutoExecKind::Server => ("Northstar.CustomServers", "autoexec_ns_server.cfg"),
+        };
+
+        install
+            .mods_dir()
+            .join(core_mod)
+            .join("mod")
+            .join("cfg")
+            .join(file)
+    }
+}
+
+impl std::fmt::Display for AutoExecKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            AutoExecKind::Client => "client",
+            AutoExecKind::Server => "server",
+        };
+
+        write!(f, "{display}")
+    }
+}
+
+/// northstar convars that are switches, other convars defaulting to 0 or 1 could be counts
+const BOOL_CONVARS: &[&str] = &[
+    "everything_unlocked",
+    "ns_allow_team_change",
+    "ns_auth_allow_insecure",
+    "ns_erase_auth_info",
+    "ns_report_server_to_masterserver",
+    "ns_report_sp_server_to_masterserver",
+    "ns_should_return_to_lobby",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConVarType {
+    Bool,
+    Int,
+    Float,
+    String,
+}
+
+impl ConVarType {
+    /// mod.json doesn't declare types so they are guessed from the default value, only known
+    /// switches are bools
+    fn infer(name: &str, default_value: &str) -> Self {
+        match default_value.trim() {
+            "0" | "1" if BOOL_CONVARS.contains(&name) => ConVarType::Bool,
+            value if value.parse::<i64>().is_ok() => ConVarType::Int,
+            value if value.parse::<f64>().is_ok() => ConVarType::Float,
+            _ => ConVarType::String,
+        }
+    }
+
+    pub fn validate(&self, value: &str) -> AnyResult<()> {
+        let valid = match self {
+            ConVarType::Bool => matches!(value, "0" | "1"),
+            ConVarType::Int => value.parse::<i64>().is_ok(),
+            ConVarType::Float => value.parse::<f64>().is_ok(),
+            ConVarType::String => !value.contains('"'),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(anyhow!("\"{value}\" isn't a valid {self}"))
+        }
+    }
+
+    /// whether a character could be part of a value of this type, used to filter typing
+    pub fn accepts(&self, c: char) -> bool {
+        match self {
+            ConVarType::Bool => matches!(c, '0' | '1'),
+            ConVarType::Int => c.is_ascii_digit() || c == '-',
+            ConVarType::Float => c.is_ascii_digit() || matches!(c, '-' | '.'),
+            ConVarType::String => c != '"',
+        }
+    }
+}
+
+impl std::fmt::Display for ConVarType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            ConVarType::Bool => "bool",
+            ConVarType::Int => "int",
+            ConVarType::Float => "float",
+            ConVarType::String => "string",
+        };
+
+        write!(f, "{display}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConVar {
+    pub name: String,
+    pub mod_name: String,
+    pub default_value: String,
+    pub help: String,
+    pub ty: ConVarType,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ModJsonConVars {
+    #[serde(default)]
+    con_vars: Vec<ConVarJson>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ConVarJson {
+    name: String,
+    #[serde(default)]
+    default_value: serde_json::Value,
+    #[serde(default)]
+    help_string: String,
+}
+
+/// every convar declared in the mod.json of the given mods, sorted by name
+pub fn collect_convars(install: &GameInstall, mods: &[InstalledModPartial]) -> Vec<ConVar> {
+    let mut convars = mods
+        .iter()
+        .filter_map(|nsmod| {
+            let mod_json = install
+                .fs
+                .read_to_string(&nsmod.path.join("mod.json"))
+                .ok()?;
+            let declared = json5::from_str::<ModJsonConVars>(&mod_json)
+                .map_err(|err| {
+                    log::warn!("couldn't read convars of {}: {err}", nsmod.mod_json.name)
+                })
+                .ok()?;
+
+            Some(declared.con_vars.into_iter().map(|convar| {
+                let default_value = match convar.default_value {
+                    serde_json::Value::String(value) => value,
+                    serde_json::Value::Null => String::new(),
+                    value => value.to_string(),
+                };
+
+                ConVar {
+                    ty: ConVarType::infer(&convar.name, &default_value),
+                    name: convar.name,
+                    mod_name: nsmod.mod_json.name.clone(),
+                    default_value,
+                    help: convar.help_string,
+                }
+            }))
+        })
+        .flatten()
+        .collect::<Vec<ConVar>>();
+
+    convars.sort_by(|a, b| a.name.cmp(&b.name));
+
+    convars
+}
+
+/// an autoexec cfg kept as raw lines so unrelated lines and comments survive a save
+#[derive(Debug, Clone)]
+pub struct AutoExec {
+    pub path: PathBuf,
+    lines: Vec<String>,
+    line_ending: &'static str,
+}
+
+impl AutoExec {
+    pub fn load(install: &GameInstall, path: PathBuf) -> AnyResult<Self> {
+        let content = match install.fs.read_to_string(&path) {
+            Result::Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("couldn't read {}", path.display()))
+            }
+        };
+
+        Ok(Self::parse(path, &content))
+    }
+
+    pub fn parse(path: PathBuf, content: &str) -> Self {
+        Self {
+            path,
+            lines: content.lines().map(str::to_owned).collect(),
+            line_ending: if content.contains("\r\n") {
+                "\r\n"
+            } else {
+                "\n"
+            },
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| split_line(line).is_some_and(|(key, _, _)| key == name))
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.find(name)
+            .and_then(|index| split_line(&self.lines[index]))
+            .map(|(_, value, _)| value.to_owned())
+    }
+
+    /// replaces the value in place keeping any trailing comment or appends a new line
+    pub fn set(&mut self, name: &str, value: &str) {
+        let line = format!("{name} \"{value}\"");
+
+        match self.find(name) {
+            Some(index) => {
+                let comment = split_line(&self.lines[index])
+                    .and_then(|(_, _, comment)| comment)
+                    .map(|comment| format!(" {comment}"))
+                    .unwrap_or_default();
+                self.lines[index] = format!("{line}{comment}");
+            }
+            None => self.lines.push(line),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.find(name)
+            .map(|index| self.lines.remove(index))
+            .is_some()
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// replaces a whole line, comments and commands that aren't convars included
+    pub fn set_line(&mut self, index: usize, line: &str) {
+        match self.lines.get_mut(index) {
+            Some(old) => *old = line.to_owned(),
+            None => self.lines.push(line.to_owned()),
+        }
+    }
+
+    pub fn insert_line(&mut self, index: usize, line: &str) {
+        self.lines
+            .insert(index.min(self.lines.len()), line.to_owned());
+    }
+
+    pub fn remove_line(&mut self, index: usize) -> Option<String> {
+        (index < self.lines.len()).then(|| self.lines.remove(index))
+    }
+
+    pub fn contents(&self) -> String {
+        let mut content = self.lines.join(self.line_ending);
+        content.push_str(self.line_ending);
+        content
+    }
+
+    pub fn save(&self, install: &GameInstall) -> AnyResult<()> {
+        install.write(&self.path, self.contents().as_bytes())
+    }
+}
+
+/// splits `name "value" // comment` into its parts, comments and blank lines return none
+fn split_line(line: &str) -> Option<(&str, &str, Option<&str>)> {
+    let mut in_quotes = false;
+    let comment_start = line.char_indices().find_map(|(index, c)| {
+        in_quotes ^= c == '"';
+        (!in_quotes && line[index..].starts_with("//")).then_some(index)
+    });
+    let (code, comment) = match comment_start {
+        Some(index) => (&line[..index], Some(line[index..].trim_end())),
+        None => (line, None),
+    };
+    let code = code.trim();
+    if code.is_empty() {
+        return None;
+    }
+
+    let (name, value) = code.split_once(char::is_whitespace).unwrap_or((code, ""));
+
+    Some((name, value.trim().trim_matches('"'), comment))
+}
@@ -0,0 +1,43 @@
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use anyhow::{anyhow, Result as AnyResult};
+
+/// work that would block the ui, like a download, run on its own thread and picked up on a
+/// later tick
+pub struct Background<T> {
+    result: Option<Receiver<AnyResult<T>>>,
+}
+
+impl<T> Default for Background<T> {
+    fn default() -> Self {
+        Self { result: None }
+    }
+}
+
+impl<T: Send + 'static> Background<T> {
+    /// whatever was still running is forgotten
+    pub fn start(&mut self, work: impl FnOnce() -> AnyResult<T> + Send + 'static) {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || _ = sender.send(work()));
+        self.result = Some(receiver);
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.result.is_some()
+    }
+
+    /// the result once the work is done
+    pub fn poll(&mut self) -> Option<AnyResult<T>> {
+        let result = match self.result.as_ref()?.try_recv() {
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err(anyhow!("the background work stopped")),
+            Result::Ok(result) => result,
+        };
+        self.result = None;
+
+        Some(result)
+    }
+}
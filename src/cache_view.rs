@@ -0,0 +1,163 @@
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    game_fs::RealFs,
+    keymap::{Action, KeyContext, Keymap},
+    package_cache::{format_size, CacheEntry, PackageCache},
+    package_filter::civil_date,
+    theme::theme,
+};
+
+#[derive(Default)]
+pub struct Cache {
+    /// most recently used first
+    entries: Vec<CacheEntry>,
+    selected: usize,
+    /// copied on reload so rendering knows the limit and where it is
+    cache: Option<PackageCache>,
+}
+
+impl Cache {
+    fn reload(&mut self, state: &GlobalState) {
+        let cache = state.package_cache.clone();
+        self.entries = cache.entries(&RealFs);
+        self.entries.reverse();
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+        self.cache = Some(cache);
+    }
+
+    fn usage(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+
+    fn remove_selected(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(entry) = self.entries.get(self.selected) else {
+            return Ok(());
+        };
+
+        state.package_cache.remove(&RealFs, &entry.key)?;
+        self.reload(state);
+
+        Ok(())
+    }
+
+    fn clean(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let (count, usage) = (self.entries.len(), self.usage());
+        state.package_cache.clean(&RealFs)?;
+        self.reload(state);
+        state.notifier.info(format!(
+            "removed {} archives from the package cache, {} freed",
+            count - self.entries.len(),
+            format_size(usage - self.usage())
+        ));
+
+        Ok(())
+    }
+}
+
+impl TabUi for Cache {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let layout = Layout::new()
+            .constraints([Constraint::Length(3), Constraint::Min(3)])
+            .split(rect);
+        let (limit, offline, dir) = self
+            .cache
+            .as_ref()
+            .map(|cache| (cache.limit, cache.offline, cache.dir.display().to_string()))
+            .unwrap_or_default();
+
+        frame.render_widget(
+            Gauge::default()
+                .ratio((self.usage() as f64 / limit.max(1) as f64).min(1.0))
+                .label(format!(
+                    "{} of {} in {} archives",
+                    format_size(self.usage()),
+                    format_size(limit),
+                    self.entries.len()
+                ))
+                .gauge_style(Style::default().fg(theme().accent).bg(theme().surface))
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(if offline {
+                            format!("{dir} (offline)")
+                        } else {
+                            dir
+                        })
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background)
+                        .fg(theme().text),
+                ),
+            layout[0],
+        );
+
+        let rows = self
+            .entries
+            .iter()
+            .map(|entry| {
+                Row::new(vec![
+                    Cell::from(entry.key.as_str()).fg(theme().text),
+                    Cell::from(format_size(entry.size)).fg(theme().muted),
+                    Cell::from(civil_date(entry.last_used / (60 * 60 * 24))).fg(theme().muted),
+                ])
+            })
+            .collect::<Vec<Row<'_>>>();
+        frame.render_stateful_widget(
+            Table::new(rows)
+                .header(
+                    Row::new(vec!["archive", "size", "last used"])
+                        .style(Style::default().fg(theme().accent)),
+                )
+                .widths(&[
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(20),
+                ])
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title("package cache")
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background),
+                )
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            layout[1],
+            &mut TableState::default().with_selected(Some(self.selected)),
+        );
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::Cache)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        let event::Event::Key(key_event) = crosstermevent else {
+            return Ok(());
+        };
+        if key_event.kind != event::KeyEventKind::Press || state.is_typing {
+            return Ok(());
+        }
+
+        match state.keymap.action(KeyContext::Cache, &key_event) {
+            Some(Action::Down) => {
+                self.selected = self.entries.len().saturating_sub(1).min(self.selected + 1)
+            }
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            Some(Action::Delete) => self.remove_selected(state)?,
+            Some(Action::Clean) => self.clean(state)?,
+            Some(Action::Reload) => self.reload(state),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.reload(state);
+
+        Ok(())
+    }
+}
@@ -1,6 +0,0 @@
-use ratatui::style::Color;
-
-pub const BACKGROUND: Color = Color::Rgb(41, 41, 41);
-pub const VGREY: Color = Color::Rgb(69, 69, 69);
-pub const VBLACK: Color = Color::Rgb(24, 24, 24);
-pub const SELECT: Color = Color::Rgb(235, 235, 235);
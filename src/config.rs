@@ -0,0 +1,77 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+use serde::Deserialize;
+
+use crate::hooks::Hook;
+
+const CONFIG_NAME: &str = "config.json5";
+
+/// drydoct's `config.json5`, every field is optional so a partial config is fine
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// context -> action -> keys, replaces the default keys of every listed action
+    pub keymap: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    /// a built-in theme name or the name of a file in `<config dir>/themes`
+    pub theme: Option<String>,
+    /// `truecolor`, `256` or `16`, detected from the terminal when missing
+    pub color_depth: Option<String>,
+    /// the Titanfall2 install, defaults to the usual steam location
+    pub game_path: Option<PathBuf>,
+    /// the northstar profile to manage, `R2Northstar` when missing
+    pub profile: Option<String>,
+    /// only show what would change in the game dir, also set by `--dry-run`
+    pub dry_run: bool,
+    /// show what a change to the game dir would do and ask before doing it
+    pub preview_changes: bool,
+    /// where packed mods are written, the downloads dir when missing
+    pub pack_dir: Option<PathBuf>,
+    /// where downloaded thunderstore zips are kept, `<cache dir>/drydoct/packages` when missing
+    pub package_cache: Option<PathBuf>,
+    /// in megabytes, the least recently used archives are removed past it, 2048 when missing
+    pub package_cache_limit: Option<u64>,
+    /// never download archives, installs only work from the package cache, also set by `--offline`
+    pub offline: bool,
+    /// install every mod version once into the mod store and link it into the profiles that have it
+    pub shared_mods: bool,
+    /// where shared mods are kept, `<game dir>/drydoct-store` when missing, hard links need it on
+    /// the same drive as the game
+    pub mod_store: Option<PathBuf>,
+    /// where the server list comes from, a path to a json file works too
+    pub master_server: Option<String>,
+    /// the thunderstore package index, a path to a json file works too
+    pub package_index: Option<String>,
+    /// where named package filters are saved, `<config dir>/filters.json` when missing
+    pub saved_filters: Option<PathBuf>,
+    /// a separate install for the dedicated server, `game_path` when missing
+    pub dedicated_path: Option<PathBuf>,
+    /// the profile the dedicated server runs from, `R2Northstar-dedicated` when missing
+    pub dedicated_profile: Option<String>,
+    /// the program and arguments that start the server, `-dedicated -profile=<profile>` is added
+    pub dedicated_command: Option<Vec<String>>,
+    /// commands run on installs, enabling and disabling mods and around the dedicated server
+    pub hooks: Vec<Hook>,
+}
+
+impl Config {
+    pub fn load() -> AnyResult<Self> {
+        let path = config_dir().join(CONFIG_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        json5::from_str(
+            &fs::read_to_string(&path)
+                .with_context(|| format!("couldn't read {}", path.display()))?,
+        )
+        .with_context(|| format!("invalid config in {}", path.display()))
+    }
+}
+
+/// `$XDG_CONFIG_HOME/drydoct` or the platform's equivalent
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("drydoct")
+}
@@ -0,0 +1,339 @@
+use std::cell;
+
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event::{self, KeyCode, MouseEvent, MouseEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    autoexec::{collect_convars, AutoExec, AutoExecKind, ConVar, ConVarType},
+    keymap::{Action, KeyContext, Keymap},
+    mods_views::reload_mods,
+    mouse::{is_left_click, Hitboxes},
+    theme::theme,
+};
+
+pub struct ConVars {
+    convars: Vec<ConVar>,
+    client: Option<AutoExec>,
+    server: Option<AutoExec>,
+    target: AutoExecKind,
+    selected: usize,
+    /// the value being typed, only set while editing
+    editing: Option<String>,
+    table: Hitboxes<()>,
+    /// the first row shown when the table was last drawn
+    offset: cell::Cell<usize>,
+}
+
+impl Default for ConVars {
+    fn default() -> Self {
+        Self {
+            convars: Vec::new(),
+            client: None,
+            server: None,
+            target: AutoExecKind::Client,
+            selected: 0,
+            editing: None,
+            table: Hitboxes::default(),
+            offset: cell::Cell::new(0),
+        }
+    }
+}
+
+impl ConVars {
+    fn autoexec(&self, kind: AutoExecKind) -> Option<&AutoExec> {
+        match kind {
+            AutoExecKind::Client => self.client.as_ref(),
+            AutoExecKind::Server => self.server.as_ref(),
+        }
+    }
+
+    fn target_mut(&mut self) -> Option<&mut AutoExec> {
+        match self.target {
+            AutoExecKind::Client => self.client.as_mut(),
+            AutoExecKind::Server => self.server.as_mut(),
+        }
+    }
+
+    /// returns whether the autoexec was saved right away
+    fn write(
+        &mut self,
+        name: &str,
+        value: Option<&str>,
+        state: &mut GlobalState,
+    ) -> AnyResult<bool> {
+        let target = self.target;
+        // a preview can be cancelled, so the shown autoexec only changes once it is saved
+        let Some(mut autoexec) = self.autoexec(target).cloned() else {
+            return Ok(false);
+        };
+
+        let description = match value {
+            Some(value) => {
+                autoexec.set(name, value);
+                format!("set {name} to \"{value}\" in the {target} autoexec")
+            }
+            None => {
+                _ = autoexec.remove(name);
+                format!("reset {name} in the {target} autoexec")
+            }
+        };
+        let saved = autoexec.clone();
+        let applied = state.apply(description, move |install| saved.save(install))?;
+        if applied {
+            if let Some(shown) = self.target_mut() {
+                *shown = autoexec;
+            }
+        }
+        Ok(applied)
+    }
+
+    fn select_down(&mut self) {
+        self.selected = self.convars.len().saturating_sub(1).min(self.selected + 1)
+    }
+
+    fn edit(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(convar) = self.convars.get(self.selected) else {
+            return Ok(());
+        };
+        let current = self
+            .autoexec(self.target)
+            .and_then(|autoexec| autoexec.get(&convar.name))
+            .unwrap_or_else(|| convar.default_value.clone());
+
+        // bools don't need typing
+        if convar.ty == ConVarType::Bool {
+            let name = convar.name.clone();
+            let value = if current == "1" { "0" } else { "1" };
+            self.write(&name, Some(value), state)?;
+        } else {
+            self.editing = Some(current);
+            state.is_typing = true;
+        }
+
+        Ok(())
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(((), row)) = self.table.hit_row(&mouse) else {
+            return Ok(());
+        };
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.select_down(),
+            MouseEventKind::ScrollUp => self.selected = self.selected.saturating_sub(1),
+            _ if is_left_click(&mouse) => {
+                // skip the border and the header
+                let Some(index) = (row as usize)
+                    .checked_sub(2)
+                    .map(|row| row + self.offset.get())
+                    .filter(|index| *index < self.convars.len())
+                else {
+                    return Ok(());
+                };
+
+                self.selected = index;
+                if state.clicks.is_double() {
+                    self.edit(state)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// a value that doesn't fit the type keeps the editor open so it can be fixed
+    fn commit_edit(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let (Some(value), Some(convar)) = (self.editing.clone(), self.convars.get(self.selected))
+        else {
+            self.editing = None;
+            state.is_typing = false;
+            return Ok(());
+        };
+
+        convar.ty.validate(&value)?;
+        self.editing = None;
+        state.is_typing = false;
+        let name = convar.name.clone();
+        if self.write(&name, Some(&value), state)? {
+            state.notifier.info(format!(
+                "set {name} to \"{value}\" in the {} autoexec",
+                self.target
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl TabUi for ConVars {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let layout = Layout::new()
+            .constraints([Constraint::Min(3), Constraint::Length(5)])
+            .split(rect);
+
+        let rows = self
+            .convars
+            .iter()
+            .map(|convar| {
+                let client = self
+                    .autoexec(AutoExecKind::Client)
+                    .and_then(|autoexec| autoexec.get(&convar.name));
+                let server = self
+                    .autoexec(AutoExecKind::Server)
+                    .and_then(|autoexec| autoexec.get(&convar.name));
+                let overridden = client.is_some() || server.is_some();
+
+                Row::new(vec![
+                    Cell::from(convar.name.as_str()),
+                    Cell::from(convar.mod_name.as_str()).fg(theme().muted),
+                    Cell::from(convar.ty.to_string()).fg(theme().muted),
+                    Cell::from(convar.default_value.as_str()),
+                    Cell::from(client.unwrap_or_default()).fg(theme().accent),
+                    Cell::from(server.unwrap_or_default()).fg(theme().accent),
+                ])
+                .style(if overridden {
+                    Style::default().fg(theme().text).bold()
+                } else {
+                    Style::default().fg(theme().text)
+                })
+            })
+            .collect::<Vec<Row<'_>>>();
+
+        self.table.clear();
+        self.table.push(layout[0], ());
+        let mut table_state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            Table::new(rows)
+                .header(
+                    Row::new(vec!["name", "mod", "type", "default", "client", "server"])
+                        .style(Style::default().fg(theme().accent)),
+                )
+                .widths(&[
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(7),
+                    Constraint::Percentage(16),
+                    Constraint::Percentage(16),
+                    Constraint::Percentage(16),
+                ])
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(format!(
+                            "convars ({}) - writing to the {} autoexec",
+                            self.convars.len(),
+                            self.target
+                        ))
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background),
+                )
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            layout[0],
+            &mut table_state,
+        );
+        self.offset.set(table_state.offset());
+
+        let details = match (self.convars.get(self.selected), &self.editing) {
+            (Some(convar), Some(value)) => Text::from(vec![
+                Line::raw(format!("editing {} ({})", convar.name, convar.ty)),
+                Line::styled(format!("{value}_"), Style::default().fg(theme().accent)),
+            ]),
+            (Some(convar), None) => Text::from(vec![
+                Line::raw(convar.help.as_str()),
+                Line::styled(
+                    self.autoexec(self.target)
+                        .map(|autoexec| autoexec.path.display().to_string())
+                        .unwrap_or_default(),
+                    Style::default().fg(theme().muted),
+                ),
+            ]),
+            (None, _) => Text::raw("no convars declared by installed mods"),
+        };
+        frame.render_widget(
+            Paragraph::new(details).wrap(Wrap { trim: true }).block(
+                Block::default()
+                    .borders(Borders::all())
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            layout[1],
+        );
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        if self.editing.is_some() {
+            return vec!["save - enter".to_owned(), "cancel - esc".to_owned()];
+        }
+
+        keymap.hints(KeyContext::ConVars)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        let key_event = match crosstermevent {
+            event::Event::Key(key_event) => key_event,
+            event::Event::Mouse(mouse) if !state.is_typing => {
+                return self.handle_mouse(mouse, state);
+            }
+            _ => return Ok(()),
+        };
+        if key_event.kind != event::KeyEventKind::Press {
+            return Ok(());
+        }
+
+        if let Some(value) = self.editing.as_mut() {
+            let ty = self.convars[self.selected].ty;
+            match key_event.code {
+                KeyCode::Char(c) if ty.accepts(c) => value.push(c),
+                KeyCode::Backspace => _ = value.pop(),
+                KeyCode::Enter => self.commit_edit(state)?,
+                KeyCode::Esc => {
+                    self.editing = None;
+                    state.is_typing = false;
+                }
+                _ => {}
+            }
+
+            return Ok(());
+        }
+
+        if state.is_typing {
+            return Ok(());
+        }
+
+        match state.keymap.action(KeyContext::ConVars, &key_event) {
+            Some(Action::Down) => self.select_down(),
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            Some(Action::ToggleTarget) => {
+                self.target = match self.target {
+                    AutoExecKind::Client => AutoExecKind::Server,
+                    AutoExecKind::Server => AutoExecKind::Client,
+                }
+            }
+            Some(Action::Reset) => {
+                if let Some(name) = self.convars.get(self.selected).map(|c| c.name.clone()) {
+                    self.write(&name, None, state)?;
+                }
+            }
+            Some(Action::Edit) => self.edit(state)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let install = &state.install;
+        let (mods, _) = reload_mods(install)?;
+        self.editing = None;
+
+        self.convars = collect_convars(install, &mods);
+        self.client = Some(AutoExec::load(install, AutoExecKind::Client.path(install))?);
+        self.server = Some(AutoExec::load(install, AutoExecKind::Server.path(install))?);
+        self.selected = self.selected.min(self.convars.len().saturating_sub(1));
+
+        Ok(())
+    }
+}
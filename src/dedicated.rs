@@ -0,0 +1,339 @@
+use std::{
+    collections::VecDeque,
+    io::{BufRead, BufReader, Read},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    sync::{Arc, Mutex, MutexGuard},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+
+use crate::{
+    autoexec::AutoExecKind,
+    install::GameInstall,
+    mod_store::copy_mod,
+    mods_views::{reload_mods, InstalledModPartial},
+    prepare::CORE_PREFIX,
+};
+
+/// the profile a dedicated server runs from when the config doesn't name one
+pub const DEDICATED_PROFILE: &str = "R2Northstar-dedicated";
+/// northstar reads the launch arguments of a dedicated server from this file next to the game
+const ARGS_NAME: &str = "ns_startup_args_dedi.txt";
+/// lines of output kept for the console, older ones are dropped
+const CONSOLE_SIZE: usize = 2000;
+/// how often a watched server is checked on
+const WATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// how the launcher is started when the config doesn't say
+pub fn default_command() -> Vec<String> {
+    if cfg!(windows) {
+        vec!["NorthstarLauncher.exe".to_owned()]
+    } else {
+        vec!["wine".to_owned(), "NorthstarLauncher.exe".to_owned()]
+    }
+}
+
+/// the install and profile a dedicated server runs from and how it is started
+#[derive(Debug, Clone)]
+pub struct DedicatedServer {
+    pub install: GameInstall,
+    /// the program and its arguments, `-dedicated -profile=<profile>` is added after them
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModState {
+    Enabled,
+    Disabled,
+    /// only in the client profile, copied over when it's enabled
+    NotCopied,
+}
+
+impl std::fmt::Display for ModState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            ModState::Enabled => "enabled",
+            ModState::Disabled => "disabled",
+            ModState::NotCopied => "not copied",
+        })
+    }
+}
+
+/// a mod that can be part of the server's mod set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerSideMod {
+    pub name: String,
+    pub version: String,
+    pub state: ModState,
+}
+
+impl DedicatedServer {
+    pub fn args_path(&self) -> PathBuf {
+        self.install.root.join(ARGS_NAME)
+    }
+
+    pub fn autoexec_path(&self) -> PathBuf {
+        AutoExecKind::Server.path(&self.install)
+    }
+
+    /// the same server seen through the fs an operation is given so previews and the journal
+    /// pick up its changes
+    pub fn through(&self, install: &GameInstall) -> Self {
+        Self {
+            install: GameInstall {
+                fs: install.fs.clone(),
+                ..self.install.clone()
+            },
+            ..self.clone()
+        }
+    }
+
+    /// a missing file means the server starts without extra arguments
+    pub fn args(&self) -> AnyResult<String> {
+        let path = self.args_path();
+        match self.install.fs.read_to_string(&path) {
+            Result::Ok(args) => Ok(args.trim().to_owned()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(err) => Err(err).with_context(|| format!("couldn't read {}", path.display())),
+        }
+    }
+
+    pub fn set_args(&self, args: &str) -> AnyResult<()> {
+        self.install
+            .write(&self.args_path(), args.trim().as_bytes())
+    }
+
+    /// the mods of the dedicated profile and the ones only the client profile has, by name
+    pub fn mod_set(&self, client: &GameInstall) -> AnyResult<Vec<ServerSideMod>> {
+        let installed = installed_mods(&self.install)?;
+        let mut mods = installed
+            .iter()
+            .map(|nsmod| ServerSideMod {
+                name: nsmod.mod_json.name.clone(),
+                version: nsmod.mod_json.version.clone(),
+                state: if nsmod.enabled {
+                    ModState::Enabled
+                } else {
+                    ModState::Disabled
+                },
+            })
+            .collect::<Vec<ServerSideMod>>();
+        mods.extend(
+            installed_mods(client)?
+                .into_iter()
+                .filter(|nsmod| {
+                    !installed
+                        .iter()
+                        .any(|other| other.mod_json.name == nsmod.mod_json.name)
+                })
+                .map(|nsmod| ServerSideMod {
+                    name: nsmod.mod_json.name,
+                    version: nsmod.mod_json.version,
+                    state: ModState::NotCopied,
+                }),
+        );
+        mods.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(mods)
+    }
+
+    /// a mod only the client profile has is copied over first, a new profile also gets
+    /// northstar's own mods since the server can't start without them
+    pub fn set_enabled(&self, client: &GameInstall, name: &str, enabled: bool) -> AnyResult<()> {
+        let installed = installed_mods(&self.install)?;
+        let is_installed = installed.iter().any(|nsmod| nsmod.mod_json.name == name);
+        if !is_installed && !enabled {
+            return Ok(());
+        }
+
+        if !is_installed {
+            let client_mods = installed_mods(client)?;
+            let copies = client_mods.iter().filter(|nsmod| {
+                nsmod.mod_json.name == name
+                    || (installed.is_empty() && nsmod.mod_json.name.starts_with(CORE_PREFIX))
+            });
+            let mut copied = false;
+            for nsmod in copies {
+                let dir = nsmod.path.file_name().context("mods have a dir name")?;
+                copy_mod(
+                    &self.through_client(client),
+                    &nsmod.path,
+                    &self.install.mods_dir().join(dir),
+                )?;
+                copied |= nsmod.mod_json.name == name;
+            }
+            if !copied {
+                bail!("{name} isn't in {}", client.profile);
+            }
+        }
+
+        self.install.set_mod_enabled(name, enabled)
+    }
+
+    /// the client install with this server's fs, copies read and write through the same one
+    fn through_client(&self, client: &GameInstall) -> GameInstall {
+        GameInstall {
+            fs: self.install.fs.clone(),
+            ..client.clone()
+        }
+    }
+
+    pub fn start(&self) -> AnyResult<ServerProcess> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .context("the dedicated server command is empty")?;
+        // the launcher is usually named relative to the game dir
+        let local = self.install.root.join(program);
+        let program = if local.is_file() {
+            local
+        } else {
+            PathBuf::from(program)
+        };
+
+        let child = Command::new(&program)
+            .args(args)
+            .arg("-dedicated")
+            .arg(format!("-profile={}", self.install.profile))
+            .current_dir(&self.install.root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("couldn't start {}", program.display()))?;
+
+        Ok(ServerProcess::new(child))
+    }
+}
+
+/// a profile that doesn't exist yet has no mods
+fn installed_mods(install: &GameInstall) -> AnyResult<Vec<InstalledModPartial>> {
+    if !install.fs.exists(&install.mods_dir()) {
+        return Ok(Vec::new());
+    }
+
+    Ok(reload_mods(install)?.0)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessStatus {
+    Running,
+    /// no code when it was killed
+    Exited(Option<i32>),
+}
+
+impl std::fmt::Display for ProcessStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessStatus::Running => write!(f, "running"),
+            ProcessStatus::Exited(Some(code)) => write!(f, "exited with {code}"),
+            ProcessStatus::Exited(None) => write!(f, "stopped"),
+        }
+    }
+}
+
+/// a started dedicated server, its output is collected on background threads
+pub struct ServerProcess {
+    child: Arc<Mutex<Child>>,
+    console: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl ServerProcess {
+    fn new(mut child: Child) -> Self {
+        let console = Arc::new(Mutex::new(VecDeque::new()));
+        if let Some(stdout) = child.stdout.take() {
+            tail(stdout, console.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tail(stderr, console.clone());
+        }
+
+        Self {
+            child: Arc::new(Mutex::new(child)),
+            console,
+        }
+    }
+
+    fn child(&self) -> MutexGuard<'_, Child> {
+        self.child.lock().expect("server process poisoned")
+    }
+
+    pub fn status(&self) -> ProcessStatus {
+        match self.child().try_wait() {
+            Result::Ok(None) => ProcessStatus::Running,
+            Result::Ok(Some(status)) => ProcessStatus::Exited(status.code()),
+            Err(err) => {
+                log::warn!("couldn't check on the dedicated server: {err}");
+                ProcessStatus::Exited(None)
+            }
+        }
+    }
+
+    /// calls `on_exit` from a background thread once the server exited or was stopped
+    pub fn on_exit(&self, on_exit: impl FnOnce(ProcessStatus) + Send + 'static) {
+        let child = self.child.clone();
+        thread::spawn(move || loop {
+            thread::sleep(WATCH_INTERVAL);
+            let status = child.lock().expect("server process poisoned").try_wait();
+            match status {
+                Result::Ok(None) => {}
+                Result::Ok(Some(status)) => break on_exit(ProcessStatus::Exited(status.code())),
+                Err(err) => break log::warn!("couldn't check on the dedicated server: {err}"),
+            }
+        });
+    }
+
+    /// the server is killed, northstar can't be asked to shut down from outside
+    pub fn stop(&self) -> AnyResult<()> {
+        let mut child = self.child();
+        if child.try_wait()?.is_none() {
+            child.kill().context("couldn't stop the dedicated server")?;
+            child.wait()?;
+        }
+
+        Ok(())
+    }
+
+    /// the newest `count` lines of output
+    pub fn console(&self, count: usize) -> Vec<String> {
+        let console = self.console.lock().expect("console poisoned");
+        console
+            .iter()
+            .skip(console.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+}
+
+/// a server left running would hold on to its port after drydoct is gone
+impl Drop for ServerProcess {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            log::warn!("{err:#}");
+        }
+    }
+}
+
+/// pushes every line of `output` into the console, the game doesn't always write utf-8
+fn tail(output: impl Read + Send + 'static, console: Arc<Mutex<VecDeque<String>>>) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = Vec::new();
+        while reader
+            .read_until(b'\n', &mut line)
+            .is_ok_and(|read| read > 0)
+        {
+            let text = String::from_utf8_lossy(&line).trim_end().to_owned();
+            line.clear();
+
+            let mut console = console.lock().expect("console poisoned");
+            console.push_back(text);
+            if console.len() > CONSOLE_SIZE {
+                console.pop_front();
+            }
+        }
+    });
+}
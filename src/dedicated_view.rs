@@ -0,0 +1,400 @@
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event::{self, KeyCode};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    autoexec::AutoExec,
+    dedicated::{ModState, ProcessStatus, ServerProcess, ServerSideMod},
+    hooks::{ChangeHooks, HookDetails, HookEvent},
+    keymap::{Action, KeyContext, Keymap},
+    theme::theme,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Pane {
+    #[default]
+    Mods,
+    /// the launch args then every line of the autoexec
+    Config,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Args,
+    Line(usize),
+    /// a line that is added before the given index
+    NewLine(usize),
+}
+
+#[derive(Default)]
+pub struct Dedicated {
+    mods: Vec<ServerSideMod>,
+    args: String,
+    autoexec: Option<AutoExec>,
+    pane: Pane,
+    selected_mod: usize,
+    /// 0 is the launch args, the autoexec lines come after
+    selected_line: usize,
+    /// what is being typed, only set while editing
+    editing: Option<(Edit, String)>,
+    process: Option<ServerProcess>,
+}
+
+impl Dedicated {
+    fn reload(&mut self, state: &GlobalState) -> AnyResult<()> {
+        let server = &state.dedicated;
+        self.mods = server.mod_set(&state.install)?;
+        self.args = server.args()?;
+        self.autoexec = Some(AutoExec::load(&server.install, server.autoexec_path())?);
+        self.selected_mod = self.selected_mod.min(self.mods.len().saturating_sub(1));
+        self.selected_line = self.selected_line.min(self.lines().len());
+
+        Ok(())
+    }
+
+    fn lines(&self) -> &[String] {
+        self.autoexec
+            .as_ref()
+            .map(AutoExec::lines)
+            .unwrap_or_default()
+    }
+
+    fn toggle_mod(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(nsmod) = self.mods.get(self.selected_mod) else {
+            return Ok(());
+        };
+
+        let name = nsmod.name.clone();
+        let enabled = nsmod.state != ModState::Enabled;
+        let server = state.dedicated.clone();
+        let client = state.install.clone();
+        let description = format!(
+            "{} {name} for {}",
+            if enabled { "enable" } else { "disable" },
+            server.install.profile
+        );
+        let hooks = ChangeHooks::toggle(&state.dedicated.install, name.clone(), enabled);
+        if state.apply_with(description, hooks, move |install| {
+            server.through(install).set_enabled(&client, &name, enabled)
+        })? {
+            self.reload(state)?;
+        }
+
+        Ok(())
+    }
+
+    fn start_editing(&mut self, state: &mut GlobalState, add: bool) {
+        let edit = match (self.selected_line, add) {
+            // new lines go after the selected one, the args row can't be duplicated
+            (line, true) => Edit::NewLine(line),
+            (0, false) => Edit::Args,
+            (line, false) => Edit::Line(line - 1),
+        };
+        let text = match edit {
+            Edit::Args => self.args.clone(),
+            Edit::Line(index) => self.lines().get(index).cloned().unwrap_or_default(),
+            Edit::NewLine(_) => String::new(),
+        };
+
+        self.editing = Some((edit, text));
+        state.is_typing = true;
+    }
+
+    fn commit_edit(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        state.is_typing = false;
+        let Some((edit, text)) = self.editing.take() else {
+            return Ok(());
+        };
+
+        let applied = match edit {
+            Edit::Args => {
+                let server = state.dedicated.clone();
+                state.apply(
+                    format!("set the launch args of {}", server.install.profile),
+                    move |install| server.through(install).set_args(&text),
+                )?
+            }
+            Edit::Line(_) | Edit::NewLine(_) => {
+                let Some(mut autoexec) = self.autoexec.clone() else {
+                    return Ok(());
+                };
+                match edit {
+                    Edit::NewLine(index) => autoexec.insert_line(index, &text),
+                    Edit::Line(index) => autoexec.set_line(index, &text),
+                    Edit::Args => unreachable!("handled above"),
+                }
+                self.save_autoexec(autoexec, state)?
+            }
+        };
+        if applied {
+            self.reload(state)?;
+            if let Edit::NewLine(index) = edit {
+                self.selected_line = index + 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove_line(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let (Some(index), Some(mut autoexec)) =
+            (self.selected_line.checked_sub(1), self.autoexec.clone())
+        else {
+            return Ok(());
+        };
+
+        if autoexec.remove_line(index).is_some() && self.save_autoexec(autoexec, state)? {
+            self.reload(state)?;
+        }
+
+        Ok(())
+    }
+
+    fn save_autoexec(&self, autoexec: AutoExec, state: &mut GlobalState) -> AnyResult<bool> {
+        // the path is absolute so it lands in the dedicated install whatever it is given
+        state.apply(
+            format!(
+                "edit the server autoexec of {}",
+                state.dedicated.install.profile
+            ),
+            move |install| autoexec.save(install),
+        )
+    }
+
+    fn start_or_stop(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let profile = &state.dedicated.install.profile;
+        match &self.process {
+            Some(process) if process.status() == ProcessStatus::Running => {
+                process.stop()?;
+                state
+                    .notifier
+                    .info(format!("stopped the dedicated server of {profile}"));
+            }
+            _ => {
+                let install = state.dedicated.install.clone();
+                state
+                    .hooks
+                    .before(HookDetails::new(HookEvent::PreLaunch, &install, Vec::new()))?;
+                let process = state.dedicated.start()?;
+                let hooks = state.hooks.clone();
+                process.on_exit(move |status| {
+                    let exit_code = match status {
+                        ProcessStatus::Exited(code) => code,
+                        ProcessStatus::Running => None,
+                    };
+                    hooks.after(HookDetails {
+                        exit_code,
+                        ..HookDetails::new(HookEvent::PostExit, &install, Vec::new())
+                    });
+                });
+                self.process = Some(process);
+                state
+                    .notifier
+                    .info(format!("started a dedicated server from {profile}"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn pane_block(&self, pane: Pane, title: String) -> Block<'_> {
+        Block::default()
+            .borders(Borders::all())
+            .title(title)
+            .title_style(Style::default().fg(theme().accent))
+            .border_style(Style::default().fg(if self.pane == pane {
+                theme().accent
+            } else {
+                theme().border
+            }))
+            .bg(theme().background)
+    }
+
+    fn render_mods(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let rows = self
+            .mods
+            .iter()
+            .map(|nsmod| {
+                Row::new(vec![
+                    Cell::from(nsmod.name.as_str()).fg(theme().text),
+                    Cell::from(nsmod.version.as_str()).fg(theme().muted),
+                    Cell::from(nsmod.state.to_string()).fg(match nsmod.state {
+                        ModState::Enabled => theme().enabled,
+                        ModState::Disabled => theme().disabled,
+                        ModState::NotCopied => theme().muted,
+                    }),
+                ])
+            })
+            .collect::<Vec<Row<'_>>>();
+        let enabled = self
+            .mods
+            .iter()
+            .filter(|nsmod| nsmod.state == ModState::Enabled)
+            .count();
+
+        frame.render_stateful_widget(
+            Table::new(rows)
+                .header(
+                    Row::new(vec!["mod", "version", "server"])
+                        .style(Style::default().fg(theme().accent)),
+                )
+                .widths(&[
+                    Constraint::Percentage(50),
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(30),
+                ])
+                .block(self.pane_block(Pane::Mods, format!("mod set ({enabled} enabled)")))
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            rect,
+            &mut TableState::default()
+                .with_selected((self.pane == Pane::Mods).then_some(self.selected_mod)),
+        );
+    }
+
+    fn render_config(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let editing = |edit: Edit| {
+            self.editing
+                .as_ref()
+                .filter(|(editing, _)| *editing == edit)
+                .map(|(_, text)| {
+                    Line::styled(format!("{text}_"), Style::default().fg(theme().accent))
+                })
+        };
+
+        let mut lines = vec![editing(Edit::Args).unwrap_or_else(|| {
+            Line::from(vec![
+                Span::raw("launch args ").fg(theme().muted),
+                Span::raw(self.args.as_str()),
+            ])
+        })];
+        for (index, line) in self.lines().iter().enumerate() {
+            lines.extend(editing(Edit::NewLine(index)));
+            lines.push(editing(Edit::Line(index)).unwrap_or_else(|| Line::raw(line.as_str())));
+        }
+        lines.extend(editing(Edit::NewLine(self.lines().len())));
+
+        let items = lines
+            .into_iter()
+            .map(|line| ListItem::new(line).fg(theme().text))
+            .collect::<Vec<ListItem<'_>>>();
+        let selected = match self.editing {
+            Some((Edit::NewLine(index), _)) => index + 1,
+            _ => self.selected_line,
+        };
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(self.pane_block(Pane::Config, "launch args and server autoexec".to_owned()))
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            rect,
+            &mut ListState::default()
+                .with_selected((self.pane == Pane::Config).then_some(selected)),
+        );
+    }
+
+    fn render_console(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let height = rect.height.saturating_sub(2) as usize;
+        let (status, lines) = match &self.process {
+            Some(process) => (process.status().to_string(), process.console(height)),
+            None => ("not started".to_owned(), Vec::new()),
+        };
+
+        frame.render_widget(
+            Paragraph::new(lines.into_iter().map(Line::raw).collect::<Vec<Line<'_>>>()).block(
+                Block::default()
+                    .borders(Borders::all())
+                    .title(format!("console - {status}"))
+                    .title_style(Style::default().fg(theme().accent))
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            rect,
+        );
+    }
+}
+
+impl TabUi for Dedicated {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let layout = Layout::new()
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(rect);
+        let top = Layout::new()
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .direction(Direction::Horizontal)
+            .split(layout[0]);
+
+        self.render_mods(frame, top[0]);
+        self.render_config(frame, top[1]);
+        self.render_console(frame, layout[1]);
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        if self.editing.is_some() {
+            return vec!["save - enter".to_owned(), "cancel - esc".to_owned()];
+        }
+
+        keymap.hints(KeyContext::Dedicated)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        let event::Event::Key(key_event) = crosstermevent else {
+            return Ok(());
+        };
+        if key_event.kind != event::KeyEventKind::Press {
+            return Ok(());
+        }
+
+        if let Some((_, text)) = self.editing.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Backspace => _ = text.pop(),
+                KeyCode::Enter => self.commit_edit(state)?,
+                KeyCode::Esc => {
+                    self.editing = None;
+                    state.is_typing = false;
+                }
+                _ => {}
+            }
+
+            return Ok(());
+        }
+
+        if state.is_typing {
+            return Ok(());
+        }
+
+        match (
+            state.keymap.action(KeyContext::Dedicated, &key_event),
+            self.pane,
+        ) {
+            (Some(Action::Left), _) => self.pane = Pane::Mods,
+            (Some(Action::Right), _) => self.pane = Pane::Config,
+            (Some(Action::Down), Pane::Mods) => {
+                self.selected_mod = self.mods.len().saturating_sub(1).min(self.selected_mod + 1)
+            }
+            (Some(Action::Up), Pane::Mods) => {
+                self.selected_mod = self.selected_mod.saturating_sub(1)
+            }
+            (Some(Action::Down), Pane::Config) => {
+                self.selected_line = self.lines().len().min(self.selected_line + 1)
+            }
+            (Some(Action::Up), Pane::Config) => {
+                self.selected_line = self.selected_line.saturating_sub(1)
+            }
+            (Some(Action::Toggle), Pane::Mods) => self.toggle_mod(state)?,
+            (Some(Action::Edit), Pane::Config) => self.start_editing(state, false),
+            (Some(Action::Add), Pane::Config) => self.start_editing(state, true),
+            (Some(Action::Delete), Pane::Config) => self.remove_line(state)?,
+            (Some(Action::StartStop), _) => self.start_or_stop(state)?,
+            (Some(Action::Reload), _) => self.reload(state)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.editing = None;
+        self.reload(state)
+    }
+}
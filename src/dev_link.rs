@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use thermite::model::ModJSON;
+
+use crate::{install::GameInstall, local_install::parse_mod_json};
+
+/// left in copied dev links, holds the folder the copy is kept in sync with
+pub const MARKER_NAME: &str = ".drydoct-dev-link";
+/// never worth looking at and big in a git checkout
+const IGNORED_DIRS: [&str; 2] = [".git", "target"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Symlink,
+    /// a copy that is synced again whenever the folder changes
+    Copy,
+}
+
+/// validates the folder and links it into the profile's mods as `dir`,
+/// copies when the platform won't make a link
+pub fn link(install: &GameInstall, source: &Path, dir: &str) -> AnyResult<LinkKind> {
+    validate(install, source)?;
+    let mod_dir = install.mods_dir().join(dir);
+    if install.fs.exists(&mod_dir) {
+        bail!("{dir} is already installed in {}", install.profile);
+    }
+
+    install
+        .fs
+        .create_dir_all(&install.mods_dir())
+        .with_context(|| format!("couldn't create {}", install.mods_dir().display()))?;
+    match install.fs.symlink_dir(source, &mod_dir) {
+        io::Result::Ok(()) => Ok(LinkKind::Symlink),
+        Err(err) => {
+            log::warn!("couldn't link {dir}, copying it instead: {err}");
+            sync_copy(install, source, &mod_dir)?;
+            Ok(LinkKind::Copy)
+        }
+    }
+}
+
+/// the folder an installed mod is linked to, if it is a dev link
+pub fn linked_source(install: &GameInstall, mod_dir: &Path) -> Option<PathBuf> {
+    let link = install.fs.read_link(mod_dir).ok();
+    // a link into the mod store is an installed mod like any other
+    if link.as_ref().is_some_and(|target| {
+        install
+            .mod_store
+            .as_ref()
+            .is_some_and(|store| target.starts_with(store))
+    }) {
+        return None;
+    }
+
+    link.or_else(|| {
+        install
+            .fs
+            .read_to_string(&mod_dir.join(MARKER_NAME))
+            .ok()
+            .map(|source| PathBuf::from(source.trim()))
+    })
+}
+
+pub fn validate(install: &GameInstall, source: &Path) -> AnyResult<ModJSON> {
+    let name = source
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let contents = install
+        .fs
+        .read(&source.join("mod.json"))
+        .with_context(|| format!("{name} has no mod.json"))?;
+
+    parse_mod_json(&name, &contents)
+}
+
+/// replaces the copy with whatever is in the folder now
+fn sync_copy(install: &GameInstall, source: &Path, mod_dir: &Path) -> AnyResult<()> {
+    if install.fs.exists(mod_dir) {
+        install
+            .fs
+            .remove_dir_all(mod_dir)
+            .with_context(|| format!("couldn't remove the old {}", mod_dir.display()))?;
+    }
+
+    for file in source_files(install, source)? {
+        let contents = install
+            .fs
+            .read(&file)
+            .with_context(|| format!("couldn't read {}", file.display()))?;
+        let relative = file
+            .strip_prefix(source)
+            .expect("files are under the source");
+        install.write(&mod_dir.join(relative), &contents)?;
+    }
+
+    install.write(
+        &mod_dir.join(MARKER_NAME),
+        source.display().to_string().as_bytes(),
+    )
+}
+
+/// every file under `dir` that isn't in an ignored dir
+fn source_files(install: &GameInstall, dir: &Path) -> AnyResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for entry in install
+        .fs
+        .read_dir(dir)
+        .with_context(|| format!("couldn't read {}", dir.display()))?
+    {
+        let ignored = entry
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| IGNORED_DIRS.contains(&name));
+        match (entry.is_dir, ignored) {
+            (true, false) => files.extend(source_files(install, &entry.path)?),
+            (true, true) => {}
+            (false, _) => files.push(entry.path),
+        }
+    }
+
+    Ok(files)
+}
+
+/// a linked mod whose folder changed
+#[derive(Debug)]
+pub struct Reload {
+    pub dir: String,
+    pub source: PathBuf,
+    pub mod_dir: PathBuf,
+    /// the new mod.json or why it isn't valid anymore
+    pub result: AnyResult<ModJSON>,
+}
+
+impl Reload {
+    /// copies the folder again when the link is a copy, meant to run through the journal so
+    /// the sync can be undone like any other change
+    pub fn sync(&self, install: &GameInstall) -> AnyResult<()> {
+        if install.fs.read_link(&self.mod_dir).is_ok() {
+            return Ok(());
+        }
+
+        sync_copy(install, &self.source, &self.mod_dir)
+    }
+}
+
+/// watches the folders of the dev links in the current profile, notify sends what changed from
+/// its own thread so the ui only drains the channel
+pub struct DevWatcher {
+    /// dropping it stops the watching
+    watcher: Option<RecommendedWatcher>,
+    sender: Sender<PathBuf>,
+    changes: Receiver<PathBuf>,
+    /// watched too so links are picked up as they come and go, a profile switch changes it
+    mods_dir: Option<PathBuf>,
+    /// the linked folder of every mod dir that is a dev link
+    links: HashMap<PathBuf, PathBuf>,
+}
+
+impl Default for DevWatcher {
+    fn default() -> Self {
+        let (sender, changes) = mpsc::channel();
+
+        Self {
+            watcher: None,
+            sender,
+            changes,
+            mods_dir: None,
+            links: HashMap::new(),
+        }
+    }
+}
+
+impl DevWatcher {
+    /// the links that changed since the last tick, the links are looked up again when the mods
+    /// dir itself changed
+    pub fn tick(&mut self, install: &GameInstall) -> Vec<Reload> {
+        if self.mods_dir.as_ref() != Some(&install.mods_dir()) {
+            self.watch(install);
+        }
+
+        let changed = self.changes.try_iter().collect::<Vec<PathBuf>>();
+        let mods_dir = install.mods_dir();
+        if changed
+            .iter()
+            .any(|path| path.parent() == Some(mods_dir.as_path()))
+        {
+            self.watch(install);
+        }
+
+        self.reload(install, &changed)
+    }
+
+    /// finds the dev links in the profile and starts watching their folders over
+    pub fn watch(&mut self, install: &GameInstall) {
+        let mods_dir = install.mods_dir();
+        self.links = install
+            .fs
+            .read_dir(&mods_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| entry.is_dir)
+            .filter_map(|entry| Some((entry.path.clone(), linked_source(install, &entry.path)?)))
+            .collect();
+        self.mods_dir = Some(mods_dir.clone());
+
+        let sender = self.sender.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Result::Ok(event) = event else {
+                return;
+            };
+            if !matches!(event.kind, EventKind::Access(_)) {
+                event
+                    .paths
+                    .into_iter()
+                    .for_each(|path| _ = sender.send(path));
+            }
+        });
+        self.watcher = match watcher {
+            Result::Ok(mut watcher) => {
+                let watched = [(&mods_dir, RecursiveMode::NonRecursive)]
+                    .into_iter()
+                    .chain(
+                        self.links
+                            .values()
+                            .map(|source| (source, RecursiveMode::Recursive)),
+                    );
+                for (path, mode) in watched {
+                    if let Err(err) = watcher.watch(path, mode) {
+                        log::warn!("couldn't watch {} for changes: {err}", path.display());
+                    }
+                }
+                Some(watcher)
+            }
+            Err(err) => {
+                log::warn!("couldn't watch the dev links for changes: {err}");
+                None
+            }
+        };
+    }
+
+    /// revalidates every link with a changed path in its folder
+    pub fn reload(&self, install: &GameInstall, changed: &[PathBuf]) -> Vec<Reload> {
+        let mut reloads = self
+            .links
+            .iter()
+            .filter(|(_, source)| {
+                changed.iter().any(|path| {
+                    path.strip_prefix(source).is_ok_and(|relative| {
+                        !relative.components().any(|component| {
+                            IGNORED_DIRS
+                                .iter()
+                                .any(|ignored| component.as_os_str() == *ignored)
+                        })
+                    })
+                })
+            })
+            .map(|(mod_dir, source)| Reload {
+                dir: mod_dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                source: source.clone(),
+                mod_dir: mod_dir.clone(),
+                result: validate(install, source),
+            })
+            .collect::<Vec<Reload>>();
+        reloads.sort_by(|a, b| a.dir.cmp(&b.dir));
+
+        reloads
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+
+use crate::{
+    background::Background,
+    game_fs::{FsEntry, GameFs},
+    install::GameInstall,
+};
+
+/// how much space a mod or plugin takes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntrySize {
+    /// the folder or file name
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    /// the part of `size` other profiles have hard links to
+    pub shared: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileUsage {
+    pub profile: String,
+    pub mods: Vec<EntrySize>,
+    pub plugins: Vec<EntrySize>,
+    /// logs, saves and anything else that isn't a mod or plugin
+    pub other: u64,
+    /// hard linked files only once
+    pub total: u64,
+}
+
+/// every profile of an install
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UsageReport {
+    pub profiles: Vec<ProfileUsage>,
+    /// what the profiles take together, hard links between them counted once
+    pub total: u64,
+}
+
+impl ProfileUsage {
+    pub fn entries(&self) -> impl Iterator<Item = &EntrySize> {
+        self.mods.iter().chain(&self.plugins)
+    }
+}
+
+impl UsageReport {
+    pub fn profile(&self, profile: &str) -> Option<&ProfileUsage> {
+        self.profiles.iter().find(|usage| usage.profile == profile)
+    }
+
+    /// how much the profiles would take without the hard links between them
+    pub fn saved(&self) -> u64 {
+        self.profiles
+            .iter()
+            .map(|usage| usage.total)
+            .sum::<u64>()
+            .saturating_sub(self.total)
+    }
+}
+
+/// hard links are told apart by their file id, files without one only by their path
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FileKey {
+    Id(u64, u64),
+    Path(PathBuf),
+}
+
+type Files = Vec<(FileKey, u64)>;
+
+/// what a profile is made of before anything is added up
+struct ScannedProfile {
+    profile: String,
+    mods: Vec<(String, PathBuf, Files)>,
+    plugins: Vec<(String, PathBuf, Files)>,
+    other: Files,
+}
+
+/// walks every profile of the install, this reads every file's metadata so it belongs on another
+/// thread for big installs
+pub fn measure(install: &GameInstall) -> AnyResult<UsageReport> {
+    let scanned = install
+        .profiles()?
+        .iter()
+        .map(|profile| scan_profile(&install.with_profile(profile)))
+        .collect::<AnyResult<Vec<ScannedProfile>>>()?;
+
+    // every file with its size and the profiles that have it
+    let mut owners: HashMap<&FileKey, (u64, BTreeSet<usize>)> = HashMap::new();
+    for (index, profile) in scanned.iter().enumerate() {
+        let entries = profile.mods.iter().chain(&profile.plugins);
+        for (key, len) in entries
+            .flat_map(|(_, _, files)| files)
+            .chain(&profile.other)
+        {
+            owners
+                .entry(key)
+                .or_insert((*len, BTreeSet::new()))
+                .1
+                .insert(index);
+        }
+    }
+    let is_shared = |key: &FileKey| owners.get(key).is_some_and(|(_, owners)| owners.len() > 1);
+    let sizes = |entries: &[(String, PathBuf, Files)]| {
+        entries
+            .iter()
+            .map(|(name, path, files)| {
+                let unique = unique(files);
+                EntrySize {
+                    name: name.clone(),
+                    path: path.clone(),
+                    size: unique.values().sum(),
+                    shared: unique
+                        .iter()
+                        .filter(|(key, _)| is_shared(key))
+                        .map(|(_, len)| len)
+                        .sum(),
+                }
+            })
+            .collect::<Vec<EntrySize>>()
+    };
+
+    let profiles = scanned
+        .iter()
+        .map(|profile| {
+            let entries = profile.mods.iter().chain(&profile.plugins);
+            let all = entries
+                .flat_map(|(_, _, files)| files.iter().cloned())
+                .chain(profile.other.iter().cloned())
+                .collect::<Files>();
+            ProfileUsage {
+                profile: profile.profile.clone(),
+                mods: sizes(&profile.mods),
+                plugins: sizes(&profile.plugins),
+                other: unique(&profile.other).values().sum(),
+                total: unique(&all).values().sum(),
+            }
+        })
+        .collect();
+
+    Ok(UsageReport {
+        profiles,
+        total: owners.values().map(|(len, _)| len).sum(),
+    })
+}
+
+fn unique(files: &Files) -> HashMap<&FileKey, u64> {
+    files.iter().map(|(key, len)| (key, *len)).collect()
+}
+
+/// mods are the folders in `mods`, plugins everything in `plugins`
+fn scan_profile(install: &GameInstall) -> AnyResult<ScannedProfile> {
+    let fs = install.fs.as_ref();
+    let mut scanned = ScannedProfile {
+        profile: install.profile.clone(),
+        mods: Vec::new(),
+        plugins: Vec::new(),
+        other: Vec::new(),
+    };
+    let (mods_dir, plugins_dir) = (install.mods_dir(), install.plugins_dir());
+
+    for entry in read_dir(fs, &install.profile_dir())? {
+        if entry.path != mods_dir && entry.path != plugins_dir {
+            files(fs, &entry.path, entry.is_dir, &mut scanned.other)?;
+            continue;
+        }
+
+        // the same paths the mods tab shows
+        let dir = fs
+            .canonicalize(&entry.path)
+            .with_context(|| format!("couldn't find {}", entry.path.display()))?;
+        for child in read_dir(fs, &dir)? {
+            let mut child_files = Vec::new();
+            files(fs, &child.path, child.is_dir, &mut child_files)?;
+            match child.path.file_name().map(|name| name.to_string_lossy()) {
+                // loose files in the mods folder aren't mods
+                Some(_) if entry.path == mods_dir && !child.is_dir => {
+                    scanned.other.extend(child_files)
+                }
+                Some(name) => {
+                    let measured = (name.into_owned(), child.path, child_files);
+                    if entry.path == mods_dir {
+                        scanned.mods.push(measured);
+                    } else {
+                        scanned.plugins.push(measured);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    Ok(scanned)
+}
+
+fn read_dir(fs: &dyn GameFs, dir: &Path) -> AnyResult<Vec<FsEntry>> {
+    fs.read_dir(dir)
+        .with_context(|| format!("couldn't read {}", dir.display()))
+}
+
+/// every file under `path`, dev links are followed so their source is counted
+fn files(fs: &dyn GameFs, path: &Path, is_dir: bool, files_found: &mut Files) -> AnyResult<()> {
+    if is_dir {
+        for entry in read_dir(fs, path)? {
+            files(fs, &entry.path, entry.is_dir, files_found)?;
+        }
+        return Ok(());
+    }
+
+    // a file removed while measuring just isn't counted
+    if let Result::Ok(metadata) = fs.metadata(path) {
+        let key = match metadata.file_id {
+            Some((device, inode)) => FileKey::Id(device, inode),
+            None => FileKey::Path(path.to_path_buf()),
+        };
+        files_found.push((key, metadata.len));
+    }
+
+    Ok(())
+}
+
+/// measures on another thread so big profiles don't hold up the ui, the last report is kept
+/// until the next one is done
+#[derive(Default)]
+pub struct DiskUsage {
+    report: Option<Arc<UsageReport>>,
+    scan: Background<UsageReport>,
+    /// something changed while measuring so it has to be done again
+    stale: bool,
+}
+
+impl DiskUsage {
+    pub fn report(&self) -> Option<Arc<UsageReport>> {
+        self.report.clone()
+    }
+
+    pub fn is_measuring(&self) -> bool {
+        self.scan.is_running()
+    }
+
+    pub fn measure(&mut self, install: &GameInstall) {
+        if self.scan.is_running() {
+            self.stale = true;
+            return;
+        }
+
+        let install = install.clone();
+        self.scan.start(move || measure(&install));
+    }
+
+    /// whether a new report came in, or why measuring failed
+    pub fn poll(&mut self, install: &GameInstall) -> AnyResult<bool> {
+        let Some(report) = self.scan.poll() else {
+            return Ok(false);
+        };
+
+        if std::mem::take(&mut self.stale) {
+            self.measure(install);
+        }
+        self.report = Some(Arc::new(report.context("couldn't measure the disk usage")?));
+
+        Ok(true)
+    }
+}
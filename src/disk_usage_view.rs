@@ -0,0 +1,240 @@
+use std::{rc::Rc, sync::Arc};
+
+use anyhow::{bail, Ok, Result as AnyResult};
+use crossterm::event;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    disk_usage::UsageReport,
+    keymap::{Action, KeyContext, Keymap},
+    mod_store::{Deduplicated, ModStore},
+    package_cache::format_size,
+    theme::theme,
+};
+
+/// the longest name a bar gets, the bars need the room more
+const LABEL_WIDTH: usize = 24;
+
+/// the total of every profile and what takes the most space in the selected one
+#[derive(Default)]
+pub struct DiskUsageView {
+    report: Option<Arc<UsageReport>>,
+    measuring: bool,
+    /// a profile in the report
+    selected: usize,
+    /// how many mods are in the store when shared mods are on
+    stored: Option<usize>,
+}
+
+impl DiskUsageView {
+    fn reload(&mut self, state: &GlobalState) {
+        let report = state.disk_usage.report();
+        // start on the profile that's in use the first time there's something to show
+        if self.report.is_none() {
+            self.selected = report
+                .as_ref()
+                .and_then(|report| {
+                    report
+                        .profiles
+                        .iter()
+                        .position(|usage| usage.profile == state.install.profile)
+                })
+                .unwrap_or_default();
+        }
+        self.report = report;
+        self.measuring = state.disk_usage.is_measuring();
+        self.stored = ModStore::of(&state.install).map(|store| store.entries(&state.install).len());
+    }
+
+    /// links the mods of every profile to the store, what was freed shows up once it's measured
+    fn deduplicate(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(store) = ModStore::of(&state.install) else {
+            bail!("turn on shared_mods in the config to share mods between profiles");
+        };
+
+        let deduplicated = Rc::new(std::cell::Cell::new(Deduplicated::default()));
+        let result = deduplicated.clone();
+        if state.apply("share the mods of every profile", move |install| {
+            result.set(store.deduplicate(install)?);
+            Ok(())
+        })? {
+            let Deduplicated { mods, saved } = deduplicated.get();
+            state.notifier.info(format!(
+                "linked {mods} mods to the mod store, {} freed",
+                format_size(saved)
+            ));
+            self.reload(state);
+        }
+
+        Ok(())
+    }
+
+    fn render_profiles(&self, frame: &mut Frame<'_>, rect: Rect, report: &UsageReport) {
+        let rows = report
+            .profiles
+            .iter()
+            .map(|usage| {
+                Row::new(vec![
+                    Cell::from(usage.profile.as_str()).fg(theme().text),
+                    Cell::from(format_size(usage.total)).fg(theme().muted),
+                ])
+            })
+            .chain([
+                Row::new(vec![
+                    Cell::from("every profile").fg(theme().accent),
+                    Cell::from(format_size(report.total)).fg(theme().accent),
+                ]),
+                Row::new(vec![
+                    Cell::from("hard links save").fg(theme().muted),
+                    Cell::from(format_size(report.saved())).fg(theme().muted),
+                ]),
+            ])
+            .chain(self.stored.map(|stored| {
+                Row::new(vec![
+                    Cell::from("in the mod store").fg(theme().muted),
+                    Cell::from(format!("{stored} mods")).fg(theme().muted),
+                ])
+            }))
+            .collect::<Vec<Row<'_>>>();
+
+        frame.render_stateful_widget(
+            Table::new(rows)
+                .header(
+                    Row::new(vec!["profile", "size"]).style(Style::default().fg(theme().accent)),
+                )
+                .widths(&[Constraint::Percentage(60), Constraint::Percentage(40)])
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(if self.measuring {
+                            "profiles (measuring)"
+                        } else {
+                            "profiles"
+                        })
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background),
+                )
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            rect,
+            &mut TableState::default().with_selected(Some(self.selected)),
+        );
+    }
+
+    /// the biggest first, everything that isn't a mod or plugin is one bar
+    fn render_chart(&self, frame: &mut Frame<'_>, rect: Rect, report: &UsageReport) {
+        let Some(usage) = report.profiles.get(self.selected) else {
+            return;
+        };
+        let mut sizes = usage
+            .entries()
+            .map(|entry| (entry.name.as_str(), entry.size))
+            .chain([("everything else", usage.other)])
+            .collect::<Vec<(&str, u64)>>();
+        sizes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        let bars = sizes
+            .iter()
+            .map(|(name, size)| {
+                let label = match name.char_indices().nth(LABEL_WIDTH) {
+                    Some((end, _)) => format!("{}…", &name[..end]),
+                    None => (*name).to_owned(),
+                };
+                Bar::default()
+                    .label(Line::raw(label))
+                    .value(*size)
+                    .text_value(format_size(*size))
+            })
+            .collect::<Vec<Bar<'_>>>();
+
+        frame.render_widget(
+            BarChart::default()
+                .data(BarGroup::default().bars(&bars))
+                .direction(Direction::Horizontal)
+                .bar_width(1)
+                .bar_gap(0)
+                .max(
+                    sizes
+                        .first()
+                        .map(|(_, size)| *size)
+                        .unwrap_or_default()
+                        .max(1),
+                )
+                .bar_style(Style::default().fg(theme().accent))
+                .value_style(Style::default().fg(theme().background).bg(theme().accent))
+                .label_style(Style::default().fg(theme().text))
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(format!("{} by size", usage.profile))
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background)
+                        .fg(theme().text),
+                ),
+            rect,
+        );
+    }
+}
+
+impl TabUi for DiskUsageView {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let Some(report) = &self.report else {
+            frame.render_widget(
+                Paragraph::new("measuring every profile...")
+                    .fg(theme().muted)
+                    .block(Block::default().borders(Borders::all()))
+                    .bg(theme().background),
+                rect,
+            );
+            return;
+        };
+
+        let layout = Layout::new()
+            .constraints([Constraint::Length(36), Constraint::Min(0)])
+            .direction(Direction::Horizontal)
+            .split(rect);
+        self.render_profiles(frame, layout[0], report);
+        self.render_chart(frame, layout[1], report);
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::DiskUsage)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        let event::Event::Key(key_event) = crosstermevent else {
+            return Ok(());
+        };
+        if key_event.kind != event::KeyEventKind::Press || state.is_typing {
+            return Ok(());
+        }
+
+        let profiles = self
+            .report
+            .as_ref()
+            .map(|report| report.profiles.len())
+            .unwrap_or_default();
+        match state.keymap.action(KeyContext::DiskUsage, &key_event) {
+            Some(Action::Down) => self.selected = profiles.saturating_sub(1).min(self.selected + 1),
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            Some(Action::Deduplicate) => self.deduplicate(state)?,
+            Some(Action::Reload) => {
+                state.disk_usage.measure(&state.install);
+                self.reload(state);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.reload(state);
+
+        Ok(())
+    }
+
+    fn on_disk_usage(&mut self, state: &GlobalState) {
+        self.reload(state);
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+
+use crate::{
+    app::GlobalState,
+    game_fs::{GameFs, RealFs},
+    mods_views::InstalledModPartial,
+    package_cache::PackageCache,
+    thunderstore::{find_package, Package},
+};
+
+const README: &str = "README.md";
+const CHANGELOG: &str = "CHANGELOG.md";
+
+/// the readme and changelog of a mod or package
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModDocs {
+    pub name: String,
+    pub readme: Option<String>,
+    pub changelog: Option<String>,
+    pub update: Option<Update>,
+}
+
+/// what changed between the installed version and the newest one
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Update {
+    pub from: String,
+    pub to: String,
+    /// none when the changelog doesn't say which version changed what
+    pub changes: Option<String>,
+}
+
+/// what the docs still need from thunderstore, fetched in the background
+#[derive(Debug, Clone)]
+pub enum Pending {
+    /// the index, to find the package of the mod in
+    Index(Box<InstalledModPartial>),
+    /// the archive of the newest version and the version that is installed, if any
+    Archive {
+        package: Package,
+        installed: Option<String>,
+    },
+}
+
+impl Pending {
+    /// the newest archive when the package of the mod has a newer version
+    pub fn for_mod(nsmod: &InstalledModPartial, index: &[Package]) -> Option<Self> {
+        let package = package_of(index, nsmod)?;
+        let installed = installed_version(nsmod);
+        package
+            .versions
+            .first()
+            .filter(|latest| is_newer(&latest.version_number, &installed))?;
+
+        Some(Pending::Archive {
+            package: package.clone(),
+            installed: Some(installed),
+        })
+    }
+}
+
+impl ModDocs {
+    /// from the mod's folder or else the archive it was installed from, the newest version is
+    /// left pending when the index has one, or the index when it isn't there yet
+    pub fn for_mod(
+        state: &GlobalState,
+        nsmod: &InstalledModPartial,
+        index: Option<&[Package]>,
+    ) -> (Self, Option<Pending>) {
+        let fs = state.install.fs.as_ref();
+        let dir = nsmod.dev_source.as_deref().unwrap_or(&nsmod.path);
+        let (mut readme, mut changelog) = from_dir(fs, dir);
+        let installed = installed_version(nsmod);
+        let package = index.and_then(|index| package_of(index, nsmod));
+
+        if readme.is_none() || changelog.is_none() {
+            let key = package
+                .map(|package| package.cache_key(&installed))
+                .or_else(|| {
+                    let author = nsmod.author.as_deref()?.trim();
+                    let manifest = nsmod.manifest.as_ref()?;
+                    Some(format!("{author}-{}-{installed}", manifest.name))
+                });
+            if let Some((_, bytes)) = key.and_then(|key| state.package_cache.peek(&RealFs, &key)) {
+                let (archive_readme, archive_changelog) = from_archive(&bytes);
+                readme = readme.or(archive_readme);
+                changelog = changelog.or(archive_changelog);
+            }
+        }
+
+        let docs = Self {
+            name: nsmod.mod_json.name.clone(),
+            readme,
+            changelog,
+            update: None,
+        };
+        let pending = match index {
+            Some(index) => Pending::for_mod(nsmod, index),
+            None => Some(Pending::Index(Box::new(nsmod.clone()))),
+        };
+
+        (docs, pending)
+    }
+
+    /// everything comes from the archive of the newest version, compared against the installed
+    /// one if there is one
+    pub fn for_package(
+        package: &Package,
+        installed: Option<&InstalledModPartial>,
+    ) -> (Self, Pending) {
+        let docs = Self {
+            name: package.name.clone(),
+            ..Self::default()
+        };
+        let pending = Pending::Archive {
+            package: package.clone(),
+            installed: installed.map(installed_version),
+        };
+
+        (docs, pending)
+    }
+
+    /// fills in what the docs don't have yet from the archive of the newest version
+    pub fn add_latest(&mut self, package: &Package, installed: Option<&str>, bytes: &[u8]) {
+        let Some(latest) = package.versions.first() else {
+            return;
+        };
+
+        let (readme, changelog) = from_archive(bytes);
+        self.update = installed
+            .filter(|installed| is_newer(&latest.version_number, installed))
+            .map(|installed| Update {
+                from: installed.to_owned(),
+                to: latest.version_number.clone(),
+                changes: changelog.as_deref().and_then(|changelog| {
+                    changes_between(changelog, installed, &latest.version_number)
+                }),
+            });
+        self.readme = self.readme.take().or(readme);
+        self.changelog = self.changelog.take().or(changelog);
+    }
+}
+
+/// the archive of the newest version of `package`, the package cache is outside the game so it's
+/// written even on a dry run
+pub fn fetch_latest(package_cache: &PackageCache, package: &Package) -> AnyResult<Vec<u8>> {
+    let latest = package
+        .versions
+        .first()
+        .with_context(|| format!("{} has no versions", package.name))?;
+    let (_, bytes) = package_cache.fetch(&RealFs, package, latest)?;
+
+    Ok(bytes)
+}
+
+/// the version thunderstore installed if it left a manifest, what the mod says otherwise
+fn installed_version(nsmod: &InstalledModPartial) -> String {
+    nsmod
+        .manifest
+        .as_ref()
+        .map(|manifest| manifest.version_number.clone())
+        .unwrap_or_else(|| nsmod.mod_json.version.clone())
+}
+
+fn package_of<'a>(index: &'a [Package], nsmod: &InstalledModPartial) -> Option<&'a Package> {
+    nsmod
+        .manifest
+        .as_ref()
+        .and_then(|manifest| {
+            index.iter().find(|package| {
+                package.name == manifest.name
+                    && nsmod
+                        .author
+                        .as_deref()
+                        .is_none_or(|author| author.trim() == package.owner)
+            })
+        })
+        .or_else(|| find_package(index, &nsmod.mod_json.name, None))
+}
+
+/// the names are matched ignoring case, mods aren't consistent about it
+fn from_dir(fs: &dyn GameFs, dir: &Path) -> (Option<String>, Option<String>) {
+    let files = fs.read_dir(dir).unwrap_or_default();
+    let read = |name: &str| {
+        let file = files.iter().find(|file| {
+            !file.is_dir
+                && file
+                    .path
+                    .file_name()
+                    .is_some_and(|file_name| file_name.eq_ignore_ascii_case(name))
+        })?;
+        fs.read_to_string(&file.path).ok()
+    };
+
+    (read(README), read(CHANGELOG))
+}
+
+/// thunderstore keeps them next to the manifest at the root of the archive
+fn from_archive(bytes: &[u8]) -> (Option<String>, Option<String>) {
+    let Result::Ok(mut zip) = zip::ZipArchive::new(Cursor::new(bytes)) else {
+        return (None, None);
+    };
+    let mut read = |name: &str| {
+        let entry = zip
+            .file_names()
+            .find(|entry| entry.eq_ignore_ascii_case(name))?
+            .to_owned();
+        let mut contents = String::new();
+        zip.by_name(&entry)
+            .ok()?
+            .read_to_string(&mut contents)
+            .ok()?;
+        Some(contents)
+    };
+
+    (read(README), read(CHANGELOG))
+}
+
+/// the entries of a changelog for versions after `from` up to and including `to`, none when the
+/// changelog doesn't have headings with versions in them
+pub fn changes_between(changelog: &str, from: &str, to: &str) -> Option<String> {
+    let (from, to) = (parse_version(from)?, parse_version(to)?);
+    let mut found_version = false;
+    let mut changes = Vec::new();
+    // the level of the heading the current entry started at and whether it's kept
+    let mut entry: Option<(usize, bool)> = None;
+    let mut fenced = false;
+
+    for line in changelog.lines() {
+        if line.trim_start().starts_with("```") {
+            fenced = !fenced;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        let is_heading = !fenced && level > 0 && line[level..].starts_with(' ');
+
+        if is_heading {
+            match parse_version(&line[level..]) {
+                Some(version) => {
+                    found_version = true;
+                    entry = Some((level, from < version && version <= to));
+                }
+                None if entry.is_some_and(|(entry_level, _)| level <= entry_level) => entry = None,
+                None => {}
+            }
+        }
+
+        if entry.is_some_and(|(_, keep)| keep) {
+            changes.push(line);
+        }
+    }
+
+    found_version.then(|| changes.join("\n").trim().to_owned())
+}
+
+/// versions that can't be read are never newer
+fn is_newer(version: &str, than: &str) -> bool {
+    parse_version(version)
+        .zip(parse_version(than))
+        .is_some_and(|(version, than)| version > than)
+}
+
+/// the first thing that looks like `1.2` or `v1.2.3` in the text
+fn parse_version(text: &str) -> Option<Vec<u64>> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let version = text[start..]
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .next()?
+        .trim_end_matches('.');
+    if !version.contains('.') {
+        return parse_version(&text[start + version.len()..]);
+    }
+
+    let mut parts = version
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    // so 1.2 and 1.2.0 are the same version
+    while parts.len() > 1 && parts.last() == Some(&0) {
+        parts.pop();
+    }
+
+    Some(parts)
+}
@@ -0,0 +1,198 @@
+use std::cell::Cell;
+
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    docs::{ModDocs, Pending},
+    keymap::{Action, KeyContext, Keymap},
+    markdown,
+    theme::theme,
+};
+
+/// the readme and changelog of a mod over the mods tab, one page at a time
+pub struct DocsViewer {
+    docs: ModDocs,
+    /// what is still being fetched for the docs
+    pending: Option<Pending>,
+    pages: Vec<(String, Vec<Line<'static>>)>,
+    page: usize,
+    scroll: usize,
+    /// how far the page can scroll in the area it was last drawn in
+    max_scroll: Cell<usize>,
+}
+
+impl DocsViewer {
+    pub fn new(docs: ModDocs, pending: Option<Pending>) -> Self {
+        let mut viewer = Self {
+            docs,
+            pending,
+            pages: Vec::new(),
+            page: 0,
+            scroll: 0,
+            max_scroll: Cell::new(0),
+        };
+        viewer.paginate();
+
+        viewer
+    }
+
+    pub fn name(&self) -> &str {
+        &self.docs.name
+    }
+
+    pub fn pending(&self) -> Option<&Pending> {
+        self.pending.as_ref()
+    }
+
+    /// what is fetched next, or nothing more once it failed
+    pub fn set_pending(&mut self, pending: Option<Pending>) {
+        self.pending = pending;
+        self.paginate();
+    }
+
+    /// shows what the newest archive has, a page other than the first one stays open
+    pub fn add_latest(&mut self, bytes: &[u8]) {
+        if let Some(Pending::Archive { package, installed }) = self.pending.take() {
+            self.docs.add_latest(&package, installed.as_deref(), bytes);
+        }
+        let page = self.pages[self.page].0.clone();
+        self.paginate();
+        if self.page > 0 {
+            self.page = self
+                .pages
+                .iter()
+                .position(|(title, _)| *title == page)
+                .unwrap_or_default();
+        }
+    }
+
+    /// the changes of an update come first since that's what it's usually opened for
+    fn paginate(&mut self) {
+        let docs = &self.docs;
+        let mut pages = Vec::new();
+        if let Some(update) = &docs.update {
+            let lines = match update.changes.as_deref() {
+                Some("") => vec![muted("the changelog has nothing for these versions")],
+                Some(changes) => markdown::render(changes),
+                None => vec![muted(
+                    "the changelog doesn't say which version changed what",
+                )],
+            };
+            pages.push((format!("changes {} → {}", update.from, update.to), lines));
+        }
+        if let Some(readme) = &docs.readme {
+            pages.push(("readme".to_owned(), markdown::render(readme)));
+        }
+        if let Some(changelog) = &docs.changelog {
+            pages.push(("changelog".to_owned(), markdown::render(changelog)));
+        }
+        if pages.is_empty() {
+            let text = if self.pending.is_some() {
+                "downloading the readme and changelog"
+            } else {
+                "there is no readme or changelog"
+            };
+            pages.push(("readme".to_owned(), vec![muted(text)]));
+        }
+
+        self.page = self.page.min(pages.len() - 1);
+        self.pages = pages;
+    }
+
+    /// returns true when the viewer should close
+    pub fn handle_key(&mut self, key: KeyEvent, keymap: &Keymap) -> bool {
+        let max_scroll = self.max_scroll.get();
+        match keymap.action(KeyContext::Docs, &key) {
+            Some(Action::Up) => self.scroll = self.scroll.saturating_sub(1),
+            Some(Action::Down) => self.scroll = (self.scroll + 1).min(max_scroll),
+            Some(Action::PreviousPage) => self.scroll = self.scroll.saturating_sub(20),
+            Some(Action::NextPage) => self.scroll = (self.scroll + 20).min(max_scroll),
+            Some(Action::Top) => self.scroll = 0,
+            Some(Action::Bottom) => self.scroll = max_scroll,
+            Some(Action::NextView) => {
+                self.page = (self.page + 1) % self.pages.len();
+                self.scroll = 0;
+            }
+            Some(Action::Close) => return true,
+            _ => {}
+        }
+
+        false
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.scroll = self.scroll.saturating_sub(3),
+            MouseEventKind::ScrollDown => {
+                self.scroll = (self.scroll + 3).min(self.max_scroll.get())
+            }
+            _ => {}
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let area = rect.inner(&Margin {
+            vertical: 1,
+            horizontal: rect.width / 10,
+        });
+        let (_, lines) = &self.pages[self.page];
+
+        // wrapped lines are guessed from their width, close enough to know where the end is
+        let inner = area.inner(&Margin {
+            vertical: 1,
+            horizontal: 1,
+        });
+        let height = lines
+            .iter()
+            .map(|line| line.width().max(1).div_ceil(inner.width.max(1) as usize))
+            .sum::<usize>();
+        self.max_scroll
+            .set(height.saturating_sub(inner.height as usize));
+
+        let mut title = vec![Span::styled(
+            format!("{} ", self.docs.name),
+            Style::default().fg(theme().accent),
+        )];
+        for (index, (page, _)) in self.pages.iter().enumerate() {
+            title.push(Span::styled(
+                format!(" {page} "),
+                if index == self.page {
+                    Style::default().fg(theme().selected).bg(theme().surface)
+                } else {
+                    Style::default().fg(theme().muted)
+                },
+            ));
+        }
+        if self.pending.is_some() {
+            title.push(Span::styled(
+                " - downloading",
+                Style::default().fg(theme().muted),
+            ));
+        }
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines.clone())
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll.min(self.max_scroll.get()) as u16, 0))
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(Line::from(title))
+                        .border_style(Style::default().fg(theme().selected))
+                        .bg(theme().background)
+                        .fg(theme().text),
+                ),
+            area,
+        );
+    }
+
+    pub fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::Docs)
+    }
+}
+
+fn muted(text: &'static str) -> Line<'static> {
+    Line::styled(text, Style::default().fg(theme().muted))
+}
@@ -0,0 +1,565 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+/// everything drydoct does to a game install goes through this so it can be swapped for an
+/// in-memory one in tests or held back in a dry run
+pub trait GameFs: Send + Sync {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+    /// sorted by path so listings don't depend on the filesystem
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>>;
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata>;
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+    /// a link at `link` to the dir `target`, a junction or an error where symlinks aren't allowed
+    fn symlink_dir(&self, target: &Path, link: &Path) -> io::Result<()>;
+    /// where the link at `path` points, an error for anything that isn't a link
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+    /// removes the link but never what it points at
+    fn remove_link(&self, path: &Path) -> io::Result<()>;
+    /// another name for the file `original`, both have to be on the same drive
+    fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()>;
+
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        String::from_utf8(self.read(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok_and(|metadata| metadata.is_dir)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+
+    /// the changes a dry run held back since the last call
+    fn take_changes(&self) -> Vec<Change> {
+        Vec::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FsMetadata {
+    pub is_dir: bool,
+    pub len: u64,
+    pub modified: SystemTime,
+    /// the device and inode, the same for every hard link to a file, none where it can't be told
+    pub file_id: Option<(u64, u64)>,
+}
+
+/// something a dry run would have done
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Write { path: PathBuf, len: usize },
+    CreateDir(PathBuf),
+    RemoveFile(PathBuf),
+    RemoveDir(PathBuf),
+    Rename { from: PathBuf, to: PathBuf },
+    Link { target: PathBuf, link: PathBuf },
+    RemoveLink(PathBuf),
+    HardLink { original: PathBuf, link: PathBuf },
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Write { path, len } => write!(f, "write {len} bytes to {}", path.display()),
+            Change::CreateDir(path) => write!(f, "create {}", path.display()),
+            Change::RemoveFile(path) => write!(f, "remove {}", path.display()),
+            Change::RemoveDir(path) => write!(f, "remove {} and everything in it", path.display()),
+            Change::Rename { from, to } => {
+                write!(f, "move {} to {}", from.display(), to.display())
+            }
+            Change::Link { target, link } => {
+                write!(f, "link {} to {}", link.display(), target.display())
+            }
+            Change::RemoveLink(path) => write!(f, "unlink {}", path.display()),
+            Change::HardLink { original, link } => {
+                write!(f, "hard link {} to {}", link.display(), original.display())
+            }
+        }
+    }
+}
+
+/// the actual disk
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RealFs;
+
+impl GameFs for RealFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    /// into a new file that takes the place of the old one, so a hard link into the mod store is
+    /// replaced instead of changing every profile that shares it
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".drydoct-tmp");
+        fs::write(&temp, contents)?;
+        fs::rename(&temp, path).inspect_err(|_| _ = fs::remove_file(&temp))
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        let mut entries = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| FsEntry {
+                // linked dirs are listed like the dirs they point at
+                is_dir: entry.file_type().is_ok_and(|ty| ty.is_dir())
+                    || entry.file_type().is_ok_and(|ty| ty.is_symlink()) && entry.path().is_dir(),
+                path: entry.path(),
+            })
+            .collect::<Vec<FsEntry>>();
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        io::Result::Ok(entries)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        let metadata = fs::metadata(path)?;
+
+        io::Result::Ok(FsMetadata {
+            is_dir: metadata.is_dir(),
+            len: metadata.len(),
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            file_id: file_id(path, &metadata),
+        })
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        path.canonicalize()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    #[cfg(unix)]
+    fn symlink_dir(&self, target: &Path, link: &Path) -> io::Result<()> {
+        std::os::unix::fs::symlink(target, link)
+    }
+
+    #[cfg(windows)]
+    fn symlink_dir(&self, target: &Path, link: &Path) -> io::Result<()> {
+        // symlinks need developer mode or admin, junctions don't
+        std::os::windows::fs::symlink_dir(target, link).or_else(|_| {
+            let status = std::process::Command::new("cmd")
+                .arg("/C")
+                .arg("mklink")
+                .arg("/J")
+                .arg(link)
+                .arg(target)
+                .stdout(std::process::Stdio::null())
+                .status()?;
+            if status.success() {
+                io::Result::Ok(())
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    format!("mklink /J failed with {status}"),
+                ))
+            }
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn symlink_dir(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn remove_link(&self, path: &Path) -> io::Result<()> {
+        // windows removes dir links and junctions like empty dirs
+        fs::remove_file(path).or_else(|_| fs::remove_dir(path))
+    }
+
+    fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+        fs::hard_link(original, link)
+    }
+}
+
+#[cfg(unix)]
+fn file_id(_path: &Path, metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((metadata.dev(), metadata.ino()))
+}
+
+/// the volume serial and file index, std only has them on nightly so the file is opened for them
+#[cfg(windows)]
+fn file_id(path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    let handle = winapi_util::Handle::from_path_any(path).ok()?;
+    let information = winapi_util::file::information(&handle).ok()?;
+
+    Some((information.volume_serial_number(), information.file_index()))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn file_id(_path: &Path, _metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+#[derive(Debug, Clone)]
+enum Node {
+    Dir,
+    File {
+        contents: Vec<u8>,
+        modified: SystemTime,
+    },
+}
+
+/// a filesystem that only lives in memory, paths are taken as is and never resolved
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    nodes: Mutex<std::collections::BTreeMap<PathBuf, Node>>,
+}
+
+#[cfg(test)]
+impl MemoryFs {
+    /// adds a file and any missing parents
+    pub fn with_file(self, path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Self {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            self.create_dir_all(parent).expect("parents are dirs");
+        }
+        self.write(path, contents.as_ref())
+            .expect("the parent was just created");
+
+        self
+    }
+
+    pub fn with_dir(self, path: impl AsRef<Path>) -> Self {
+        self.create_dir_all(path.as_ref())
+            .expect("parents are dirs");
+
+        self
+    }
+
+    fn nodes(&self) -> std::sync::MutexGuard<'_, std::collections::BTreeMap<PathBuf, Node>> {
+        self.nodes.lock().expect("memory fs poisoned")
+    }
+}
+
+#[cfg(test)]
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} doesn't exist", path.display()),
+    )
+}
+
+#[cfg(test)]
+fn not_a_dir(path: &Path) -> io::Error {
+    io::Error::other(format!("{} isn't a directory", path.display()))
+}
+
+#[cfg(test)]
+fn is_a_dir(path: &Path) -> io::Error {
+    io::Error::other(format!("{} is a directory", path.display()))
+}
+
+#[cfg(test)]
+impl GameFs for MemoryFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        match self.nodes().get(path) {
+            Some(Node::File { contents, .. }) => io::Result::Ok(contents.clone()),
+            Some(Node::Dir) => Err(is_a_dir(path)),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        match path.parent().map(|parent| nodes.get(parent)) {
+            Some(Some(Node::Dir)) | None => {}
+            Some(Some(Node::File { .. })) => return Err(not_a_dir(path)),
+            Some(None) => return Err(not_found(path.parent().unwrap_or(path))),
+        }
+        if let Some(Node::Dir) = nodes.get(path) {
+            return Err(is_a_dir(path));
+        }
+
+        nodes.insert(
+            path.to_path_buf(),
+            Node::File {
+                contents: contents.to_vec(),
+                modified: SystemTime::now(),
+            },
+        );
+
+        io::Result::Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        let nodes = self.nodes();
+        match nodes.get(path) {
+            Some(Node::Dir) => {}
+            Some(Node::File { .. }) => return Err(not_a_dir(path)),
+            None => return Err(not_found(path)),
+        }
+
+        io::Result::Ok(
+            nodes
+                .range(path.to_path_buf()..)
+                .skip(1)
+                .take_while(|(child, _)| child.starts_with(path))
+                .filter(|(child, _)| child.parent() == Some(path))
+                .map(|(child, node)| FsEntry {
+                    path: child.clone(),
+                    is_dir: matches!(node, Node::Dir),
+                })
+                .collect(),
+        )
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        match self.nodes().get(path) {
+            Some(Node::Dir) => io::Result::Ok(FsMetadata {
+                is_dir: true,
+                len: 0,
+                modified: SystemTime::UNIX_EPOCH,
+                file_id: None,
+            }),
+            Some(Node::File { contents, modified }) => io::Result::Ok(FsMetadata {
+                is_dir: false,
+                len: contents.len() as u64,
+                modified: *modified,
+                file_id: None,
+            }),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if self.exists(path) {
+            io::Result::Ok(path.to_path_buf())
+        } else {
+            Err(not_found(path))
+        }
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        for dir in path.ancestors().filter(|dir| !dir.as_os_str().is_empty()) {
+            match nodes.get(dir) {
+                Some(Node::Dir) => break,
+                Some(Node::File { .. }) => return Err(not_a_dir(dir)),
+                None => _ = nodes.insert(dir.to_path_buf(), Node::Dir),
+            }
+        }
+
+        io::Result::Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        match nodes.get(path) {
+            Some(Node::File { .. }) => _ = nodes.remove(path),
+            Some(Node::Dir) => return Err(is_a_dir(path)),
+            None => return Err(not_found(path)),
+        }
+
+        io::Result::Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        if !nodes.contains_key(path) {
+            return Err(not_found(path));
+        }
+        nodes.retain(|node, _| !node.starts_with(path));
+
+        io::Result::Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let mut nodes = self.nodes();
+        if !nodes.contains_key(from) {
+            return Err(not_found(from));
+        }
+        if let Some(parent) = to.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            if !matches!(nodes.get(parent), Some(Node::Dir)) {
+                return Err(not_found(parent));
+            }
+        }
+
+        let moved = nodes
+            .keys()
+            .filter(|node| node.starts_with(from))
+            .cloned()
+            .collect::<Vec<PathBuf>>();
+        nodes.retain(|node, _| !node.starts_with(to));
+        for old in moved {
+            let node = nodes.remove(&old).expect("was just listed");
+            let relative = old.strip_prefix(from).expect("was filtered by prefix");
+            let new = if relative.as_os_str().is_empty() {
+                to.to_path_buf()
+            } else {
+                to.join(relative)
+            };
+            nodes.insert(new, node);
+        }
+
+        io::Result::Ok(())
+    }
+
+    /// links aren't modelled, anything linking falls back to copying
+    fn symlink_dir(&self, _target: &Path, _link: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the memory fs has no links",
+        ))
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.metadata(path)?;
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} isn't a link", path.display()),
+        ))
+    }
+
+    fn remove_link(&self, path: &Path) -> io::Result<()> {
+        self.read_link(path).map(|_| ())
+    }
+
+    fn hard_link(&self, _original: &Path, _link: &Path) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the memory fs has no links",
+        ))
+    }
+}
+
+/// reads from the wrapped filesystem but only records what it is asked to change
+pub struct DryRunFs {
+    inner: Arc<dyn GameFs>,
+    changes: Mutex<Vec<Change>>,
+}
+
+impl DryRunFs {
+    pub fn new(inner: Arc<dyn GameFs>) -> Self {
+        Self {
+            inner,
+            changes: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn record(&self, change: Change) -> io::Result<()> {
+        log::info!("dry run, would {change}");
+        self.changes.lock().expect("dry run poisoned").push(change);
+
+        io::Result::Ok(())
+    }
+}
+
+impl GameFs for DryRunFs {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.record(Change::Write {
+            path: path.to_path_buf(),
+            len: contents.len(),
+        })
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        self.inner.read_dir(path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        self.inner.metadata(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        if self.inner.is_dir(path) {
+            return io::Result::Ok(());
+        }
+        self.record(Change::CreateDir(path.to_path_buf()))
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.record(Change::RemoveFile(path.to_path_buf()))
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.record(Change::RemoveDir(path.to_path_buf()))
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.record(Change::Rename {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        })
+    }
+
+    fn symlink_dir(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.record(Change::Link {
+            target: target.to_path_buf(),
+            link: link.to_path_buf(),
+        })
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.read_link(path)
+    }
+
+    fn remove_link(&self, path: &Path) -> io::Result<()> {
+        self.record(Change::RemoveLink(path.to_path_buf()))
+    }
+
+    fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+        self.record(Change::HardLink {
+            original: original.to_path_buf(),
+            link: link.to_path_buf(),
+        })
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+
+    fn take_changes(&self) -> Vec<Change> {
+        std::mem::take(&mut *self.changes.lock().expect("dry run poisoned"))
+    }
+}
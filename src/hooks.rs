@@ -0,0 +1,289 @@
+use std::{
+    io::{Read, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Ok, Result as AnyResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dedicated::ProcessStatus,
+    install::GameInstall,
+    notifications::{Level, Notification, Notifier},
+};
+
+/// how long a hook gets when the config doesn't say, in seconds
+pub const DEFAULT_TIMEOUT: u64 = 30;
+
+/// when a hook runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    PreInstall,
+    PostInstall,
+    /// before the dedicated server starts, play in the main menu doesn't launch anything itself
+    PreLaunch,
+    /// after the dedicated server stopped, however it stopped
+    PostExit,
+    Enable,
+    Disable,
+}
+
+/// a command from the config that runs on an event
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hook {
+    pub event: HookEvent,
+    /// the program and its arguments
+    pub command: Vec<String>,
+    /// in seconds, the command is killed after it
+    pub timeout: Option<u64>,
+}
+
+/// what a hook is told, as `DRYDOCT_*` environment variables and as json on its stdin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HookDetails {
+    pub event: HookEvent,
+    pub profile: String,
+    pub game_path: PathBuf,
+    /// the mods the event is about, empty for launches
+    pub mods: Vec<String>,
+    /// how the server exited, only set for post exit
+    pub exit_code: Option<i32>,
+}
+
+/// the hooks around a change to the game dir, kept with it while a preview waits
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeHooks {
+    /// can call the change off
+    pub before: Option<HookDetails>,
+    /// runs in the background once the change is done
+    pub after: Option<HookDetails>,
+}
+
+/// the hooks of the config, their output ends up in the notification history
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    hooks: Arc<Vec<Hook>>,
+    notifier: Notifier,
+    /// hooks still running in the background
+    running: Arc<AtomicUsize>,
+}
+
+impl std::fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            HookEvent::PreInstall => "pre_install",
+            HookEvent::PostInstall => "post_install",
+            HookEvent::PreLaunch => "pre_launch",
+            HookEvent::PostExit => "post_exit",
+            HookEvent::Enable => "enable",
+            HookEvent::Disable => "disable",
+        };
+
+        write!(f, "{display}")
+    }
+}
+
+impl HookDetails {
+    pub fn new(event: HookEvent, install: &GameInstall, mods: Vec<String>) -> Self {
+        Self {
+            event,
+            profile: install.profile.clone(),
+            game_path: install.root.clone(),
+            mods,
+            exit_code: None,
+        }
+    }
+
+    fn env(&self) -> Vec<(&'static str, String)> {
+        let mut env = vec![
+            ("DRYDOCT_EVENT", self.event.to_string()),
+            ("DRYDOCT_PROFILE", self.profile.clone()),
+            ("DRYDOCT_GAME_PATH", self.game_path.display().to_string()),
+            ("DRYDOCT_MODS", self.mods.join(",")),
+        ];
+        if let Some(code) = self.exit_code {
+            env.push(("DRYDOCT_EXIT_CODE", code.to_string()));
+        }
+
+        env
+    }
+}
+
+impl ChangeHooks {
+    /// pre and post install for `mods`
+    pub fn install(install: &GameInstall, mods: Vec<String>) -> Self {
+        Self {
+            before: Some(HookDetails::new(
+                HookEvent::PreInstall,
+                install,
+                mods.clone(),
+            )),
+            after: Some(HookDetails::new(HookEvent::PostInstall, install, mods)),
+        }
+    }
+
+    /// enable or disable for a single mod, nothing runs before a toggle
+    pub fn toggle(install: &GameInstall, name: String, enabled: bool) -> Self {
+        let event = if enabled {
+            HookEvent::Enable
+        } else {
+            HookEvent::Disable
+        };
+
+        Self {
+            before: None,
+            after: Some(HookDetails::new(event, install, vec![name])),
+        }
+    }
+}
+
+impl Hooks {
+    pub fn new(hooks: Vec<Hook>, notifier: Notifier) -> Self {
+        Self {
+            hooks: Arc::new(hooks),
+            notifier,
+            running: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst) > 0
+    }
+
+    fn of(&self, event: HookEvent) -> Vec<Hook> {
+        self.hooks
+            .iter()
+            .filter(|hook| hook.event == event)
+            .cloned()
+            .collect()
+    }
+
+    /// runs the hooks one after the other before a change, the first one that fails is returned
+    /// so the change can be called off
+    pub fn before(&self, details: HookDetails) -> AnyResult<()> {
+        for hook in self.of(details.event) {
+            let output = run_hook(&hook, &details)
+                .with_context(|| format!("the {} hook {} failed", details.event, name(&hook)))?;
+            self.notify(&hook, &details, output);
+        }
+
+        Ok(())
+    }
+
+    /// runs the hooks in the background after something happened, failures are only shown
+    pub fn after(&self, details: HookDetails) {
+        let hooks = self.of(details.event);
+        if hooks.is_empty() {
+            return;
+        }
+
+        let background = self.clone();
+        self.running.fetch_add(1, Ordering::SeqCst);
+        thread::spawn(move || {
+            for hook in &hooks {
+                match run_hook(hook, &details) {
+                    Result::Ok(output) => background.notify(hook, &details, output),
+                    Err(err) => background.notifier.error(&err.context(format!(
+                        "the {} hook {} failed",
+                        details.event,
+                        name(hook)
+                    ))),
+                }
+            }
+            background.running.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+
+    fn notify(&self, hook: &Hook, details: &HookDetails, output: Vec<String>) {
+        self.notifier.push(
+            Notification::new(
+                Level::Info,
+                format!("ran the {} hook {}", details.event, name(hook)),
+            )
+            .with_output(output),
+        );
+    }
+}
+
+/// the program without its arguments
+fn name(hook: &Hook) -> String {
+    hook.command.first().cloned().unwrap_or_default()
+}
+
+/// the lines the hook printed, a hook that exits with an error fails with them
+fn run_hook(hook: &Hook, details: &HookDetails) -> AnyResult<Vec<String>> {
+    let (program, args) = hook
+        .command
+        .split_first()
+        .context("the hook command is empty")?;
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(details.env())
+        .current_dir(&details.game_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("couldn't start {program}"))?;
+
+    // on its own thread, a hook that never reads its stdin would block the write once the pipe is
+    // full, one that closes it early is fine
+    if let Some(mut stdin) = child.stdin.take() {
+        let json = serde_json::to_string(details).expect("hook details always serialize");
+        thread::spawn(move || _ = stdin.write_all(json.as_bytes()));
+    }
+    let (stdout, stderr) = (read(child.stdout.take()), read(child.stderr.take()));
+
+    let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_TIMEOUT));
+    let start = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if start.elapsed() > timeout {
+            child
+                .kill()
+                .with_context(|| format!("couldn't stop {program}"))?;
+            child.wait()?;
+            bail!("timed out after {}s", timeout.as_secs());
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let output = [stdout, stderr]
+        .into_iter()
+        .flat_map(|output| {
+            let output = output.join().unwrap_or_default();
+            output.lines().map(str::to_owned).collect::<Vec<String>>()
+        })
+        .collect::<Vec<String>>();
+    if !status.success() {
+        let status = ProcessStatus::Exited(status.code());
+        if output.is_empty() {
+            bail!("{program} {status}");
+        }
+        return Err(anyhow!("{}", output.join("\n")).context(format!("{program} {status}")));
+    }
+
+    Ok(output)
+}
+
+/// reads all of `output` on its own thread so a hook filling one pipe doesn't block on the other
+fn read(output: Option<impl Read + Send + 'static>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut text = Vec::new();
+        if let Some(mut output) = output {
+            _ = output.read_to_end(&mut text);
+        }
+        String::from_utf8_lossy(&text).into_owned()
+    })
+}
@@ -0,0 +1,165 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+use serde_json::{Map, Value};
+
+use crate::game_fs::GameFs;
+
+pub const DEFAULT_PROFILE: &str = "R2Northstar";
+const ENABLED_MODS_NAME: &str = "enabledmods.json";
+
+/// a Titanfall2 install and the northstar profile drydoct works on,
+/// every read and write of it goes through `fs`
+#[derive(Clone)]
+pub struct GameInstall {
+    pub root: PathBuf,
+    pub profile: String,
+    pub fs: Arc<dyn GameFs>,
+    /// where mods are installed once and linked into profiles, none when every profile has its
+    /// own copies
+    pub mod_store: Option<PathBuf>,
+}
+
+impl std::fmt::Debug for GameInstall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GameInstall")
+            .field("root", &self.root)
+            .field("profile", &self.profile)
+            .field("dry_run", &self.fs.is_dry_run())
+            .field("mod_store", &self.mod_store)
+            .finish()
+    }
+}
+
+impl GameInstall {
+    pub fn new(root: PathBuf, fs: Arc<dyn GameFs>) -> Self {
+        Self {
+            root,
+            profile: DEFAULT_PROFILE.to_owned(),
+            fs,
+            mod_store: None,
+        }
+    }
+
+    /// the same install looked at through another profile
+    pub fn with_profile(&self, profile: &str) -> Self {
+        Self {
+            profile: profile.to_owned(),
+            ..self.clone()
+        }
+    }
+
+    /// what `-profile=` points northstar at
+    pub fn profile_dir(&self) -> PathBuf {
+        self.root.join(&self.profile)
+    }
+
+    pub fn mods_dir(&self) -> PathBuf {
+        self.profile_dir().join("mods")
+    }
+
+    pub fn plugins_dir(&self) -> PathBuf {
+        self.profile_dir().join("plugins")
+    }
+
+    pub fn logs_dir(&self) -> PathBuf {
+        self.profile_dir().join("logs")
+    }
+
+    pub fn enabled_mods_path(&self) -> PathBuf {
+        self.profile_dir().join(ENABLED_MODS_NAME)
+    }
+
+    /// paths in `text` that are inside the game dir made relative to it so they fit on screen
+    pub fn shorten(&self, text: &str) -> String {
+        text.replace(
+            &format!("{}{}", self.root.display(), std::path::MAIN_SEPARATOR),
+            "",
+        )
+    }
+
+    pub fn read_to_string(&self, path: &Path) -> AnyResult<String> {
+        self.fs
+            .read_to_string(path)
+            .with_context(|| format!("couldn't read {}", path.display()))
+    }
+
+    /// creates the parent first like northstar does for its own files
+    pub fn write(&self, path: &Path, contents: &[u8]) -> AnyResult<()> {
+        if let Some(parent) = path.parent() {
+            self.fs
+                .create_dir_all(parent)
+                .with_context(|| format!("couldn't create {}", parent.display()))?;
+        }
+
+        self.fs
+            .write(path, contents)
+            .with_context(|| format!("couldn't write {}", path.display()))
+    }
+
+    /// every directory next to the game that has a `mods` folder, sorted by name
+    pub fn profiles(&self) -> AnyResult<Vec<String>> {
+        Ok(self
+            .fs
+            .read_dir(&self.root)
+            .with_context(|| format!("couldn't read {}", self.root.display()))?
+            .into_iter()
+            .filter(|entry| entry.is_dir && self.fs.is_dir(&entry.path.join("mods")))
+            .filter_map(|entry| Some(entry.path.file_name()?.to_str()?.to_owned()))
+            .collect())
+    }
+
+    /// a missing file means northstar hasn't written one yet so everything is enabled
+    pub fn enabled_mods(&self) -> AnyResult<EnabledMods> {
+        let path = self.enabled_mods_path();
+        if !self.fs.exists(&path) {
+            return Ok(EnabledMods::default());
+        }
+
+        EnabledMods::parse(&self.read_to_string(&path)?)
+            .with_context(|| format!("invalid {}", path.display()))
+    }
+
+    pub fn set_mod_enabled(&self, name: &str, enabled: bool) -> AnyResult<()> {
+        let mut enabled_mods = self.enabled_mods()?;
+        enabled_mods.set(name, enabled);
+
+        self.write(
+            &self.enabled_mods_path(),
+            enabled_mods.contents().as_bytes(),
+        )
+    }
+}
+
+/// `enabledmods.json`, keys that aren't mods are kept as they are
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnabledMods {
+    entries: Map<String, Value>,
+}
+
+impl EnabledMods {
+    pub fn parse(content: &str) -> AnyResult<Self> {
+        Ok(Self {
+            entries: json5::from_str(content)?,
+        })
+    }
+
+    /// mods northstar doesn't know about yet are loaded
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.entries
+            .get(name)
+            .and_then(Value::as_bool)
+            .unwrap_or(true)
+    }
+
+    pub fn set(&mut self, name: &str, enabled: bool) {
+        self.entries.insert(name.to_owned(), Value::Bool(enabled));
+    }
+
+    pub fn contents(&self) -> String {
+        serde_json::to_string_pretty(&self.entries).expect("a json map always serializes")
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thermite::model::Manifest;
+
+use crate::{
+    dev_link::linked_source, game_fs::GameFs, install::GameInstall, package_cache::PackageCache,
+};
+
+/// in the profile, one record per mod dir
+const RECORD_DIR: &str = ".drydoct/integrity";
+/// written by mod managers next to what came out of the archive
+const MANAGER_FILES: [&str; 1] = ["thunderstore_author.txt"];
+
+pub fn sha256(contents: &[u8]) -> String {
+    Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileRecord {
+    pub sha256: String,
+    /// the path in the source zip or folder, missing when it can't be extracted again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entry: Option<String>,
+}
+
+/// the files a mod had right after it was installed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// the zip or folder it was installed from
+    #[serde(default)]
+    pub source: Option<PathBuf>,
+    /// by their `/` separated path in the mod dir
+    pub files: BTreeMap<String, FileRecord>,
+}
+
+impl Record {
+    pub fn new<'a>(
+        source: Option<PathBuf>,
+        files: impl IntoIterator<Item = (String, &'a [u8], Option<String>)>,
+    ) -> Self {
+        Self {
+            source,
+            files: files
+                .into_iter()
+                .map(|(path, contents, entry)| {
+                    (
+                        path,
+                        FileRecord {
+                            sha256: sha256(contents),
+                            entry,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn path(install: &GameInstall, dir: &str) -> PathBuf {
+        install
+            .profile_dir()
+            .join(RECORD_DIR)
+            .join(format!("{dir}.json"))
+    }
+
+    pub fn load(install: &GameInstall, dir: &str) -> AnyResult<Option<Self>> {
+        let path = Self::path(install, dir);
+        if !install.fs.exists(&path) {
+            return Ok(None);
+        }
+
+        serde_json::from_str(&install.read_to_string(&path)?)
+            .with_context(|| format!("invalid {}", path.display()))
+    }
+
+    pub fn save(&self, install: &GameInstall, dir: &str) -> AnyResult<()> {
+        install.write(
+            &Self::path(install, dir),
+            &serde_json::to_vec_pretty(self).expect("records always serialize"),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Issue {
+    Missing,
+    Modified,
+    /// not something it was installed with, never touched by a repair
+    Extra,
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Issue::Missing => "missing",
+            Issue::Modified => "modified",
+            Issue::Extra => "extra",
+        })
+    }
+}
+
+/// where the files a mod should have came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    /// recorded when drydoct installed it
+    Recorded,
+    /// the thunderstore zip it came from was found in the package cache
+    Archive,
+    /// nothing to check against
+    Unknown,
+    /// dev links change all the time so they aren't checked
+    DevLink,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub dir: String,
+    pub expected: Expected,
+    /// sorted by path
+    pub issues: Vec<(String, Issue)>,
+    record: Option<Record>,
+}
+
+impl Report {
+    pub fn count(&self, issue: Issue) -> usize {
+        self.issues
+            .iter()
+            .filter(|(_, found)| *found == issue)
+            .count()
+    }
+
+    /// missing or modified files that a repair could bring back
+    pub fn is_damaged(&self) -> bool {
+        self.count(Issue::Missing) + self.count(Issue::Modified) > 0
+    }
+
+    pub fn summary(&self) -> String {
+        match self.expected {
+            Expected::Unknown => return "nothing to check against".to_owned(),
+            Expected::DevLink => return "dev link, not checked".to_owned(),
+            Expected::Recorded | Expected::Archive => {}
+        }
+        if self.issues.is_empty() {
+            return "ok".to_owned();
+        }
+
+        [Issue::Missing, Issue::Modified, Issue::Extra]
+            .into_iter()
+            .map(|issue| (self.count(issue), issue))
+            .filter(|(count, _)| *count > 0)
+            .map(|(count, issue)| format!("{count} {issue}"))
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// every mod dir in the profile sorted by name
+pub fn verify_all(install: &GameInstall, package_cache: &PackageCache) -> AnyResult<Vec<Report>> {
+    install
+        .fs
+        .read_dir(&install.mods_dir())
+        .with_context(|| format!("couldn't read {}", install.mods_dir().display()))?
+        .into_iter()
+        .filter(|entry| entry.is_dir)
+        .map(|entry| verify(install, &entry.path, package_cache))
+        .collect()
+}
+
+pub fn verify(
+    install: &GameInstall,
+    mod_dir: &Path,
+    package_cache: &PackageCache,
+) -> AnyResult<Report> {
+    let dir = mod_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .with_context(|| format!("{} has no name", mod_dir.display()))?;
+
+    let (expected, record) = if linked_source(install, mod_dir).is_some() {
+        (Expected::DevLink, None)
+    } else if let Some(record) = Record::load(install, &dir)? {
+        (Expected::Recorded, Some(record))
+    } else if let Some(record) = from_package_cache(install, mod_dir, &dir, package_cache) {
+        (Expected::Archive, Some(record))
+    } else {
+        (Expected::Unknown, None)
+    };
+    let Some(expected_files) = record.as_ref() else {
+        return Ok(Report {
+            dir,
+            expected,
+            issues: Vec::new(),
+            record,
+        });
+    };
+
+    let mut actual = Vec::new();
+    walk(install.fs.as_ref(), mod_dir, mod_dir, &mut actual)?;
+    let mut issues = Vec::new();
+    for (path, file) in &expected_files.files {
+        match install.fs.read(&mod_dir.join(path)) {
+            Result::Ok(contents) if sha256(&contents) == file.sha256 => {}
+            Result::Ok(_) => issues.push((path.clone(), Issue::Modified)),
+            Err(_) => issues.push((path.clone(), Issue::Missing)),
+        }
+    }
+    issues.extend(
+        actual
+            .into_iter()
+            .filter(|path| {
+                !expected_files.files.contains_key(path) && !MANAGER_FILES.contains(&path.as_str())
+            })
+            .map(|path| (path, Issue::Extra)),
+    );
+    issues.sort();
+
+    Ok(Report {
+        dir,
+        expected,
+        issues,
+        record,
+    })
+}
+
+/// extracts the missing and modified files again, returns how many were put back
+pub fn repair(install: &GameInstall, report: &Report) -> AnyResult<usize> {
+    let Some(record) = &report.record else {
+        bail!("{} has nothing to repair it from", report.dir);
+    };
+    let source = record
+        .source
+        .as_ref()
+        .with_context(|| format!("{} doesn't know where it was installed from", report.dir))?;
+    let mut source_files = SourceFiles::open(install.fs.as_ref(), source)?;
+    let mod_dir = install.mods_dir().join(&report.dir);
+
+    let mut repaired = 0;
+    for (path, _) in report
+        .issues
+        .iter()
+        .filter(|(_, issue)| *issue != Issue::Extra)
+    {
+        let file = &record.files[path];
+        let entry = file
+            .entry
+            .as_ref()
+            .with_context(|| format!("{path} isn't in {}", source.display()))?;
+        let contents = source_files.read(entry)?;
+        if sha256(&contents) != file.sha256 {
+            bail!(
+                "{entry} in {} changed since {} was installed",
+                source.display(),
+                report.dir
+            );
+        }
+
+        install.write(&mod_dir.join(path), &contents)?;
+        repaired += 1;
+    }
+
+    Ok(repaired)
+}
+
+/// the files of a zip or a folder
+enum SourceFiles<'a> {
+    Folder(&'a dyn GameFs, PathBuf),
+    Zip(zip::ZipArchive<Cursor<Vec<u8>>>, PathBuf),
+}
+
+impl<'a> SourceFiles<'a> {
+    fn open(fs: &'a dyn GameFs, source: &Path) -> AnyResult<Self> {
+        if fs.is_dir(source) {
+            return Ok(Self::Folder(fs, source.to_path_buf()));
+        }
+
+        let bytes = fs
+            .read(source)
+            .with_context(|| format!("couldn't read {}", source.display()))?;
+        Ok(Self::Zip(
+            zip::ZipArchive::new(Cursor::new(bytes))
+                .with_context(|| format!("couldn't open {}", source.display()))?,
+            source.to_path_buf(),
+        ))
+    }
+
+    fn read(&mut self, entry: &str) -> AnyResult<Vec<u8>> {
+        match self {
+            Self::Folder(fs, dir) => fs
+                .read(&dir.join(entry))
+                .with_context(|| format!("couldn't read {entry} from {}", dir.display())),
+            Self::Zip(archive, path) => {
+                let mut file = archive
+                    .by_name(entry)
+                    .with_context(|| format!("{entry} isn't in {}", path.display()))?;
+                let mut contents = Vec::new();
+                file.read_to_end(&mut contents)?;
+                Ok(contents)
+            }
+        }
+    }
+}
+
+/// what a thunderstore mod should look like from its archive in the package cache
+fn from_package_cache(
+    install: &GameInstall,
+    mod_dir: &Path,
+    dir: &str,
+    package_cache: &PackageCache,
+) -> Option<Record> {
+    let fs = install.fs.as_ref();
+    let manifest =
+        json5::from_str::<Manifest>(&fs.read_to_string(&mod_dir.join("manifest.json")).ok()?)
+            .ok()?;
+    let author = fs
+        .read_to_string(&mod_dir.join("thunderstore_author.txt"))
+        .ok()?;
+    let (archive, bytes) = package_cache.peek(
+        fs,
+        &format!(
+            "{}-{}-{}",
+            author.trim(),
+            manifest.name,
+            manifest.version_number
+        ),
+    )?;
+
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).ok()?;
+    let prefix = format!("mods/{dir}/");
+    let mut files = Vec::new();
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index).ok()?;
+        let path = match file.name() {
+            "manifest.json" => "manifest.json".to_owned(),
+            name => match name.strip_prefix(&prefix) {
+                Some(path) if !file.is_dir() => path.to_owned(),
+                _ => continue,
+            },
+        };
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).ok()?;
+        files.push((path, contents, file.name().to_owned()));
+    }
+
+    Some(Record::new(
+        Some(archive),
+        files.iter().map(|(path, contents, entry)| {
+            (path.clone(), contents.as_slice(), Some(entry.clone()))
+        }),
+    ))
+}
+
+fn walk(fs: &dyn GameFs, root: &Path, dir: &Path, files: &mut Vec<String>) -> AnyResult<()> {
+    for entry in fs
+        .read_dir(dir)
+        .with_context(|| format!("couldn't read {}", dir.display()))?
+    {
+        if entry.is_dir {
+            walk(fs, root, &entry.path, files)?;
+            continue;
+        }
+
+        files.push(
+            entry
+                .path
+                .strip_prefix(root)
+                .expect("read_dir returns children")
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    integrity::{self, Expected, Issue, Report},
+    keymap::{Action, KeyContext, Keymap},
+    theme::theme,
+};
+
+#[derive(Default)]
+pub struct Integrity {
+    reports: Vec<Report>,
+    selected: usize,
+}
+
+impl Integrity {
+    fn verify(&mut self, state: &GlobalState) -> AnyResult<()> {
+        self.reports = integrity::verify_all(&state.install, &state.package_cache)?;
+        self.selected = self.selected.min(self.reports.len().saturating_sub(1));
+
+        Ok(())
+    }
+
+    fn repair_selected(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(report) = self.reports.get(self.selected).cloned() else {
+            return Ok(());
+        };
+        if !report.is_damaged() {
+            state
+                .notifier
+                .info(format!("{} has nothing to repair", report.dir));
+            return Ok(());
+        }
+
+        let dir = report.dir.clone();
+        let count = report.count(Issue::Missing) + report.count(Issue::Modified);
+        if state.apply(format!("repair {dir}"), move |install| {
+            integrity::repair(install, &report).map(|_| ())
+        })? {
+            state
+                .notifier
+                .info(format!("extracted {count} damaged files of {dir} again"));
+        }
+
+        self.verify(state)
+    }
+}
+
+impl TabUi for Integrity {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let layout = Layout::new()
+            .constraints([Constraint::Min(3), Constraint::Length(8)])
+            .split(rect);
+
+        let rows = self
+            .reports
+            .iter()
+            .map(|report| {
+                let color = match report.expected {
+                    _ if report.is_damaged() => theme().error,
+                    Expected::Recorded | Expected::Archive if report.issues.is_empty() => {
+                        theme().enabled
+                    }
+                    Expected::Recorded | Expected::Archive => theme().warn,
+                    Expected::Unknown | Expected::DevLink => theme().muted,
+                };
+                let checked_against = match report.expected {
+                    Expected::Recorded => "install record",
+                    Expected::Archive => "cached archive",
+                    Expected::Unknown | Expected::DevLink => "",
+                };
+
+                Row::new(vec![
+                    Cell::from(report.dir.as_str()).fg(theme().text),
+                    Cell::from(checked_against).fg(theme().muted),
+                    Cell::from(report.summary()).fg(color),
+                ])
+            })
+            .collect::<Vec<Row<'_>>>();
+
+        frame.render_stateful_widget(
+            Table::new(rows)
+                .header(
+                    Row::new(vec!["mod", "checked against", "status"])
+                        .style(Style::default().fg(theme().accent)),
+                )
+                .widths(&[
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(40),
+                ])
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(format!(
+                            "integrity ({} damaged)",
+                            self.reports
+                                .iter()
+                                .filter(|report| report.is_damaged())
+                                .count()
+                        ))
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background),
+                )
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            layout[0],
+            &mut TableState::default().with_selected(Some(self.selected)),
+        );
+
+        let details = match self.reports.get(self.selected) {
+            Some(report) if !report.issues.is_empty() => Text::from(
+                report
+                    .issues
+                    .iter()
+                    .map(|(path, issue)| {
+                        Line::from(vec![
+                            Span::styled(
+                                format!("{issue:<9}"),
+                                Style::default().fg(match issue {
+                                    Issue::Missing | Issue::Modified => theme().error,
+                                    Issue::Extra => theme().warn,
+                                }),
+                            ),
+                            Span::raw(path.as_str()),
+                        ])
+                    })
+                    .collect::<Vec<Line<'_>>>(),
+            ),
+            Some(report) => Text::raw(report.summary()),
+            None => Text::raw("no mods installed"),
+        };
+        frame.render_widget(
+            Paragraph::new(details).block(
+                Block::default()
+                    .borders(Borders::all())
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            layout[1],
+        );
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::Integrity)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        let event::Event::Key(key_event) = crosstermevent else {
+            return Ok(());
+        };
+        if key_event.kind != event::KeyEventKind::Press || state.is_typing {
+            return Ok(());
+        }
+
+        match state.keymap.action(KeyContext::Integrity, &key_event) {
+            Some(Action::Down) => {
+                self.selected = self.reports.len().saturating_sub(1).min(self.selected + 1)
+            }
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            Some(Action::Repair) => self.repair_selected(state)?,
+            Some(Action::Reload) => self.verify(state)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.verify(state)
+    }
+}
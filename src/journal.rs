@@ -0,0 +1,607 @@
+use std::{
+    collections::VecDeque,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+
+use crate::{
+    game_fs::{Change, DryRunFs, FsEntry, FsMetadata, GameFs},
+    hooks::ChangeHooks,
+    install::GameInstall,
+    integrity::sha256,
+};
+
+const MAX_TRANSACTIONS: usize = 50;
+/// removed files and dirs are moved here in the game dir so undoing them is a rename, they stay
+/// until the transaction that removed them is dropped
+pub const TRASH_NAME: &str = "drydoct-trash";
+
+/// anything that changes the game dir, only ever through the install it is given
+pub type Operation = dyn Fn(&GameInstall) -> AnyResult<()>;
+
+/// what a write left, only enough to tell whether the file changed since
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Written {
+    pub len: usize,
+    pub sha256: String,
+}
+
+impl Written {
+    pub fn of(contents: &[u8]) -> Self {
+        Self {
+            len: contents.len(),
+            sha256: sha256(contents),
+        }
+    }
+}
+
+/// a single change to the game dir with enough of the old state to take it back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Op {
+    Write {
+        path: PathBuf,
+        before: Option<Vec<u8>>,
+        after: Written,
+    },
+    CreateDir(PathBuf),
+    RemoveFile {
+        path: PathBuf,
+        /// where it was moved, none when it couldn't be and it's gone for good
+        trash: Option<PathBuf>,
+    },
+    RemoveDir {
+        path: PathBuf,
+        /// where it was moved, none when it couldn't be and it's gone for good
+        trash: Option<PathBuf>,
+    },
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    Link {
+        target: PathBuf,
+        link: PathBuf,
+    },
+    Unlink {
+        target: PathBuf,
+        link: PathBuf,
+    },
+    HardLink {
+        original: PathBuf,
+        link: PathBuf,
+    },
+}
+
+impl std::fmt::Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Op::Write {
+                path,
+                before: None,
+                after,
+            } => write!(f, "created {} ({} bytes)", path.display(), after.len),
+            Op::Write {
+                path,
+                before: Some(before),
+                after,
+            } => write!(
+                f,
+                "wrote {} ({} -> {} bytes)",
+                path.display(),
+                before.len(),
+                after.len
+            ),
+            Op::CreateDir(path) => write!(f, "created {}", path.display()),
+            Op::RemoveFile {
+                path,
+                trash: Some(_),
+            }
+            | Op::RemoveDir {
+                path,
+                trash: Some(_),
+            } => write!(f, "removed {}", path.display()),
+            Op::RemoveFile { path, trash: None } | Op::RemoveDir { path, trash: None } => {
+                write!(f, "removed {} for good", path.display())
+            }
+            Op::Rename { from, to } => write!(f, "moved {} to {}", from.display(), to.display()),
+            Op::Link { target, link } => {
+                write!(f, "linked {} to {}", link.display(), target.display())
+            }
+            Op::Unlink { link, .. } => write!(f, "unlinked {}", link.display()),
+            Op::HardLink { original, link } => {
+                write!(
+                    f,
+                    "hard linked {} to {}",
+                    link.display(),
+                    original.display()
+                )
+            }
+        }
+    }
+}
+
+impl Op {
+    /// refuses when the game dir no longer looks like it did right after the op, what a write
+    /// left is moved to the trash and handed back so it can be done again
+    fn undo(&self, fs: &dyn GameFs, trash_dir: &Path) -> AnyResult<Option<PathBuf>> {
+        match self {
+            Op::Write {
+                path,
+                before,
+                after,
+            } => {
+                if fs
+                    .read(path)
+                    .ok()
+                    .map(|contents| Written::of(&contents))
+                    .as_ref()
+                    != Some(after)
+                {
+                    bail!("{} was changed since", path.display());
+                }
+                let written = move_to_trash(fs, trash_dir, path)?;
+                if let Some(before) = before {
+                    fs.write(path, before)
+                        .inspect_err(|_| _ = fs.rename(&written, path))?;
+                }
+                return Ok(Some(written));
+            }
+            Op::CreateDir(path) => {
+                if !fs.read_dir(path)?.is_empty() {
+                    bail!("{} isn't empty anymore", path.display());
+                }
+                fs.remove_dir_all(path)?;
+            }
+            Op::RemoveFile { path, trash } | Op::RemoveDir { path, trash } => {
+                let Some(trash) = trash else {
+                    bail!("{} was removed for good", path.display());
+                };
+                if fs.exists(path) {
+                    bail!("{} was created again since", path.display());
+                }
+                if !fs.exists(trash) {
+                    bail!("{} is gone from the trash", path.display());
+                }
+                fs.rename(trash, path)?;
+            }
+            Op::Rename { from, to } => {
+                if fs.exists(from) {
+                    bail!("{} was created again since", from.display());
+                }
+                fs.rename(to, from)?;
+            }
+            Op::Link { target, link } => {
+                if fs.read_link(link).ok().as_ref() != Some(target) {
+                    bail!("{} was changed since", link.display());
+                }
+                fs.remove_link(link)?;
+            }
+            Op::Unlink { target, link } => {
+                if fs.exists(link) {
+                    bail!("{} was created again since", link.display());
+                }
+                fs.symlink_dir(target, link)?;
+            }
+            Op::HardLink { original, link } => {
+                let id = |path: &Path| fs.metadata(path).map(|metadata| metadata.file_id);
+                match (id(link), id(original)) {
+                    (Err(_), _) => bail!("{} was removed since", link.display()),
+                    // replaced by another file, where file ids are known
+                    (Result::Ok(Some(link_id)), Result::Ok(Some(original_id)))
+                        if link_id != original_id =>
+                    {
+                        bail!("{} was changed since", link.display())
+                    }
+                    _ => fs.remove_file(link)?,
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// does the op again after it was undone, puts things back when undoing its transaction
+    /// fails part way, a write is put back from where its undo moved it
+    fn redo(&self, fs: &dyn GameFs, written: Option<&Path>) -> io::Result<()> {
+        match self {
+            Op::Write { path, .. } => match written {
+                Some(written) => fs.rename(written, path),
+                None => Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "what was written is gone",
+                )),
+            },
+            Op::CreateDir(path) => fs.create_dir_all(path),
+            Op::RemoveFile {
+                path,
+                trash: Some(trash),
+            } => fs.rename(path, trash),
+            Op::RemoveFile { path, trash: None } => fs.remove_file(path),
+            Op::RemoveDir {
+                path,
+                trash: Some(trash),
+            } => fs.rename(path, trash),
+            Op::RemoveDir { path, trash: None } => fs.remove_dir_all(path),
+            Op::Rename { from, to } => fs.rename(from, to),
+            Op::Link { target, link } => fs.symlink_dir(target, link),
+            Op::Unlink { link, .. } => fs.remove_link(link),
+            Op::HardLink { original, link } => fs.hard_link(original, link),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub description: String,
+    pub time: SystemTime,
+    pub ops: Vec<Op>,
+    /// the operation errored part way, whatever it did is still in `ops`
+    pub failed: bool,
+}
+
+impl Transaction {
+    /// the files and dirs it moved into the trash
+    fn trash(&self) -> impl Iterator<Item = &PathBuf> {
+        self.ops.iter().filter_map(|op| match op {
+            Op::RemoveFile {
+                trash: Some(trash), ..
+            }
+            | Op::RemoveDir {
+                trash: Some(trash), ..
+            } => Some(trash),
+            _ => None,
+        })
+    }
+}
+
+/// the operation a tab asked for, waiting for the user to look at what it would change
+pub struct PendingOperation {
+    pub description: String,
+    pub changes: Vec<Change>,
+    /// run around `op` once it's confirmed, not before
+    pub hooks: ChangeHooks,
+    op: Box<Operation>,
+}
+
+/// the last transactions done to the game dir this session, newest first
+#[derive(Debug, Default)]
+pub struct Journal {
+    transactions: VecDeque<Transaction>,
+}
+
+impl Journal {
+    /// runs `op` as one transaction, in a dry run nothing is recorded since nothing changed
+    pub fn run(
+        &mut self,
+        install: &GameInstall,
+        description: &str,
+        op: impl Fn(&GameInstall) -> AnyResult<()>,
+    ) -> AnyResult<()> {
+        if install.fs.is_dry_run() {
+            return op(install);
+        }
+
+        let recorder = Arc::new(Recorder {
+            inner: install.fs.clone(),
+            trash: install.root.join(TRASH_NAME),
+            ops: Mutex::new(Vec::new()),
+        });
+        let result = op(&GameInstall {
+            fs: recorder.clone(),
+            ..install.clone()
+        });
+
+        let ops = std::mem::take(&mut *recorder.ops.lock().expect("recorder poisoned"));
+        if !ops.is_empty() {
+            let transaction = Transaction {
+                description: description.to_owned(),
+                time: SystemTime::now(),
+                ops,
+                failed: result.is_err(),
+            };
+            log::info!(
+                "{}: {}",
+                transaction.description,
+                transaction
+                    .ops
+                    .iter()
+                    .map(Op::to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            );
+            self.transactions.push_front(transaction);
+            while self.transactions.len() > MAX_TRANSACTIONS {
+                let dropped = self.transactions.pop_back().expect("there are more than 0");
+                dropped
+                    .trash()
+                    .for_each(|trash| remove_trash(install.fs.as_ref(), trash));
+            }
+        }
+
+        result.with_context(|| format!("couldn't {description}"))
+    }
+
+    /// what `op` would change without changing anything
+    pub fn preview(install: &GameInstall, op: &Operation) -> AnyResult<Vec<Change>> {
+        let dry_run = Arc::new(DryRunFs::new(install.fs.clone()));
+        op(&GameInstall {
+            fs: dry_run.clone(),
+            ..install.clone()
+        })?;
+
+        Ok(dry_run.take_changes())
+    }
+
+    /// holds `op` back until [`Journal::confirm`] is called with it
+    pub fn prepare(
+        install: &GameInstall,
+        description: impl Into<String>,
+        op: impl Fn(&GameInstall) -> AnyResult<()> + 'static,
+    ) -> AnyResult<PendingOperation> {
+        let description = description.into();
+        let changes = Self::preview(install, &op)
+            .with_context(|| format!("couldn't preview {description}"))?;
+
+        Ok(PendingOperation {
+            description,
+            changes,
+            hooks: ChangeHooks::default(),
+            op: Box::new(op),
+        })
+    }
+
+    pub fn confirm(&mut self, install: &GameInstall, pending: PendingOperation) -> AnyResult<()> {
+        self.run(install, &pending.description, pending.op)
+    }
+
+    /// takes back the `count` newest transactions, newest first,
+    /// returns the descriptions of the ones that were undone
+    pub fn undo(&mut self, install: &GameInstall, count: usize) -> AnyResult<Vec<String>> {
+        let mut undone = Vec::new();
+
+        for _ in 0..count {
+            let Some(transaction) = self.transactions.front() else {
+                break;
+            };
+
+            let fs = install.fs.as_ref();
+            let trash_dir = install.root.join(TRASH_NAME);
+            let mut undone_ops = Vec::new();
+            for op in transaction.ops.iter().rev() {
+                let err = match op.undo(fs, &trash_dir) {
+                    Result::Ok(written) => {
+                        undone_ops.push((op, written));
+                        continue;
+                    }
+                    Err(err) => err,
+                };
+
+                // the ops undone so far are done again so the game dir isn't left half undone
+                for (op, written) in undone_ops.iter().rev() {
+                    if let Err(redo_err) = op.redo(fs, written.as_deref()) {
+                        log::error!("couldn't put back {op}: {redo_err}");
+                    }
+                }
+                return Err(err.context(format!("couldn't undo \"{}\"", transaction.description)));
+            }
+            undone_ops
+                .iter()
+                .filter_map(|(_, written)| written.as_ref())
+                .for_each(|written| remove_trash(fs, written));
+
+            let transaction = self
+                .transactions
+                .pop_front()
+                .expect("front was just checked");
+            log::info!("undid {}", transaction.description);
+            undone.push(transaction.description);
+        }
+
+        Ok(undone)
+    }
+
+    pub fn transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.transactions.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.transactions.len()
+    }
+
+    /// removes what earlier sessions left in the trash, their transactions are gone with them
+    pub fn empty_trash(install: &GameInstall) {
+        let trash = install.root.join(TRASH_NAME);
+        if install.fs.is_dry_run() || !install.fs.exists(&trash) {
+            return;
+        }
+        if let Err(err) = install.fs.remove_dir_all(&trash) {
+            log::warn!("couldn't empty {}: {err}", trash.display());
+        }
+    }
+}
+
+/// passes everything through and remembers what each change replaced
+struct Recorder {
+    inner: Arc<dyn GameFs>,
+    /// where removed dirs are moved
+    trash: PathBuf,
+    ops: Mutex<Vec<Op>>,
+}
+
+impl Recorder {
+    fn record(&self, op: Op) {
+        self.ops.lock().expect("recorder poisoned").push(op);
+    }
+
+    /// falls back to removing `path` for good when it can't be moved to the trash
+    fn remove(
+        &self,
+        path: &Path,
+        remove: impl FnOnce() -> io::Result<()>,
+    ) -> io::Result<Option<PathBuf>> {
+        match move_to_trash(self.inner.as_ref(), &self.trash, path) {
+            io::Result::Ok(trash) => io::Result::Ok(Some(trash)),
+            Err(err) => {
+                log::warn!(
+                    "couldn't move {} to the trash, removing it can't be undone: {err}",
+                    path.display()
+                );
+                remove()?;
+                io::Result::Ok(None)
+            }
+        }
+    }
+}
+
+/// moves `path` into the trash, the rename fails when the trash is on another drive
+fn move_to_trash(fs: &dyn GameFs, trash_dir: &Path, path: &Path) -> io::Result<PathBuf> {
+    static MOVED: AtomicUsize = AtomicUsize::new(0);
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or_default();
+    let trash = trash_dir.join(format!("{time}-{}", MOVED.fetch_add(1, Ordering::SeqCst)));
+
+    fs.create_dir_all(trash_dir)?;
+    fs.rename(path, &trash)?;
+    io::Result::Ok(trash)
+}
+
+fn remove_trash(fs: &dyn GameFs, trash: &Path) {
+    let removed = if fs.is_dir(trash) {
+        fs.remove_dir_all(trash)
+    } else {
+        fs.remove_file(trash)
+    };
+    if let Err(err) = removed {
+        log::warn!("couldn't empty {}: {err}", trash.display());
+    }
+}
+
+impl GameFs for Recorder {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.inner.read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let before = self.inner.read(path).ok();
+        self.inner.write(path, contents)?;
+        self.record(Op::Write {
+            path: path.to_path_buf(),
+            before,
+            after: Written::of(contents),
+        });
+
+        io::Result::Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<FsEntry>> {
+        self.inner.read_dir(path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<FsMetadata> {
+        self.inner.metadata(path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.canonicalize(path)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut missing = path
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !self.inner.exists(dir))
+            .map(Path::to_path_buf)
+            .collect::<Vec<PathBuf>>();
+        self.inner.create_dir_all(path)?;
+
+        // outermost first so undoing in reverse removes the innermost first
+        missing.reverse();
+        missing
+            .into_iter()
+            .for_each(|dir| self.record(Op::CreateDir(dir)));
+
+        io::Result::Ok(())
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        if !self.inner.exists(path) {
+            return self.inner.remove_file(path);
+        }
+
+        let trash = self.remove(path, || self.inner.remove_file(path))?;
+        self.record(Op::RemoveFile {
+            path: path.to_path_buf(),
+            trash,
+        });
+
+        io::Result::Ok(())
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        // only the link goes, what it points at is left alone
+        if self.inner.read_link(path).is_ok() {
+            return self.remove_link(path);
+        }
+
+        let trash = self.remove(path, || self.inner.remove_dir_all(path))?;
+        self.record(Op::RemoveDir {
+            path: path.to_path_buf(),
+            trash,
+        });
+
+        io::Result::Ok(())
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.inner.rename(from, to)?;
+        self.record(Op::Rename {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        });
+
+        io::Result::Ok(())
+    }
+
+    fn symlink_dir(&self, target: &Path, link: &Path) -> io::Result<()> {
+        self.inner.symlink_dir(target, link)?;
+        self.record(Op::Link {
+            target: target.to_path_buf(),
+            link: link.to_path_buf(),
+        });
+
+        io::Result::Ok(())
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.inner.read_link(path)
+    }
+
+    fn remove_link(&self, path: &Path) -> io::Result<()> {
+        let target = self.inner.read_link(path)?;
+        self.inner.remove_link(path)?;
+        self.record(Op::Unlink {
+            target,
+            link: path.to_path_buf(),
+        });
+
+        io::Result::Ok(())
+    }
+
+    fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+        self.inner.hard_link(original, link)?;
+        self.record(Op::HardLink {
+            original: original.to_path_buf(),
+            link: link.to_path_buf(),
+        });
+
+        io::Result::Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::GlobalState,
+    install::GameInstall,
+    journal::{Journal, PendingOperation},
+    keymap::{Action, KeyContext, Keymap},
+    logging::format_time,
+    theme::theme,
+};
+
+/// the transactions of this session drawn over the tabs, undoing goes back to the selected one
+#[derive(Debug, Default)]
+pub struct JournalView {
+    pub show: bool,
+    selected: usize,
+}
+
+impl JournalView {
+    pub fn toggle(&mut self) {
+        self.show = !self.show;
+        self.selected = 0;
+    }
+
+    /// returns true if the key was consumed by the journal
+    pub fn handle_key(&mut self, key: KeyEvent, state: &mut GlobalState) -> AnyResult<bool> {
+        if !self.show {
+            return Ok(false);
+        }
+
+        match state.keymap.action(KeyContext::Journal, &key) {
+            Some(Action::Down) => self.select_down(&state.journal),
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            Some(Action::Undo) => {
+                let undone = state.journal.undo(&state.install, self.selected + 1);
+                self.selected = 0;
+                state.disk_usage.measure(&state.install);
+                for description in undone? {
+                    state.notifier.info(format!("undid \"{description}\""));
+                }
+            }
+            Some(Action::Close) => self.show = false,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent, journal: &Journal) {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.select_down(journal),
+            MouseEventKind::ScrollUp => self.selected = self.selected.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn select_down(&mut self, journal: &Journal) {
+        self.selected = journal.len().saturating_sub(1).min(self.selected + 1)
+    }
+
+    pub fn render(
+        &self,
+        frame: &mut Frame<'_>,
+        rect: Rect,
+        journal: &Journal,
+        install: &GameInstall,
+    ) {
+        let area = rect.inner(&Margin {
+            vertical: rect.height / 8,
+            horizontal: rect.width / 8,
+        });
+
+        let items = journal
+            .transactions()
+            .enumerate()
+            .map(|(index, transaction)| {
+                // everything that would be undone with the selected one
+                let style = if index < self.selected {
+                    Style::default().fg(theme().warn)
+                } else {
+                    Style::default()
+                };
+                let mut lines = vec![Line::from(vec![
+                    Span::styled(
+                        format!("[{}] ", format_time(transaction.time)),
+                        Style::default().fg(theme().muted),
+                    ),
+                    Span::styled(transaction.description.as_str(), style),
+                    Span::styled(
+                        if transaction.failed { " (failed)" } else { "" },
+                        Style::default().fg(theme().error),
+                    ),
+                ])];
+                lines.extend(transaction.ops.iter().map(|op| {
+                    Line::styled(
+                        format!("    {}", install.shorten(&op.to_string())),
+                        Style::default().fg(theme().muted),
+                    )
+                }));
+                ListItem::new(lines)
+            })
+            .collect::<Vec<ListItem<'_>>>();
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(format!("journal ({})", journal.len()))
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background)
+                        .fg(theme().text),
+                )
+                .highlight_style(Style::default().bg(theme().surface).bold()),
+            area,
+            &mut ListState::default().with_selected(Some(self.selected)),
+        );
+    }
+
+    pub fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::Journal)
+    }
+}
+
+/// asks before a pending operation runs, listing everything it would change
+pub fn render_pending(
+    frame: &mut Frame<'_>,
+    rect: Rect,
+    pending: &PendingOperation,
+    install: &GameInstall,
+) {
+    let area = rect.inner(&Margin {
+        vertical: rect.height / 4,
+        horizontal: rect.width / 6,
+    });
+
+    let lines = if pending.changes.is_empty() {
+        vec![Line::styled(
+            "nothing would change",
+            Style::default().fg(theme().muted),
+        )]
+    } else {
+        pending
+            .changes
+            .iter()
+            .map(|change| Line::raw(format!("would {}", install.shorten(&change.to_string()))))
+            .collect()
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::all())
+                .title(format!("{}?", pending.description))
+                .title_style(Style::default().fg(theme().accent))
+                .border_style(Style::default().fg(theme().warn))
+                .bg(theme().background)
+                .fg(theme().text),
+        ),
+        area,
+    );
+}
@@ -0,0 +1,665 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail, Context, Ok, Result as AnyResult};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// where a key is pressed, every context has its own set of actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyContext {
+    Global,
+    Main,
+    Mods,
+    Utils,
+    NsLogs,
+    ConVars,
+    Notifications,
+    LogViewer,
+    Journal,
+    Confirm,
+    Integrity,
+    Servers,
+    Prepare,
+    Dedicated,
+    /// the packages view of the mods tab, on top of the mods binds
+    Packages,
+    Cache,
+    /// the readme and changelog viewer of the mods tab
+    Docs,
+    DiskUsage,
+    /// comparing two profiles or a profile and a mod list
+    Diff,
+}
+
+impl KeyContext {
+    const ALL: [KeyContext; 19] = [
+        KeyContext::Global,
+        KeyContext::Main,
+        KeyContext::Mods,
+        KeyContext::Utils,
+        KeyContext::NsLogs,
+        KeyContext::ConVars,
+        KeyContext::Notifications,
+        KeyContext::LogViewer,
+        KeyContext::Journal,
+        KeyContext::Confirm,
+        KeyContext::Integrity,
+        KeyContext::Servers,
+        KeyContext::Prepare,
+        KeyContext::Dedicated,
+        KeyContext::Packages,
+        KeyContext::Cache,
+        KeyContext::Docs,
+        KeyContext::DiskUsage,
+        KeyContext::Diff,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyContext::Global => "global",
+            KeyContext::Main => "main",
+            KeyContext::Mods => "mods",
+            KeyContext::Utils => "utils",
+            KeyContext::NsLogs => "nslogs",
+            KeyContext::ConVars => "convars",
+            KeyContext::Notifications => "notifications",
+            KeyContext::LogViewer => "log_viewer",
+            KeyContext::Journal => "journal",
+            KeyContext::Confirm => "confirm",
+            KeyContext::Integrity => "integrity",
+            KeyContext::Servers => "servers",
+            KeyContext::Prepare => "prepare",
+            KeyContext::Dedicated => "dedicated",
+            KeyContext::Packages => "packages",
+            KeyContext::Cache => "cache",
+            KeyContext::Docs => "docs",
+            KeyContext::DiskUsage => "disk_usage",
+            KeyContext::Diff => "diff",
+        }
+    }
+
+    /// overlays are drawn over the tabs and intentionally shadow global keys
+    fn is_overlay(&self) -> bool {
+        matches!(
+            self,
+            KeyContext::Notifications
+                | KeyContext::LogViewer
+                | KeyContext::Journal
+                | KeyContext::Confirm
+                | KeyContext::Docs
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    Quit,
+    ToggleNotifications,
+    ToggleLogs,
+    ToggleJournal,
+    GoToTab(usize),
+    Play,
+    Reload,
+    NextPage,
+    PreviousPage,
+    Up,
+    Down,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    NextView,
+    NextSection,
+    Open,
+    Edit,
+    Reset,
+    Close,
+    Copy,
+    Clear,
+    ToggleErrors,
+    ToggleTarget,
+    ToggleFollow,
+    CycleLevel,
+    Search,
+    Toggle,
+    NextProfile,
+    Undo,
+    Confirm,
+    Install,
+    Link,
+    Pack,
+    Repair,
+    Sort,
+    ToggleJoinable,
+    Prepare,
+    Paste,
+    ToggleThrowaway,
+    Add,
+    Delete,
+    StartStop,
+    Filter,
+    SaveFilter,
+    NextFilter,
+    Clean,
+    Docs,
+    Deduplicate,
+    CopyLeft,
+    CopyRight,
+    Export,
+}
+
+impl Action {
+    pub fn name(&self) -> String {
+        match self {
+            Action::Quit => "quit",
+            Action::ToggleNotifications => "toggle_notifications",
+            Action::ToggleLogs => "toggle_logs",
+            Action::ToggleJournal => "toggle_journal",
+            Action::GoToTab(tab) => return format!("tab_{}", tab + 1),
+            Action::Play => "play",
+            Action::Reload => "reload",
+            Action::NextPage => "next_page",
+            Action::PreviousPage => "previous_page",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::NextView => "next_view",
+            Action::NextSection => "next_section",
+            Action::Open => "open",
+            Action::Edit => "edit",
+            Action::Reset => "reset",
+            Action::Close => "close",
+            Action::Copy => "copy",
+            Action::Clear => "clear",
+            Action::ToggleErrors => "toggle_errors",
+            Action::ToggleTarget => "toggle_target",
+            Action::ToggleFollow => "toggle_follow",
+            Action::CycleLevel => "cycle_level",
+            Action::Search => "search",
+            Action::Toggle => "toggle",
+            Action::NextProfile => "next_profile",
+            Action::Undo => "undo",
+            Action::Confirm => "confirm",
+            Action::Install => "install",
+            Action::Link => "link",
+            Action::Pack => "pack",
+            Action::Repair => "repair",
+            Action::Sort => "sort",
+            Action::ToggleJoinable => "toggle_joinable",
+            Action::Prepare => "prepare",
+            Action::Paste => "paste",
+            Action::ToggleThrowaway => "toggle_throwaway",
+            Action::Add => "add",
+            Action::Delete => "delete",
+            Action::StartStop => "start_stop",
+            Action::Filter => "filter",
+            Action::SaveFilter => "save_filter",
+            Action::NextFilter => "next_filter",
+            Action::Clean => "clean",
+            Action::Docs => "docs",
+            Action::Deduplicate => "deduplicate",
+            Action::CopyLeft => "copy_left",
+            Action::CopyRight => "copy_right",
+            Action::Export => "export",
+        }
+        .to_owned()
+    }
+
+    /// the short label shown in the bind hints
+    fn label(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ToggleNotifications => "notifications",
+            Action::ToggleLogs => "logs",
+            Action::ToggleJournal => "journal",
+            Action::GoToTab(_) => "tabs",
+            Action::Play => "play",
+            Action::Reload => "reload",
+            Action::NextPage => "next",
+            Action::PreviousPage => "previous",
+            Action::Up | Action::Down | Action::Left | Action::Right => "select",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::NextView => "view",
+            Action::NextSection => "section",
+            Action::Open => "open",
+            Action::Edit => "edit",
+            Action::Reset => "reset",
+            Action::Close => "close",
+            Action::Copy => "copy",
+            Action::Clear => "clear",
+            Action::ToggleErrors => "errors only",
+            Action::ToggleTarget => "client/server",
+            Action::ToggleFollow => "follow",
+            Action::CycleLevel => "level",
+            Action::Search => "search",
+            Action::Toggle => "enable/disable",
+            Action::NextProfile => "profile",
+            Action::Undo => "undo to here",
+            Action::Confirm => "apply",
+            Action::Install => "install",
+            Action::Link => "dev link",
+            Action::Pack => "pack",
+            Action::Repair => "repair",
+            Action::Sort => "sort",
+            Action::ToggleJoinable => "joinable only",
+            Action::Prepare => "prepare",
+            Action::Paste => "prepare from clipboard",
+            Action::ToggleThrowaway => "throwaway profile",
+            Action::Add => "add line",
+            Action::Delete => "remove",
+            Action::StartStop => "start/stop",
+            Action::Filter => "filter",
+            Action::SaveFilter => "save filter",
+            Action::NextFilter => "saved filters",
+            Action::Clean => "clean up",
+            Action::Docs => "readme/changelog",
+            Action::Deduplicate => "share mods",
+            Action::CopyLeft => "copy ←",
+            Action::CopyRight => "copy →",
+            Action::Export => "export mod list",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBind {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBind {
+    pub fn parse(bind: &str) -> AnyResult<Self> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts = bind.split('+').collect::<Vec<&str>>();
+        // a lone "+" or "ctrl++" means the plus key
+        if bind.ends_with("++") || bind == "+" {
+            parts.retain(|part| !part.is_empty());
+            parts.push("+");
+        }
+        let key = parts
+            .pop()
+            .filter(|key| !key.is_empty())
+            .context("empty key")?;
+
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "ctr" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier \"{modifier}\" in \"{bind}\""),
+            }
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            _ if key.chars().count() == 1 => KeyCode::Char(key.chars().next().expect("one char")),
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "space" => KeyCode::Char(' '),
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            function if function.starts_with('f') => KeyCode::F(
+                function[1..]
+                    .parse()
+                    .map_err(|_| anyhow!("unknown key \"{key}\" in \"{bind}\""))?,
+            ),
+            _ => bail!("unknown key \"{key}\" in \"{bind}\""),
+        };
+
+        Ok(Self { code, modifiers })
+    }
+
+    fn matches(&self, key: &KeyEvent) -> bool {
+        // the case of a char already says if shift was held
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl std::fmt::Display for KeyBind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::Enter => write!(f, "enter"),
+            KeyCode::Esc => write!(f, "esc"),
+            KeyCode::Tab => write!(f, "tab"),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::Backspace => write!(f, "backspace"),
+            KeyCode::Delete => write!(f, "delete"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Home => write!(f, "home"),
+            KeyCode::End => write!(f, "end"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+fn default_binds() -> Vec<(KeyContext, Action, &'static [&'static str])> {
+    let mut binds: Vec<(KeyContext, Action, &'static [&'static str])> = vec![
+        (KeyContext::Global, Action::Quit, &["q", "esc", "ctrl+c"]),
+        (KeyContext::Global, Action::ToggleNotifications, &["f2"]),
+        (KeyContext::Global, Action::ToggleLogs, &["f3"]),
+        (KeyContext::Global, Action::ToggleJournal, &["f4"]),
+        (KeyContext::Main, Action::Play, &["p"]),
+        (KeyContext::Mods, Action::Reload, &["ctrl+r"]),
+        (KeyContext::Mods, Action::NextPage, &["n", "pagedown"]),
+        (KeyContext::Mods, Action::PreviousPage, &["p", "pageup"]),
+        (KeyContext::Mods, Action::Up, &["up", "k"]),
+        (KeyContext::Mods, Action::Down, &["down", "j"]),
+        (KeyContext::Mods, Action::Left, &["left", "h"]),
+        (KeyContext::Mods, Action::Right, &["right", "l"]),
+        (KeyContext::Mods, Action::NextView, &["tab"]),
+        (KeyContext::Mods, Action::Open, &["enter"]),
+        (KeyContext::Mods, Action::Toggle, &["space"]),
+        (KeyContext::Mods, Action::NextProfile, &["P"]),
+        (KeyContext::Mods, Action::Install, &["i"]),
+        (KeyContext::Mods, Action::Link, &["L"]),
+        (KeyContext::Mods, Action::Pack, &["z"]),
+        (KeyContext::Mods, Action::Docs, &["d"]),
+        (KeyContext::Utils, Action::NextSection, &["tab"]),
+        (KeyContext::NsLogs, Action::Open, &["enter"]),
+        (KeyContext::NsLogs, Action::Up, &["up", "k"]),
+        (KeyContext::NsLogs, Action::Down, &["down", "j"]),
+        (KeyContext::NsLogs, Action::Left, &["left", "h"]),
+        (KeyContext::NsLogs, Action::Right, &["right", "l"]),
+        (KeyContext::NsLogs, Action::ToggleErrors, &["e"]),
+        (KeyContext::NsLogs, Action::Reload, &["ctrl+r"]),
+        (KeyContext::ConVars, Action::Up, &["up", "k"]),
+        (KeyContext::ConVars, Action::Down, &["down", "j"]),
+        (KeyContext::ConVars, Action::Edit, &["enter"]),
+        (KeyContext::ConVars, Action::Reset, &["d"]),
+        (KeyContext::ConVars, Action::ToggleTarget, &["t"]),
+        (KeyContext::Integrity, Action::Up, &["up", "k"]),
+        (KeyContext::Integrity, Action::Down, &["down", "j"]),
+        (KeyContext::Integrity, Action::Repair, &["r"]),
+        (KeyContext::Integrity, Action::Reload, &["ctrl+r"]),
+        (KeyContext::Servers, Action::Up, &["up", "k"]),
+        (KeyContext::Servers, Action::Down, &["down", "j"]),
+        (KeyContext::Servers, Action::Sort, &["s"]),
+        (KeyContext::Servers, Action::Search, &["/"]),
+        (KeyContext::Servers, Action::ToggleJoinable, &["f"]),
+        (KeyContext::Servers, Action::Reload, &["ctrl+r"]),
+        (KeyContext::Servers, Action::Prepare, &["p"]),
+        (KeyContext::Servers, Action::Paste, &["v"]),
+        (KeyContext::Prepare, Action::Confirm, &["enter", "y"]),
+        (KeyContext::Prepare, Action::ToggleThrowaway, &["t"]),
+        (KeyContext::Prepare, Action::Close, &["n"]),
+        (KeyContext::Dedicated, Action::Up, &["up", "k"]),
+        (KeyContext::Dedicated, Action::Down, &["down", "j"]),
+        (KeyContext::Dedicated, Action::Left, &["left", "h"]),
+        (KeyContext::Dedicated, Action::Right, &["right", "l"]),
+        (KeyContext::Dedicated, Action::Toggle, &["space"]),
+        (KeyContext::Dedicated, Action::Edit, &["enter"]),
+        (KeyContext::Dedicated, Action::Add, &["a"]),
+        (KeyContext::Dedicated, Action::Delete, &["d"]),
+        (KeyContext::Dedicated, Action::StartStop, &["s"]),
+        (KeyContext::Dedicated, Action::Reload, &["ctrl+r"]),
+        (KeyContext::Packages, Action::Search, &["/"]),
+        (KeyContext::Packages, Action::Filter, &["f"]),
+        (KeyContext::Packages, Action::SaveFilter, &["s"]),
+        (KeyContext::Packages, Action::NextFilter, &["F"]),
+        (KeyContext::Packages, Action::Clear, &["c"]),
+        (KeyContext::Cache, Action::Up, &["up", "k"]),
+        (KeyContext::Cache, Action::Down, &["down", "j"]),
+        (KeyContext::Cache, Action::Delete, &["d"]),
+        (KeyContext::Cache, Action::Clean, &["c"]),
+        (KeyContext::Cache, Action::Reload, &["ctrl+r"]),
+        (KeyContext::DiskUsage, Action::Up, &["up", "k"]),
+        (KeyContext::DiskUsage, Action::Down, &["down", "j"]),
+        (KeyContext::DiskUsage, Action::Deduplicate, &["D"]),
+        (KeyContext::DiskUsage, Action::Reload, &["ctrl+r"]),
+        (KeyContext::Diff, Action::Up, &["up", "k"]),
+        (KeyContext::Diff, Action::Down, &["down", "j"]),
+        (KeyContext::Diff, Action::CopyLeft, &["left", "h"]),
+        (KeyContext::Diff, Action::CopyRight, &["right", "l"]),
+        (KeyContext::Diff, Action::NextProfile, &["P"]),
+        (KeyContext::Diff, Action::Export, &["e"]),
+        (KeyContext::Diff, Action::Reload, &["ctrl+r"]),
+        (KeyContext::Docs, Action::Up, &["up", "k"]),
+        (KeyContext::Docs, Action::Down, &["down", "j"]),
+        (KeyContext::Docs, Action::PreviousPage, &["pageup"]),
+        (KeyContext::Docs, Action::NextPage, &["pagedown", "space"]),
+        (KeyContext::Docs, Action::Top, &["home", "g"]),
+        (KeyContext::Docs, Action::Bottom, &["end", "G"]),
+        (KeyContext::Docs, Action::NextView, &["tab"]),
+        (KeyContext::Docs, Action::Close, &["d", "backspace", "esc"]),
+        (KeyContext::Notifications, Action::Close, &["esc"]),
+        (KeyContext::Notifications, Action::Up, &["up", "k"]),
+        (KeyContext::Notifications, Action::Down, &["down", "j"]),
+        (KeyContext::Notifications, Action::Top, &["home", "g"]),
+        (KeyContext::Notifications, Action::Bottom, &["end", "G"]),
+        (KeyContext::Notifications, Action::Copy, &["y"]),
+        (KeyContext::Notifications, Action::Clear, &["c"]),
+        (KeyContext::LogViewer, Action::Close, &["esc"]),
+        (KeyContext::LogViewer, Action::Up, &["up", "k"]),
+        (KeyContext::LogViewer, Action::Down, &["down", "j"]),
+        (KeyContext::LogViewer, Action::PreviousPage, &["pageup"]),
+        (KeyContext::LogViewer, Action::NextPage, &["pagedown"]),
+        (KeyContext::LogViewer, Action::CycleLevel, &["l"]),
+        (KeyContext::LogViewer, Action::Search, &["/"]),
+        (KeyContext::LogViewer, Action::ToggleFollow, &["f"]),
+        (KeyContext::Journal, Action::Close, &["esc"]),
+        (KeyContext::Journal, Action::Up, &["up", "k"]),
+        (KeyContext::Journal, Action::Down, &["down", "j"]),
+        (KeyContext::Journal, Action::Undo, &["u"]),
+        (KeyContext::Confirm, Action::Confirm, &["y", "enter"]),
+        (KeyContext::Confirm, Action::Close, &["n", "esc"]),
+    ];
+
+    const TABS: [&[&str]; 9] = [
+        &["1"],
+        &["2"],
+        &["3"],
+        &["4"],
+        &["5"],
+        &["6"],
+        &["7"],
+        &["8"],
+        &["9"],
+    ];
+    binds.extend(
+        TABS.into_iter()
+            .enumerate()
+            .map(|(tab, keys)| (KeyContext::Global, Action::GoToTab(tab), keys)),
+    );
+
+    binds
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    binds: BTreeMap<KeyContext, Vec<(Action, Vec<KeyBind>)>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut binds: BTreeMap<KeyContext, Vec<(Action, Vec<KeyBind>)>> = BTreeMap::new();
+
+        for (context, action, keys) in default_binds() {
+            binds.entry(context).or_default().push((
+                action,
+                keys.iter()
+                    .map(|key| KeyBind::parse(key).expect("default binds should be valid"))
+                    .collect(),
+            ));
+        }
+
+        Self { binds }
+    }
+}
+
+impl Keymap {
+    /// the default keymap with the user's overrides applied
+    pub fn with_overrides(
+        overrides: &BTreeMap<String, BTreeMap<String, Vec<String>>>,
+    ) -> AnyResult<Self> {
+        let mut keymap = Self::default();
+
+        for (context_name, actions) in overrides {
+            let context = KeyContext::ALL
+                .into_iter()
+                .find(|context| context.name() == context_name)
+                .with_context(|| format!("unknown keymap context \"{context_name}\""))?;
+            let binds = keymap.binds.entry(context).or_default();
+
+            for (action_name, keys) in actions {
+                let (_, bound) = binds
+                    .iter_mut()
+                    .find(|(action, _)| action.name() == *action_name)
+                    .with_context(|| {
+                        format!(
+                            "unknown action \"{action_name}\" in keymap context \"{context_name}\""
+                        )
+                    })?;
+
+                *bound = keys
+                    .iter()
+                    .map(|key| KeyBind::parse(key))
+                    .collect::<AnyResult<Vec<KeyBind>>>()?;
+            }
+        }
+
+        Ok(keymap)
+    }
+
+    pub fn action(&self, context: KeyContext, key: &KeyEvent) -> Option<Action> {
+        self.binds
+            .get(&context)?
+            .iter()
+            .find_map(|(action, binds)| {
+                binds
+                    .iter()
+                    .any(|bind| bind.matches(key))
+                    .then_some(*action)
+            })
+    }
+
+    pub fn keys(&self, context: KeyContext, action: Action) -> &[KeyBind] {
+        self.binds
+            .get(&context)
+            .and_then(|binds| binds.iter().find(|(bound, _)| *bound == action))
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// keys bound to more than one action in a context, or shadowed by a global key
+    pub fn conflicts(&self) -> Vec<String> {
+        let global = self
+            .binds
+            .get(&KeyContext::Global)
+            .cloned()
+            .unwrap_or_default();
+        let mut conflicts = Vec::new();
+
+        for (context, binds) in self.binds.iter() {
+            let flat = binds
+                .iter()
+                .flat_map(|(action, keys)| keys.iter().map(move |key| (*action, *key)))
+                .collect::<Vec<(Action, KeyBind)>>();
+
+            for (index, (action, key)) in flat.iter().enumerate() {
+                if let Some((other, _)) = flat[..index]
+                    .iter()
+                    .find(|(other, other_key)| other != action && other_key == key)
+                {
+                    conflicts.push(format!(
+                        "\"{key}\" is bound to both {} and {} in {}",
+                        other.name(),
+                        action.name(),
+                        context.name()
+                    ));
+                }
+
+                if *context == KeyContext::Global || context.is_overlay() {
+                    continue;
+                }
+                if let Some((global_action, _)) = global
+                    .iter()
+                    .find(|(_, global_keys)| global_keys.contains(key))
+                {
+                    conflicts.push(format!(
+                        "\"{key}\" for {} in {} is shadowed by global {}",
+                        action.name(),
+                        context.name(),
+                        global_action.name()
+                    ));
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// the bind hints for a context, movement actions are merged into a single hint
+    pub fn hints(&self, context: KeyContext) -> Vec<String> {
+        let Some(binds) = self.binds.get(&context) else {
+            return Vec::new();
+        };
+
+        let mut hints: Vec<String> = Vec::new();
+        let mut movement: Vec<String> = Vec::new();
+
+        for (action, keys) in binds.iter().filter(|(_, keys)| !keys.is_empty()) {
+            let keys = keys
+                .iter()
+                .map(KeyBind::to_string)
+                .collect::<Vec<String>>()
+                .join("/");
+
+            match action {
+                Action::Up | Action::Down | Action::Left | Action::Right => movement.push(keys),
+                Action::GoToTab(_) => {}
+                action => hints.push(format!("{} - {keys}", action.label())),
+            }
+        }
+
+        if !movement.is_empty() {
+            hints.insert(0, format!("select - ({})", movement.join(" ")));
+        }
+
+        hints
+    }
+
+    pub fn tab_hint(&self, tabs: usize) -> String {
+        let keys = (0..tabs)
+            .filter_map(|tab| self.keys(KeyContext::Global, Action::GoToTab(tab)).first())
+            .map(KeyBind::to_string)
+            .collect::<Vec<String>>()
+            .join("/");
+
+        format!("tabs - {keys}")
+    }
+}
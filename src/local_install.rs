@@ -0,0 +1,361 @@
+use std::{
+    collections::BTreeMap,
+    io::{Cursor, Read},
+    path::{Component, Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Ok, Result as AnyResult};
+use thermite::model::ModJSON;
+
+use crate::{install::GameInstall, integrity::Record, mod_store::ModStore};
+
+/// the files of a zip or folder keyed by their path inside it, `/` separated
+pub type Files = BTreeMap<String, Vec<u8>>;
+
+/// a zip or folder on disk that holds one or more mods
+#[derive(Debug, Clone)]
+pub struct LocalPackage {
+    /// what the package is called when a mod.json sits right at its root
+    name: String,
+    files: Files,
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedMod {
+    /// the dir it gets in the profile's `mods`
+    pub dir: String,
+    pub mod_json: ModJSON,
+    /// an installed mod with the same dir is replaced
+    pub replaces: bool,
+    files: Files,
+    /// where each file came from in the zip or folder
+    entries: BTreeMap<String, String>,
+}
+
+/// everything a package would put into a profile
+#[derive(Debug, Clone)]
+pub struct InstallPlan {
+    /// none when the archive only exists in memory, a verify can't repair from it then
+    pub source: Option<PathBuf>,
+    /// the thunderstore author, written next to every mod like other managers do
+    pub author: Option<String>,
+    pub mods: Vec<PlannedMod>,
+    pub plugins: Vec<(String, Vec<u8>)>,
+}
+
+impl LocalPackage {
+    /// reads a `.zip` or a folder, everything goes through the install's fs
+    pub fn load(install: &GameInstall, path: &Path) -> AnyResult<Self> {
+        let fs = &install.fs;
+        // a zip is named after its file without the extension
+        let name = if fs.is_dir(path) {
+            path.file_name()
+        } else {
+            path.file_stem()
+        }
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("{} has no name", path.display()))?
+        .to_owned();
+
+        if !fs.exists(path) {
+            bail!("{} doesn't exist", path.display());
+        }
+
+        let files = if fs.is_dir(path) {
+            let mut files = Files::new();
+            read_folder(install, path, path, &mut files)?;
+            files
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+        {
+            let bytes = fs
+                .read(path)
+                .with_context(|| format!("couldn't read {}", path.display()))?;
+            return Self::from_zip(&name, bytes)
+                .with_context(|| format!("couldn't open {}", path.display()));
+        } else {
+            bail!("{} is neither a folder nor a .zip", path.display());
+        };
+
+        Ok(Self { name, files })
+    }
+
+    /// a zip that is already in memory, like a download
+    pub fn from_zip(name: &str, bytes: Vec<u8>) -> AnyResult<Self> {
+        Ok(Self {
+            name: name.to_owned(),
+            files: read_zip(bytes)?,
+        })
+    }
+
+    /// works out where everything goes and checks every mod.json
+    pub fn plan(&self, install: &GameInstall, source: &Path) -> AnyResult<InstallPlan> {
+        let (prefix, files) = unwrap_single_dir(&self.files);
+        let mut plan = InstallPlan {
+            source: Some(source.to_path_buf()),
+            author: None,
+            mods: Vec::new(),
+            plugins: Vec::new(),
+        };
+
+        if files.contains_key("mod.json") {
+            // a bare mod folder
+            plan.mods
+                .push(plan_mod(install, &self.name, files.clone(), &prefix)?);
+        } else if has_dir(&files, "mods") || has_dir(&files, "plugins") {
+            // thunderstore layout, the manifest is copied into every mod so the mods view can show it
+            let manifest = files.get("manifest.json");
+            for dir in sub_dirs(&files, "mods/") {
+                let mut mod_files = files_under(&files, &format!("mods/{dir}/"));
+                let copy_manifest = manifest.is_some() && !mod_files.contains_key("manifest.json");
+                if let (Some(manifest), true) = (manifest, copy_manifest) {
+                    mod_files.insert("manifest.json".to_owned(), manifest.clone());
+                }
+                let mut planned =
+                    plan_mod(install, &dir, mod_files, &format!("{prefix}mods/{dir}/"))?;
+                if copy_manifest {
+                    planned
+                        .entries
+                        .insert("manifest.json".to_owned(), format!("{prefix}manifest.json"));
+                }
+                plan.mods.push(planned);
+            }
+            plan.plugins = files_under(&files, "plugins/")
+                .into_iter()
+                .filter(|(name, _)| !name.contains('/') && name.to_lowercase().ends_with(".dll"))
+                .collect();
+        } else {
+            // several mod folders next to each other
+            for dir in sub_dirs(&files, "") {
+                let mod_files = files_under(&files, &format!("{dir}/"));
+                if mod_files.contains_key("mod.json") {
+                    plan.mods.push(plan_mod(
+                        install,
+                        &dir,
+                        mod_files,
+                        &format!("{prefix}{dir}/"),
+                    )?);
+                }
+            }
+        }
+
+        if plan.mods.is_empty() && plan.plugins.is_empty() {
+            bail!(
+                "couldn't find a mod.json, mods/ or plugins/ in {}",
+                source.display()
+            );
+        }
+
+        Ok(plan)
+    }
+}
+
+impl InstallPlan {
+    pub fn describe(&self) -> String {
+        let names = self
+            .mods
+            .iter()
+            .map(|planned| planned.mod_json.name.as_str())
+            .chain(self.plugins.iter().map(|(name, _)| name.as_str()))
+            .collect::<Vec<&str>>()
+            .join(", ");
+        match self.source.as_ref().and_then(|source| source.file_name()) {
+            Some(source) => format!("install {names} from {}", source.to_string_lossy()),
+            None => format!("install {names}"),
+        }
+    }
+
+    /// replaces mods with the same dir name, overwrites plugins with the same name
+    pub fn install(&self, install: &GameInstall) -> AnyResult<()> {
+        for planned in &self.mods {
+            let mod_dir = install.mods_dir().join(&planned.dir);
+            if install.fs.exists(&mod_dir) {
+                install
+                    .fs
+                    .remove_dir_all(&mod_dir)
+                    .with_context(|| format!("couldn't remove the old {}", planned.dir))?;
+            }
+
+            let mut files = planned.files.clone();
+            if let Some(author) = &self.author {
+                files.insert(
+                    "thunderstore_author.txt".to_owned(),
+                    author.as_bytes().to_vec(),
+                );
+            }
+            match ModStore::of(install) {
+                Some(store) => _ = store.install(install, &mod_dir, &files)?,
+                None => {
+                    for (path, contents) in &files {
+                        install.write(&mod_dir.join(path), contents)?;
+                    }
+                }
+            }
+
+            // so a verify can tell later if anything went missing and get it back from the source
+            Record::new(
+                self.source.clone(),
+                planned.files.iter().map(|(path, contents)| {
+                    (
+                        path.clone(),
+                        contents.as_slice(),
+                        planned.entries.get(path).cloned(),
+                    )
+                }),
+            )
+            .save(install, &planned.dir)?;
+        }
+
+        for (name, contents) in &self.plugins {
+            install.write(&install.plugins_dir().join(name), contents)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `entry_prefix` is where the mod's files are in the zip or folder
+fn plan_mod(
+    install: &GameInstall,
+    dir: &str,
+    files: Files,
+    entry_prefix: &str,
+) -> AnyResult<PlannedMod> {
+    let mod_json = parse_mod_json(
+        dir,
+        files
+            .get("mod.json")
+            .with_context(|| format!("{dir} has no mod.json"))?,
+    )?;
+
+    Ok(PlannedMod {
+        dir: dir.to_owned(),
+        mod_json,
+        replaces: install.fs.exists(&install.mods_dir().join(dir)),
+        entries: files
+            .keys()
+            .map(|path| (path.clone(), format!("{entry_prefix}{path}")))
+            .collect(),
+        files,
+    })
+}
+
+/// checks a mod.json the way the mods view reads it, `dir` is only for the error
+pub fn parse_mod_json(dir: &str, contents: &[u8]) -> AnyResult<ModJSON> {
+    std::str::from_utf8(contents)
+        .map_err(|err| anyhow!(err))
+        .and_then(|content| Ok(json5::from_str::<ModJSON>(content)?))
+        .with_context(|| format!("{dir}/mod.json isn't a valid mod.json"))
+}
+
+/// every file under `dir` keyed by its path from `root`
+pub fn read_folder(
+    install: &GameInstall,
+    root: &Path,
+    dir: &Path,
+    files: &mut Files,
+) -> AnyResult<()> {
+    for entry in install
+        .fs
+        .read_dir(dir)
+        .with_context(|| format!("couldn't read {}", dir.display()))?
+    {
+        if entry.is_dir {
+            read_folder(install, root, &entry.path, files)?;
+            continue;
+        }
+
+        let relative = entry
+            .path
+            .strip_prefix(root)
+            .expect("read_dir returns children")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let contents = install
+            .fs
+            .read(&entry.path)
+            .with_context(|| format!("couldn't read {}", entry.path.display()))?;
+        files.insert(relative, contents);
+    }
+
+    Ok(())
+}
+
+fn read_zip(bytes: Vec<u8>) -> AnyResult<Files> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes))?;
+    let mut files = Files::new();
+
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        if file.is_dir() {
+            continue;
+        }
+        // rejects absolute paths and `..` so nothing lands outside the profile
+        let path = file
+            .enclosed_name()
+            .filter(|path| path.components().all(|c| matches!(c, Component::Normal(_))))
+            .map(Path::to_path_buf)
+            .with_context(|| format!("{} points outside of the zip", file.name()))?;
+
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)
+            .with_context(|| format!("couldn't extract {}", file.name()))?;
+        files.insert(path.to_string_lossy().replace('\\', "/"), contents);
+    }
+
+    Ok(files)
+}
+
+/// zips made by zipping a folder have everything inside that folder,
+/// also returns the dirs that were stripped
+fn unwrap_single_dir(files: &Files) -> (String, Files) {
+    let mut files = files.clone();
+    let mut prefix = String::new();
+
+    loop {
+        let top = sub_dirs(&files, "");
+        let [dir] = top.as_slice() else {
+            return (prefix, files);
+        };
+        if dir == "mods"
+            || dir == "plugins"
+            || files.contains_key(&format!("{dir}/mod.json"))
+            || !files.keys().all(|path| path.contains('/'))
+        {
+            return (prefix, files);
+        }
+
+        prefix += &format!("{dir}/");
+        files = files_under(&files, &format!("{dir}/"));
+    }
+}
+
+fn has_dir(files: &Files, dir: &str) -> bool {
+    files
+        .keys()
+        .any(|path| path.starts_with(&format!("{dir}/")))
+}
+
+/// the names of the dirs directly under `prefix`, sorted
+fn sub_dirs(files: &Files, prefix: &str) -> Vec<String> {
+    let mut dirs = files
+        .keys()
+        .filter_map(|path| path.strip_prefix(prefix)?.split_once('/'))
+        .map(|(dir, _)| dir.to_owned())
+        .collect::<Vec<String>>();
+    dirs.dedup();
+
+    dirs
+}
+
+fn files_under(files: &Files, prefix: &str) -> Files {
+    files
+        .iter()
+        .filter_map(|(path, contents)| {
+            Some((path.strip_prefix(prefix)?.to_owned(), contents.clone()))
+        })
+        .collect()
+}
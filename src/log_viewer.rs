@@ -0,0 +1,228 @@
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+use log::LevelFilter;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    logging::{self, LogRecord},
+    theme::theme,
+};
+
+pub struct LogViewer {
+    pub show: bool,
+    records: Vec<LogRecord>,
+    generation: usize,
+    level: LevelFilter,
+    search: String,
+    searching: bool,
+    follow: bool,
+    /// lines scrolled up from the newest record
+    scroll: usize,
+}
+
+impl Default for LogViewer {
+    fn default() -> Self {
+        Self {
+            show: false,
+            records: Vec::new(),
+            generation: 0,
+            level: LevelFilter::Debug,
+            search: String::new(),
+            searching: false,
+            follow: true,
+            scroll: 0,
+        }
+    }
+}
+
+impl LogViewer {
+    pub fn toggle(&mut self) {
+        self.show = !self.show;
+        self.searching = false;
+    }
+
+    /// pulls new records from the ring buffer, only does work if the viewer is open
+    pub fn refresh(&mut self) {
+        let generation = logging::generation();
+        if !self.show || generation == self.generation {
+            return;
+        }
+
+        let new_records = generation - self.generation;
+        self.generation = generation;
+        self.records = logging::recent();
+
+        if !self.follow {
+            // keep the view on the same records while new ones come in
+            self.scroll += self
+                .records
+                .iter()
+                .rev()
+                .take(new_records)
+                .filter(|record| self.matches(record))
+                .count();
+        }
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.show && self.searching
+    }
+
+    /// returns true if the key was consumed by the viewer
+    pub fn handle_key(&mut self, key: KeyEvent, keymap: &Keymap) -> AnyResult<bool> {
+        if !self.show {
+            return Ok(false);
+        }
+
+        if self.searching {
+            match key.code {
+                KeyCode::Char(c) => self.search.push(c),
+                KeyCode::Backspace => _ = self.search.pop(),
+                KeyCode::Enter | KeyCode::Esc => self.searching = false,
+                _ => {}
+            }
+            self.scroll = 0;
+
+            return Ok(true);
+        }
+
+        match keymap.action(KeyContext::LogViewer, &key) {
+            Some(Action::Up) => {
+                self.follow = false;
+                self.scroll = self.scroll.saturating_add(1);
+            }
+            Some(Action::Down) => self.scroll = self.scroll.saturating_sub(1),
+            Some(Action::PreviousPage) => {
+                self.follow = false;
+                self.scroll = self.scroll.saturating_add(20);
+            }
+            Some(Action::NextPage) => self.scroll = self.scroll.saturating_sub(20),
+            Some(Action::ToggleFollow) => {
+                self.follow = !self.follow;
+                self.scroll = 0;
+            }
+            Some(Action::CycleLevel) => {
+                self.level = match self.level {
+                    LevelFilter::Off | LevelFilter::Trace => LevelFilter::Error,
+                    LevelFilter::Error => LevelFilter::Warn,
+                    LevelFilter::Warn => LevelFilter::Info,
+                    LevelFilter::Info => LevelFilter::Debug,
+                    LevelFilter::Debug => LevelFilter::Trace,
+                };
+                self.scroll = 0;
+            }
+            Some(Action::Search) => {
+                self.searching = true;
+                self.search.clear();
+            }
+            Some(Action::Close) => self.show = false,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => {
+                self.follow = false;
+                self.scroll = self.scroll.saturating_add(3);
+            }
+            MouseEventKind::ScrollDown => self.scroll = self.scroll.saturating_sub(3),
+            _ => {}
+        }
+    }
+
+    fn matches(&self, record: &LogRecord) -> bool {
+        record.level <= self.level
+            && (self.search.is_empty()
+                || record
+                    .message
+                    .to_lowercase()
+                    .contains(&self.search.to_lowercase())
+                || record.target.contains(&self.search))
+    }
+
+    pub fn render(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let area = rect.inner(&Margin {
+            vertical: 1,
+            horizontal: 2,
+        });
+        let layout = Layout::new()
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(area);
+
+        let lines = self
+            .records
+            .iter()
+            .filter(|record| self.matches(record))
+            .collect::<Vec<&LogRecord>>();
+        let height = layout[0].height.saturating_sub(2) as usize;
+        let end = lines.len().saturating_sub(self.scroll.min(lines.len()));
+        let start = end.saturating_sub(height);
+
+        let text = lines[start..end]
+            .iter()
+            .map(|record| {
+                Line::from(vec![
+                    Span::raw(format!("{} ", logging::format_time(record.time))).fg(theme().muted),
+                    Span::styled(format!("{:<5} ", record.level), level_style(record.level)),
+                    Span::raw(format!("{} ", record.target)).fg(theme().muted),
+                    Span::raw(record.message.as_str()),
+                ])
+            })
+            .collect::<Vec<Line<'_>>>();
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(text).block(
+                Block::default()
+                    .borders(Borders::all())
+                    .title(format!(
+                        "logs - {} ({}/{})",
+                        logging::log_dir().display(),
+                        lines.len(),
+                        self.records.len()
+                    ))
+                    .title_style(Style::default().fg(theme().accent))
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            layout[0],
+        );
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(format!("level: {} ", self.level)),
+                Span::raw(format!("follow: {} ", self.follow)),
+                Span::raw("search: "),
+                Span::styled(
+                    format!("{}{}", self.search, if self.searching { "_" } else { "" }),
+                    Style::default().fg(theme().accent),
+                ),
+            ]))
+            .bg(theme().surface)
+            .fg(theme().text),
+            layout[1],
+        );
+    }
+
+    pub fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        if self.searching {
+            return vec!["done - enter/esc".to_owned()];
+        }
+
+        keymap.hints(KeyContext::LogViewer)
+    }
+}
+
+fn level_style(level: log::Level) -> Style {
+    match level {
+        log::Level::Error => Style::default().fg(theme().error),
+        log::Level::Warn => Style::default().fg(theme().warn),
+        log::Level::Info => Style::default().fg(theme().info),
+        log::Level::Debug => Style::default().fg(theme().text),
+        log::Level::Trace => Style::default().fg(theme().muted),
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+
+const LOG_NAME: &str = "drydoct.log";
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+const MAX_LOG_FILES: usize = 5;
+const RING_SIZE: usize = 2000;
+
+static RING: Lazy<Mutex<LogRing>> = Lazy::new(|| Mutex::new(LogRing::default()));
+
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub time: SystemTime,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{} {:<5} {}] {}",
+            format_time(self.time),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+#[derive(Default)]
+struct LogRing {
+    records: VecDeque<LogRecord>,
+    /// total amount of records ever pushed, used to tell if anything changed
+    generation: usize,
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> AnyResult<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("couldn't open log file {}", path.display()))?;
+        let size = file.metadata()?.len();
+
+        Ok(Self { path, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        if self.size + line.len() as u64 > MAX_LOG_SIZE {
+            rotate(&self.path)?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            self.size = 0;
+        }
+
+        writeln!(self.file, "{line}")?;
+        self.size += line.len() as u64 + 1;
+
+        std::io::Result::Ok(())
+    }
+}
+
+pub struct Logger {
+    level: LevelFilter,
+    file: Mutex<Option<LogFile>>,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let record = LogRecord {
+            time: SystemTime::now(),
+            level: record.level(),
+            target: record.target().to_owned(),
+            message: record.args().to_string(),
+        };
+
+        if let Some(file) = self.file.lock().expect("log file poisoned").as_mut() {
+            // nowhere to report this to, the ring buffer still has the record
+            _ = file.write_line(&record.to_string());
+        }
+
+        let mut ring = RING.lock().expect("log ring poisoned");
+        ring.records.push_back(record);
+        if ring.records.len() > RING_SIZE {
+            ring.records.pop_front();
+        }
+        ring.generation += 1;
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().expect("log file poisoned").as_mut() {
+            _ = file.file.flush();
+        }
+    }
+}
+
+/// the directory logs are written to, `$XDG_STATE_HOME/drydoct` or the local data dir where there is no state dir
+pub fn log_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(std::env::temp_dir)
+        .join("drydoct")
+}
+
+pub fn init(level: LevelFilter) -> AnyResult<PathBuf> {
+    let dir = log_dir();
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("couldn't create log directory {}", dir.display()))?;
+
+    let path = dir.join(LOG_NAME);
+    if path.exists() {
+        rotate(&path)?;
+    }
+
+    log::set_boxed_logger(Box::new(Logger {
+        level,
+        file: Mutex::new(Some(LogFile::open(path.clone())?)),
+    }))?;
+    log::set_max_level(level);
+
+    Ok(path)
+}
+
+/// shifts `drydoct.log` to `drydoct.log.1` and so on, dropping the oldest file
+fn rotate(path: &Path) -> std::io::Result<()> {
+    let rotated = |index: usize| PathBuf::from(format!("{}.{index}", path.display()));
+
+    _ = fs::remove_file(rotated(MAX_LOG_FILES));
+    for index in (1..MAX_LOG_FILES).rev() {
+        if rotated(index).exists() {
+            fs::rename(rotated(index), rotated(index + 1))?;
+        }
+    }
+
+    fs::rename(path, rotated(1))
+}
+
+/// a copy of the most recent records, oldest first
+pub fn recent() -> Vec<LogRecord> {
+    RING.lock()
+        .expect("log ring poisoned")
+        .records
+        .iter()
+        .cloned()
+        .collect()
+}
+
+pub fn generation() -> usize {
+    RING.lock().expect("log ring poisoned").generation
+}
+
+/// formats the time of day in utc as `hh:mm:ss`
+pub fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+        % (60 * 60 * 24);
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / (60 * 60),
+        secs / 60 % 60,
+        secs % 60
+    )
+}
@@ -1,24 +1,81 @@
 use anyhow::Result as AnyResult;
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use log::LevelFilter;
 
-use crate::app::App;
+use crate::{app::App, game_fs::RealFs};
 
 mod app;
-mod colors;
+mod autoexec;
+mod background;
+mod cache_view;
+mod config;
+mod convars;
+mod dedicated;
+mod dedicated_view;
+mod dev_link;
+mod disk_usage;
+mod disk_usage_view;
+mod docs;
+mod docs_view;
+mod game_fs;
+mod hooks;
+mod install;
+mod integrity;
+mod integrity_view;
+mod journal;
+mod journal_view;
+mod keymap;
+mod local_install;
+mod log_viewer;
+mod logging;
 mod mainmenu;
+mod markdown;
+mod master_server;
+mod mod_store;
 mod mods;
 mod mods_views;
+mod mouse;
+mod net;
+mod notifications;
+mod nslogs;
+mod nslogs_analyzer;
+mod pack;
+mod package_cache;
+mod package_filter;
+mod packages_view;
+mod path_input;
+mod prepare;
+mod profile_diff;
+mod profile_diff_view;
+mod servers;
+mod theme;
+mod thunderstore;
+mod utils;
+
+#[cfg(test)]
+mod tests;
 
 fn main() -> AnyResult<()> {
-    simple_logging::log_to_file("logs.log", LevelFilter::Debug)?;
+    let log_path = logging::init(LevelFilter::Debug)?;
+    log::info!("logging to {}", log_path.display());
+
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    if args.first().is_some_and(|arg| arg == "pack") {
+        println!("{}", pack::run_cli(&args[1..], &RealFs)?);
+        return Ok(());
+    }
 
     startup()?;
 
-    let status = App::new().run();
+    let status = App::new(
+        args.iter().any(|arg| arg == "--dry-run"),
+        args.iter().any(|arg| arg == "--offline"),
+    )
+    .run();
 
     shutdown()?;
     status?;
@@ -28,12 +85,12 @@ fn main() -> AnyResult<()> {
 
 pub fn startup() -> AnyResult<()> {
     enable_raw_mode()?;
-    execute!(std::io::stderr(), EnterAlternateScreen)?;
+    execute!(std::io::stderr(), EnterAlternateScreen, EnableMouseCapture)?;
     Ok(())
 }
 
 pub fn shutdown() -> AnyResult<()> {
-    execute!(std::io::stderr(), LeaveAlternateScreen)?;
+    execute!(std::io::stderr(), DisableMouseCapture, LeaveAlternateScreen)?;
     disable_raw_mode()?;
     Ok(())
 }
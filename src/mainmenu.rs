@@ -1,15 +1,17 @@
 use anyhow::Ok;
-use crossterm::event::{self, KeyCode};
+use crossterm::event;
 use once_cell::sync::Lazy;
 use ratatui::{prelude::*, widgets::*};
 
 use crate::{
     app::{GlobalState, TabUi},
-    colors::BACKGROUND,
+    keymap::{Action, KeyContext, Keymap},
+    mouse::{is_left_click, Hitboxes},
+    theme::theme,
 };
 
 static NAME_PLATE: Lazy<Vec<String>> = Lazy::new(|| {
-    include_str!("..\\name_plate.txt")
+    include_str!("../name_plate.txt")
         .split("DIRECTIVE_SPLIT")
         .map(str::to_string)
         .collect::<Vec<String>>()
@@ -18,6 +20,7 @@ static NAME_PLATE: Lazy<Vec<String>> = Lazy::new(|| {
 #[derive(Default)]
 pub struct MainMenu {
     playing: bool,
+    play_button: Hitboxes<()>,
 }
 
 impl TabUi for MainMenu {
@@ -31,43 +34,47 @@ impl TabUi for MainMenu {
             .split(rect);
 
         let name_plate = Paragraph::new(NAME_PLATE[0].clone())
-            .bg(BACKGROUND)
-            .red()
+            .bg(theme().background)
+            .fg(theme().selected)
             .add_modifier(Modifier::BOLD);
 
         frame.render_widget(name_plate, layout[0]);
 
         frame.render_widget(
-            Block::new().borders(Borders::empty()).bg(BACKGROUND),
+            Block::new()
+                .borders(Borders::empty())
+                .bg(theme().background),
             layout[1],
         );
 
-        let button = Paragraph::new(
-            self.playing
-                .then(|| NAME_PLATE[1].clone())
-                .unwrap_or_else(|| NAME_PLATE[2].clone()),
-        )
+        let button = Paragraph::new(if self.playing {
+            NAME_PLATE[1].clone()
+        } else {
+            NAME_PLATE[2].clone()
+        })
         .block(
             Block::default()
                 .borders(Borders::all())
                 .padding(Padding::zero())
                 .add_modifier(Modifier::BOLD)
-                .black()
-                .bg(BACKGROUND),
-        )
-        .style(
-            self.playing
-                .then(|| Style::default().light_red())
-                .unwrap_or_else(|| Style::default()),
+                .fg(theme().surface)
+                .bg(theme().background),
         )
+        .style(if self.playing {
+            Style::default().fg(theme().accent)
+        } else {
+            Style::default().fg(theme().text)
+        })
         .add_modifier(Modifier::BOLD)
-        .bg(BACKGROUND);
+        .bg(theme().background);
 
         frame.render_widget(button, layout[2]);
+        self.play_button.clear();
+        self.play_button.push(layout[2], ());
     }
 
-    fn render_binds(&self) -> Vec<&'static str> {
-        vec!["play - p"]
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::Main)
     }
 
     fn update(
@@ -75,19 +82,26 @@ impl TabUi for MainMenu {
         crosstermevent: event::Event,
         state: &mut GlobalState,
     ) -> anyhow::Result<()> {
-        if let event::Event::Key(key_event) = crosstermevent {
-            if key_event.kind == event::KeyEventKind::Press && !state.is_typing {
-                match key_event.code {
-                    KeyCode::Char('p') => self.playing = !self.playing,
-                    _ => {}
-                }
+        match crosstermevent {
+            event::Event::Key(key_event)
+                if key_event.kind == event::KeyEventKind::Press
+                    && !state.is_typing
+                    && state.keymap.action(KeyContext::Main, &key_event) == Some(Action::Play) =>
+            {
+                self.playing = !self.playing
+            }
+            event::Event::Mouse(mouse)
+                if is_left_click(&mouse) && self.play_button.hit(&mouse).is_some() =>
+            {
+                self.playing = !self.playing
             }
+            _ => {}
         }
 
         Ok(())
     }
 
-    fn on_focus(&mut self) -> anyhow::Result<()> {
+    fn on_focus(&mut self, _state: &mut GlobalState) -> anyhow::Result<()> {
         Ok(())
     }
 }
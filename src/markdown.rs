@@ -0,0 +1,228 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use ratatui::prelude::*;
+
+use crate::theme::theme;
+
+/// markdown as styled lines, wrapping is left to the paragraph they end up in
+pub fn render(markdown: &str) -> Vec<Line<'static>> {
+    let mut renderer = Renderer::default();
+    for event in Parser::new_ext(
+        markdown,
+        Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS,
+    ) {
+        renderer.event(event);
+    }
+    renderer.flush();
+    while renderer
+        .lines
+        .last()
+        .is_some_and(|line| line.spans.is_empty())
+    {
+        renderer.lines.pop();
+    }
+
+    renderer.lines
+}
+
+#[derive(Default)]
+struct Renderer {
+    lines: Vec<Line<'static>>,
+    /// the line being built
+    spans: Vec<Span<'static>>,
+    /// every open inline style, the innermost last
+    styles: Vec<Style>,
+    /// the next number of every open list, none for bullets
+    lists: Vec<Option<u64>>,
+    quotes: usize,
+    code_block: bool,
+    /// where the open links go, shown after their text
+    links: Vec<String>,
+}
+
+impl Renderer {
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default().fg(theme().text), |style, inner| {
+                style.patch(*inner)
+            })
+    }
+
+    fn push(&mut self, text: impl Into<String>, style: Style) {
+        if self.spans.is_empty() && self.quotes > 0 {
+            self.spans.push(Span::styled(
+                "│ ".repeat(self.quotes),
+                Style::default().fg(theme().muted),
+            ));
+        }
+        self.spans.push(Span::styled(text.into(), style));
+    }
+
+    fn flush(&mut self) {
+        if !self.spans.is_empty() {
+            self.lines.push(Line::from(std::mem::take(&mut self.spans)));
+        }
+    }
+
+    /// one empty line between blocks, never two
+    fn gap(&mut self) {
+        self.flush();
+        if self.lines.last().is_some_and(|line| !line.spans.is_empty()) {
+            self.lines.push(Line::default());
+        }
+    }
+
+    fn event(&mut self, event: Event<'_>) {
+        match event {
+            Event::Start(tag) => self.start(tag),
+            Event::End(tag) => self.end(tag),
+            Event::Text(text) if self.code_block => {
+                for line in text.lines() {
+                    self.push(
+                        format!("  {line}"),
+                        Style::default().fg(theme().info).bg(theme().surface),
+                    );
+                    self.flush();
+                }
+            }
+            Event::Text(text) => self.push(text.into_string(), self.style()),
+            Event::Code(code) => self.push(
+                code.into_string(),
+                Style::default().fg(theme().info).bg(theme().surface),
+            ),
+            Event::Html(html) => self.push(
+                html.trim_end().to_owned(),
+                Style::default().fg(theme().muted),
+            ),
+            Event::SoftBreak => self.push(" ", self.style()),
+            Event::HardBreak => self.flush(),
+            Event::Rule => {
+                self.gap();
+                self.push("─".repeat(20), Style::default().fg(theme().muted));
+                self.gap();
+            }
+            Event::TaskListMarker(done) => self.push(
+                if done { "[x] " } else { "[ ] " },
+                Style::default().fg(theme().accent),
+            ),
+            Event::FootnoteReference(label) => {
+                self.push(format!("[{label}]"), Style::default().fg(theme().muted))
+            }
+        }
+    }
+
+    fn start(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Heading(level, ..) => {
+                self.gap();
+                let style = Style::default().fg(theme().accent).bold();
+                self.styles.push(match level {
+                    HeadingLevel::H1 => style.underlined(),
+                    HeadingLevel::H2 => style,
+                    _ => Style::default().fg(theme().selected).bold(),
+                });
+            }
+            Tag::Paragraph if self.lists.is_empty() => self.gap(),
+            Tag::Paragraph | Tag::TableHead | Tag::TableRow | Tag::FootnoteDefinition(_) => {
+                self.flush()
+            }
+            Tag::BlockQuote => {
+                self.gap();
+                self.quotes += 1;
+                self.styles
+                    .push(Style::default().fg(theme().muted).italic());
+            }
+            Tag::CodeBlock(kind) => {
+                self.gap();
+                if let CodeBlockKind::Fenced(language) = kind {
+                    if !language.is_empty() {
+                        self.push(language.into_string(), Style::default().fg(theme().muted));
+                        self.flush();
+                    }
+                }
+                self.code_block = true;
+            }
+            Tag::List(start) => {
+                if self.lists.is_empty() {
+                    self.gap();
+                }
+                self.flush();
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                self.flush();
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{indent}{}. ", *number - 1)
+                    }
+                    _ => format!("{indent}• "),
+                };
+                self.push(marker, Style::default().fg(theme().accent));
+            }
+            Tag::Table(_) => self.gap(),
+            Tag::TableCell => {
+                if !self.spans.is_empty() {
+                    self.push(" │ ", Style::default().fg(theme().muted));
+                }
+            }
+            Tag::Emphasis => self.styles.push(Style::default().italic()),
+            Tag::Strong => self.styles.push(Style::default().bold()),
+            Tag::Strikethrough => self.styles.push(Style::default().crossed_out()),
+            Tag::Link(_, url, _) => {
+                self.links.push(url.into_string());
+                self.styles
+                    .push(Style::default().fg(theme().accent).underlined());
+            }
+            Tag::Image(_, url, _) => {
+                self.links.push(url.into_string());
+                self.push("image: ", Style::default().fg(theme().muted));
+                self.styles.push(Style::default().fg(theme().muted));
+            }
+        }
+    }
+
+    fn end(&mut self, tag: Tag<'_>) {
+        match tag {
+            Tag::Heading(..) => {
+                self.styles.pop();
+                self.gap();
+            }
+            Tag::Paragraph if self.lists.is_empty() => self.gap(),
+            Tag::Paragraph | Tag::Item | Tag::TableHead | Tag::TableRow => self.flush(),
+            Tag::FootnoteDefinition(_) | Tag::TableCell => {}
+            Tag::BlockQuote => {
+                self.styles.pop();
+                self.flush();
+                self.quotes -= 1;
+                self.gap();
+            }
+            Tag::CodeBlock(_) => {
+                self.code_block = false;
+                self.gap();
+            }
+            Tag::List(_) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.gap();
+                }
+            }
+            Tag::Table(_) => self.gap(),
+            Tag::Emphasis | Tag::Strong | Tag::Strikethrough => _ = self.styles.pop(),
+            Tag::Link(..) | Tag::Image(..) => {
+                self.styles.pop();
+                let url = self.links.pop().unwrap_or_default();
+                // autolinks already show where they go
+                let shown = self
+                    .spans
+                    .last()
+                    .is_some_and(|span| span.content.as_ref() == url);
+                if !url.is_empty() && !shown {
+                    self.push(format!(" ({url})"), Style::default().fg(theme().muted));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result as AnyResult};
+use serde::Deserialize;
+
+use crate::{mods_views::InstalledModPartial, net};
+
+pub const MASTER_SERVER_URL: &str = "https://northstar.tf/client/servers";
+
+/// a server as the master server lists it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Server {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub map: String,
+    pub playlist: String,
+    pub region: String,
+    pub player_count: u32,
+    pub max_players: u32,
+    pub has_password: bool,
+    pub mod_info: ModInfo,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ModInfo {
+    #[serde(rename = "Mods")]
+    pub mods: Vec<ServerMod>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ServerMod {
+    pub name: String,
+    pub version: String,
+    pub required_on_client: bool,
+    /// the thunderstore author, only hand made lists have it
+    pub author: Option<String>,
+}
+
+impl Server {
+    /// the mods a client needs to join
+    pub fn required_mods(&self) -> impl Iterator<Item = &ServerMod> {
+        self.mod_info
+            .mods
+            .iter()
+            .filter(|server_mod| server_mod.required_on_client)
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.player_count >= self.max_players
+    }
+
+    /// case insensitive, looks at everything shown in the list
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.to_lowercase();
+        [
+            &self.name,
+            &self.description,
+            &self.map,
+            &self.playlist,
+            &self.region,
+        ]
+        .into_iter()
+        .any(|field| field.to_lowercase().contains(&search))
+    }
+}
+
+/// how a required mod compares to what is installed in the profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModStatus {
+    Installed,
+    Disabled,
+    /// installed in another version
+    OtherVersion(String),
+    Missing,
+}
+
+impl ModStatus {
+    pub fn of(server_mod: &ServerMod, installed: &[InstalledModPartial]) -> Self {
+        let Some(nsmod) = installed
+            .iter()
+            .find(|nsmod| nsmod.mod_json.name == server_mod.name)
+        else {
+            return ModStatus::Missing;
+        };
+
+        if nsmod.mod_json.version != server_mod.version {
+            ModStatus::OtherVersion(nsmod.mod_json.version.clone())
+        } else if !nsmod.enabled {
+            ModStatus::Disabled
+        } else {
+            ModStatus::Installed
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        *self == ModStatus::Installed
+    }
+}
+
+/// the server list from the master server, or from a json file when `url` isn't http(s)
+pub fn fetch_servers(url: &str) -> AnyResult<Vec<Server>> {
+    serde_json::from_slice(&net::fetch(url)?)
+        .with_context(|| format!("{url} didn't return a server list"))
+}
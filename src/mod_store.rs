@@ -0,0 +1,322 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    dev_link::linked_source,
+    install::GameInstall,
+    local_install::{read_folder, Files},
+    prepare::copy_dir,
+};
+
+/// in the game dir since hard links only work on the same drive
+pub const STORE_NAME: &str = "drydoct-store";
+/// which mod dirs link to which mod in the store
+const INDEX_NAME: &str = "index.json";
+
+/// every mod version installed once and stored by the sha256 of its files, profiles get hard
+/// links to it instead of their own copy
+#[derive(Debug, Clone)]
+pub struct ModStore {
+    pub dir: PathBuf,
+}
+
+/// a mod in the store and the mod dirs that link to it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoreEntry {
+    pub hash: String,
+    /// the dir it was first installed as
+    pub name: String,
+    /// it is removed from the store once this is empty
+    pub refs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct StoreIndex {
+    entries: Vec<StoreEntry>,
+}
+
+/// how a mod dir got its files from the store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linked {
+    HardLinks,
+    /// hard links didn't work, the whole dir is a link instead
+    Symlink,
+    /// neither kind of link worked
+    Copy,
+}
+
+/// what deduplicating the profiles did
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Deduplicated {
+    /// mod dirs that link to the store now
+    pub mods: usize,
+    /// the size of the copies that were replaced by links
+    pub saved: u64,
+}
+
+impl ModStore {
+    /// none unless shared mods are turned on
+    pub fn of(install: &GameInstall) -> Option<Self> {
+        install.mod_store.clone().map(|dir| Self { dir })
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_NAME)
+    }
+
+    pub fn entry_dir(&self, hash: &str) -> PathBuf {
+        self.dir.join(hash)
+    }
+
+    /// an index that can't be read is started over, its mods stay until they are deduplicated again
+    fn load(&self, install: &GameInstall) -> StoreIndex {
+        let Result::Ok(index) = install.fs.read_to_string(&self.index_path()) else {
+            return StoreIndex::default();
+        };
+
+        serde_json::from_str(&index).unwrap_or_else(|err| {
+            log::warn!("starting the mod store over, its index is broken: {err}");
+            StoreIndex::default()
+        })
+    }
+
+    fn save(&self, install: &GameInstall, index: &StoreIndex) -> AnyResult<()> {
+        install.write(
+            &self.index_path(),
+            serde_json::to_string_pretty(index)
+                .expect("the store index always serializes")
+                .as_bytes(),
+        )
+    }
+
+    pub fn entries(&self, install: &GameInstall) -> Vec<StoreEntry> {
+        self.load(install).entries
+    }
+
+    /// puts `files` into the store as `mod_dir` and links them back, whatever `mod_dir` linked to
+    /// before is released
+    pub fn install(
+        &self,
+        install: &GameInstall,
+        mod_dir: &Path,
+        files: &Files,
+    ) -> AnyResult<Linked> {
+        let hash = hash(files);
+        let dir = self.entry_dir(&hash);
+        if !install.fs.is_dir(&dir) {
+            for (path, contents) in files {
+                install.write(&dir.join(path), contents)?;
+            }
+        }
+        self.register(install, &hash, mod_dir)?;
+
+        let linked = self.link(install, &hash, mod_dir, files)?;
+        self.forget(install, mod_dir, Some(&hash))?;
+        Ok(linked)
+    }
+
+    /// forgets that `path` or any mod dir under it links to the store, mods nothing links to
+    /// anymore are removed
+    pub fn release(&self, install: &GameInstall, path: &Path) -> AnyResult<()> {
+        self.forget(install, path, None)
+    }
+
+    fn forget(&self, install: &GameInstall, path: &Path, keep: Option<&str>) -> AnyResult<()> {
+        let fs = install.fs.as_ref();
+        let mut index = self.load(install);
+        let mut changed = false;
+        for entry in index
+            .entries
+            .iter_mut()
+            .filter(|entry| Some(entry.hash.as_str()) != keep)
+        {
+            let count = entry.refs.len();
+            // dirs removed outside of drydoct don't hold on to anything either
+            entry
+                .refs
+                .retain(|mod_dir| !mod_dir.starts_with(path) && fs.exists(mod_dir));
+            changed |= entry.refs.len() != count;
+        }
+        if !changed {
+            return Ok(());
+        }
+
+        let (used, unused): (Vec<StoreEntry>, Vec<StoreEntry>) = index
+            .entries
+            .drain(..)
+            .partition(|entry| !entry.refs.is_empty());
+        index.entries = used;
+        for entry in unused {
+            let dir = self.entry_dir(&entry.hash);
+            log::info!("removing {} from the mod store", entry.name);
+            if fs.exists(&dir) {
+                fs.remove_dir_all(&dir)
+                    .with_context(|| format!("couldn't remove {}", dir.display()))?;
+            }
+        }
+
+        self.save(install, &index)
+    }
+
+    /// adds `mod_dir` to the links of the stored mod
+    fn register(&self, install: &GameInstall, hash: &str, mod_dir: &Path) -> AnyResult<()> {
+        let mut index = self.load(install);
+        match index.entries.iter_mut().find(|entry| entry.hash == hash) {
+            Some(entry) if entry.refs.iter().any(|other| other == mod_dir) => return Ok(()),
+            Some(entry) => entry.refs.push(mod_dir.to_path_buf()),
+            None => index.entries.push(StoreEntry {
+                hash: hash.to_owned(),
+                name: mod_dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                refs: vec![mod_dir.to_path_buf()],
+            }),
+        }
+
+        self.save(install, &index)
+    }
+
+    /// hard links every file, links the whole dir when that doesn't work and copies as a last resort
+    fn link(
+        &self,
+        install: &GameInstall,
+        hash: &str,
+        mod_dir: &Path,
+        files: &Files,
+    ) -> AnyResult<Linked> {
+        let fs = install.fs.as_ref();
+        let dir = self.entry_dir(hash);
+        fs.create_dir_all(mod_dir)
+            .with_context(|| format!("couldn't create {}", mod_dir.display()))?;
+        let hard_linked = files.keys().try_for_each(|path| {
+            let link = mod_dir.join(path);
+            if let Some(parent) = link.parent() {
+                fs.create_dir_all(parent)?;
+            }
+            fs.hard_link(&dir.join(path), &link)
+        });
+        let Err(err) = hard_linked else {
+            return Ok(Linked::HardLinks);
+        };
+
+        log::warn!(
+            "couldn't hard link {} to the mod store, linking the dir instead: {err}",
+            mod_dir.display()
+        );
+        fs.remove_dir_all(mod_dir)
+            .with_context(|| format!("couldn't remove {}", mod_dir.display()))?;
+        if fs.symlink_dir(&dir, mod_dir).is_ok() {
+            return Ok(Linked::Symlink);
+        }
+        copy_dir(install, &dir, mod_dir)?;
+
+        Ok(Linked::Copy)
+    }
+
+    /// the stored mod `mod_dir` links to
+    fn find(&self, install: &GameInstall, mod_dir: &Path) -> Option<StoreEntry> {
+        let canonical = |path: &Path| install.fs.canonicalize(path).ok();
+        let mod_dir_canonical = canonical(mod_dir);
+        self.load(install).entries.into_iter().find(|entry| {
+            entry.refs.iter().any(|other| {
+                other == mod_dir
+                    || mod_dir_canonical.is_some() && canonical(other) == mod_dir_canonical
+            })
+        })
+    }
+
+    /// moves the mods of every profile into the store, a mod that is the same in several profiles
+    /// is only kept once
+    pub fn deduplicate(&self, install: &GameInstall) -> AnyResult<Deduplicated> {
+        let fs = install.fs.as_ref();
+        let mut deduplicated = Deduplicated::default();
+
+        for profile in install.profiles()? {
+            let mods_dir = install.with_profile(&profile).mods_dir();
+            for entry in fs
+                .read_dir(&mods_dir)
+                .with_context(|| format!("couldn't read {}", mods_dir.display()))?
+            {
+                // dev links are synced from their folder and links into the store are done
+                if !entry.is_dir || linked_source(install, &entry.path).is_some() {
+                    continue;
+                }
+
+                let mut files = Files::new();
+                read_folder(install, &entry.path, &entry.path, &mut files)?;
+                let hash = hash(&files);
+                if self
+                    .find(install, &entry.path)
+                    .is_some_and(|stored| stored.hash == hash)
+                {
+                    continue;
+                }
+
+                let dir = self.entry_dir(&hash);
+                let stored = fs.is_dir(&dir);
+                if stored {
+                    fs.remove_dir_all(&entry.path)
+                        .with_context(|| format!("couldn't remove {}", entry.path.display()))?;
+                } else {
+                    // the first copy is moved so nothing is written twice
+                    fs.create_dir_all(&self.dir)
+                        .with_context(|| format!("couldn't create {}", self.dir.display()))?;
+                    fs.rename(&entry.path, &dir).with_context(|| {
+                        format!("couldn't move {} to the mod store", entry.path.display())
+                    })?;
+                }
+                self.forget(install, &entry.path, Some(&hash))?;
+                self.register(install, &hash, &entry.path)?;
+
+                if self.link(install, &hash, &entry.path, &files)? != Linked::Copy {
+                    deduplicated.mods += 1;
+                    if stored {
+                        deduplicated.saved += files
+                            .values()
+                            .map(|contents| contents.len() as u64)
+                            .sum::<u64>();
+                    }
+                }
+            }
+        }
+
+        Ok(deduplicated)
+    }
+}
+
+/// `to` gets the same mod as `from`, linked when `from` comes from the store and copied otherwise
+pub fn copy_mod(install: &GameInstall, from: &Path, to: &Path) -> AnyResult<()> {
+    let stored = ModStore::of(install).and_then(|store| Some((store.find(install, from)?, store)));
+    let Some((entry, store)) = stored else {
+        return copy_dir(install, from, to);
+    };
+
+    let mut files = Files::new();
+    let dir = store.entry_dir(&entry.hash);
+    read_folder(install, &dir, &dir, &mut files)?;
+    store.register(install, &entry.hash, to)?;
+    store.link(install, &entry.hash, to, &files)?;
+
+    Ok(())
+}
+
+/// the same files under the same paths always give the same hash
+fn hash(files: &Files) -> String {
+    let mut hasher = Sha256::new();
+    for (path, contents) in files {
+        hasher.update(path.as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(contents);
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
@@ -1,40 +1,504 @@
-use std::path::PathBuf;
+use std::{cell::Cell, rc::Rc, sync::Arc};
 
-use anyhow::{Ok, Result as AnyResult};
-use crossterm::event::{self, KeyCode, KeyModifiers};
+use anyhow::{Context, Ok, Result as AnyResult};
+use crossterm::event::{self, MouseEventKind};
 use ratatui::{prelude::*, widgets::*};
 
 use crate::{
     app::{GlobalState, TabUi},
-    colors::{BACKGROUND, SELECT},
-    mods_views::ModView,
+    background::Background,
+    dev_link::{self, LinkKind},
+    disk_usage::{EntrySize, UsageReport},
+    docs::{fetch_latest, ModDocs, Pending},
+    docs_view::DocsViewer,
+    hooks::ChangeHooks,
+    keymap::{Action, KeyContext, Keymap},
+    local_install::LocalPackage,
+    mods_views::{reload_mods, ModView},
+    mouse::{is_left_click, Hitboxes},
+    pack,
+    package_cache::format_size,
+    packages_view::{find_installed, BrowserEvent, PackageBrowser},
+    path_input::{PathInput, PathInputEvent},
+    theme::theme,
 };
 
-const MOD_DISPLAY_SIZE: usize = 5;
-static GAME_PATH: &str = r#"C:\Program Files (x86)\Steam\steamapps\common\Titanfall2"#;
+const VIEWS: [&str; 4] = ["Mods", "Plugins", "Packages", "cta"];
+const SIDE_WIDTH: u16 = 10;
+const MIN_CARD_WIDTH: u16 = 24;
+const MIN_CARD_HEIGHT: u16 = 6;
+/// below this width the grid turns into a list
+const COMPACT_WIDTH: u16 = 60;
+/// where the game is looked for when the config doesn't say
+// TODO: add path search
+pub static GAME_PATH: &str = r#"C:\Program Files (x86)\Steam\steamapps\common\Titanfall2"#;
+
+/// how many cards fit in the area the grid was last drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Grid {
+    columns: usize,
+    rows: usize,
+    compact: bool,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            columns: 5,
+            rows: 5,
+            compact: false,
+        }
+    }
+}
+
+impl Grid {
+    fn fit(area: Rect, compact: bool) -> Self {
+        if compact {
+            return Self {
+                columns: 1,
+                rows: area.height.max(1) as usize,
+                compact,
+            };
+        }
+
+        Self {
+            columns: (area.width / MIN_CARD_WIDTH).max(1) as usize,
+            rows: (area.height / MIN_CARD_HEIGHT).max(1) as usize,
+            compact,
+        }
+    }
+
+    fn page_size(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    /// the row major index of every cell on the page starting at `page_start` and where it goes
+    fn cells(&self, area: Rect, page_start: usize) -> Vec<(usize, Rect)> {
+        // split by hand, `Layout` spreads the leftover cells differently from run to run
+        let split = |start: u16, length: u16, parts: usize| {
+            (0..parts)
+                .map(|part| {
+                    let from = (length as usize * part / parts) as u16;
+                    let to = (length as usize * (part + 1) / parts) as u16;
+                    (start + from, to - from)
+                })
+                .collect::<Vec<(u16, u16)>>()
+        };
+
+        split(area.y, area.height, self.rows)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(row, (y, height))| {
+                split(area.x, area.width, self.columns)
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(column, (x, width))| {
+                        (
+                            page_start + row * self.columns + column,
+                            Rect::new(x, y, width, height),
+                        )
+                    })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathPurpose {
+    Install,
+    Link,
+}
+
+impl PathPurpose {
+    fn title(self) -> &'static str {
+        match self {
+            PathPurpose::Install => "install from a zip or folder",
+            PathPurpose::Link => "link a mod folder for development",
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct Mods {
     mod_view: ModView,
-    page_offset: usize,
     selected_mod: usize,
     selected_view: usize,
+    /// whether the details of the selected entry are shown over the grid
+    details: bool,
+    /// a path that is being typed and what it is for
+    path_input: Option<(PathInput, PathPurpose)>,
+    packages: PackageBrowser,
+    /// the readme and changelog of the selected entry
+    docs: Option<DocsViewer>,
+    /// the newest archive of the package the docs are for
+    downloading_docs: Background<Vec<u8>>,
+    /// copied on reload with the profile it's shown for, the sizes come in on another thread
+    usage: Option<(Arc<UsageReport>, String)>,
+    grid: Cell<Grid>,
+    views: Hitboxes<usize>,
+    cards: Hitboxes<usize>,
 }
 
 impl TabUi for Mods {
     fn render_ui(&self, frame: &mut ratatui::Frame<'_>, rect: Rect) {
+        self.views.clear();
+        self.cards.clear();
+        frame.render_widget(Block::new().bg(theme().background), rect);
+
+        let compact = rect.width < COMPACT_WIDTH || rect.height < MIN_CARD_HEIGHT + 1;
+        let grid_area = if compact {
+            self.render_view_bar(frame, rect)
+        } else {
+            self.render_side_bar(frame, rect)
+        };
+        let grid_area = match &self.mod_view {
+            ModView::Packages(packages) => {
+                self.packages.render_bar(frame, grid_area, packages.len())
+            }
+            _ => grid_area,
+        };
+
+        let grid = Grid::fit(grid_area, compact);
+        self.grid.set(grid);
+        let page_start = self.page_start();
+
+        for (e, rect) in grid.cells(grid_area, page_start) {
+            if compact && e >= self.mod_view.len() {
+                break;
+            }
+            self.cards.push(rect, e);
+
+            let selected_style = if self.selected_mod == e {
+                Style::default().fg(theme().selected)
+            } else {
+                Style::default()
+            };
+
+            if compact {
+                frame.render_widget(
+                    Paragraph::new(Line::from(
+                        [
+                            self.title(e).spans,
+                            self.size(e)
+                                .map(|size| {
+                                    vec![Span::styled(
+                                        format!(" {}", format_size(size.size)),
+                                        Style::default().fg(theme().muted),
+                                    )]
+                                })
+                                .unwrap_or_default(),
+                            vec![
+                                Span::raw(" "),
+                                Span::raw(self.mod_view.get_summary(e).unwrap_or_default()),
+                            ],
+                        ]
+                        .concat(),
+                    ))
+                    .fg(theme().text)
+                    .style(if self.selected_mod == e {
+                        Style::default().bg(theme().surface).bold()
+                    } else {
+                        Style::default()
+                    }),
+                    rect,
+                );
+                continue;
+            }
+
+            let card = match self.mod_view.get_as_paragraph(e, self.size(e)) {
+                Some(paragraph) => Paragraph::new(paragraph)
+                    .bg(theme().background)
+                    .wrap(Wrap { trim: true })
+                    .fg(theme().text)
+                    .alignment(Alignment::Center)
+                    .bold()
+                    .block(
+                        Block::default()
+                            .title(self.title(e))
+                            .borders(Borders::all())
+                            .style(selected_style),
+                    ),
+                None => Paragraph::new("EMPTY")
+                    .bg(theme().background)
+                    .fg(theme().muted)
+                    .alignment(Alignment::Center)
+                    .block(
+                        Block::default()
+                            .borders(Borders::all())
+                            .style(selected_style),
+                    ),
+            };
+            frame.render_widget(card, rect);
+        }
+
+        if self.details {
+            self.render_details(frame, grid_area);
+        }
+
+        if let Some((input, purpose)) = &self.path_input {
+            input.render(frame, rect, purpose.title());
+        }
+        self.packages.render_prompt(frame, rect);
+        if let Some(docs) = &self.docs {
+            docs.render(frame, rect);
+        }
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        if let Some(docs) = &self.docs {
+            return docs.render_binds(keymap);
+        }
+        if let Some((input, _)) = &self.path_input {
+            return input.render_binds();
+        }
+        if self.packages.is_typing() {
+            return self.packages.render_binds(keymap);
+        }
+
+        let mut binds = keymap.hints(KeyContext::Mods);
+        if matches!(self.mod_view, ModView::Packages(_)) {
+            binds.extend(self.packages.render_binds(keymap));
+        }
+        binds
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        if let event::Event::Key(key_event) = crosstermevent {
+            if let Some(docs) = self.docs.as_mut() {
+                if key_event.kind == event::KeyEventKind::Press
+                    && docs.handle_key(key_event, &state.keymap)
+                {
+                    self.docs = None;
+                    self.downloading_docs = Background::default();
+                }
+
+                return Ok(());
+            }
+
+            if let Some((input, purpose)) = self.path_input.as_mut() {
+                if key_event.kind == event::KeyEventKind::Press {
+                    let purpose = *purpose;
+                    match input.handle_key(key_event, state.install.fs.as_ref()) {
+                        PathInputEvent::Submit if purpose == PathPurpose::Install => {
+                            self.install_from_input(state)?
+                        }
+                        PathInputEvent::Submit => self.link_from_input(state)?,
+                        PathInputEvent::Cancel => {
+                            self.path_input = None;
+                            state.is_typing = false;
+                        }
+                        PathInputEvent::Edited => {}
+                    }
+                }
+
+                return Ok(());
+            }
+
+            if key_event.kind == event::KeyEventKind::Press
+                && matches!(self.mod_view, ModView::Packages(_))
+            {
+                match self.packages.handle_key(key_event, state)? {
+                    BrowserEvent::Changed => {
+                        self.reload(state)?;
+                        self.select(self.selected_mod);
+                        return Ok(());
+                    }
+                    BrowserEvent::Handled => return Ok(()),
+                    BrowserEvent::Ignored => {}
+                }
+            }
+
+            if key_event.kind == event::KeyEventKind::Press && !state.is_typing {
+                match state.keymap.action(KeyContext::Mods, &key_event) {
+                    Some(Action::Reload) => {
+                        self.packages.forget_index();
+                        state.disk_usage.measure(&state.install);
+                        let skipped = self.reload(state)?;
+                        self.select(self.selected_mod);
+                        notify_skipped(state, skipped);
+                        state
+                            .notifier
+                            .info(format!("reloaded {} entries", self.mod_view.len()));
+                    }
+                    Some(Action::NextPage) => self.next_page(),
+                    Some(Action::PreviousPage) => self.previous_page(),
+                    Some(Action::Left) => self.select(self.selected_mod.saturating_sub(1)),
+                    Some(Action::Right) => self.select(self.selected_mod + 1),
+                    Some(Action::Down) => self.select(self.selected_mod + self.grid.get().columns),
+                    Some(Action::Up) => {
+                        self.select(self.selected_mod.saturating_sub(self.grid.get().columns))
+                    }
+                    Some(Action::NextView) => {
+                        // there are only 4 views
+                        self.switch_view((self.selected_view + 1) % 4, state)?;
+                    }
+                    Some(Action::Open) => self.details = !self.details,
+                    Some(Action::Toggle) => self.toggle_selected(state)?,
+                    Some(Action::NextProfile) => self.next_profile(state)?,
+                    Some(Action::Install) => {
+                        self.path_input = Some((PathInput::default(), PathPurpose::Install));
+                        state.is_typing = true;
+                    }
+                    Some(Action::Pack) => self.pack_selected(state)?,
+                    Some(Action::Docs) => self.open_docs(state)?,
+                    Some(Action::Link) => {
+                        self.path_input = Some((PathInput::default(), PathPurpose::Link));
+                        state.is_typing = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let event::Event::Mouse(mouse) = crosstermevent {
+            if state.is_typing {
+                return Ok(());
+            }
+            if let Some(docs) = self.docs.as_mut() {
+                docs.handle_mouse(mouse);
+                return Ok(());
+            }
+
+            match mouse.kind {
+                MouseEventKind::ScrollDown => self.next_page(),
+                MouseEventKind::ScrollUp => self.previous_page(),
+                _ if !is_left_click(&mouse) => {}
+                _ if self.details => self.details = false,
+                _ => {
+                    if let Some(view) = self.views.hit(&mouse) {
+                        self.switch_view(view, state)?;
+                    } else if let Some(card) = self.cards.hit(&mouse) {
+                        self.selected_mod = card;
+                        self.details = state.clicks.is_double();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.reload(state)?;
+        self.select(self.selected_mod);
+
+        Ok(())
+    }
+
+    fn tick(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let fetched = self.packages.tick();
+        if let Some(docs) = self.docs.as_mut() {
+            if let Some(Pending::Index(nsmod)) = docs.pending() {
+                match self.packages.index() {
+                    Some(index) => {
+                        let pending = Pending::for_mod(nsmod, index);
+                        docs.set_pending(pending);
+                        self.fetch_docs(state);
+                    }
+                    // the update check is left out
+                    None if fetched.is_err() => docs.set_pending(None),
+                    None => {}
+                }
+            }
+        }
+        if fetched? && matches!(self.mod_view, ModView::Packages(_)) {
+            self.reload(state)?;
+            self.select(self.selected_mod);
+        }
+
+        match (self.downloading_docs.poll(), self.docs.as_mut()) {
+            (Some(Result::Ok(bytes)), Some(docs)) => docs.add_latest(&bytes),
+            (Some(Err(err)), Some(docs)) => {
+                docs.set_pending(None);
+                return Err(err.context(format!(
+                    "couldn't get the readme and changelog of {}",
+                    docs.name()
+                )));
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_disk_usage(&mut self, state: &GlobalState) {
+        self.load_usage(state);
+    }
+
+    fn overlay(&self) -> Option<KeyContext> {
+        self.docs.as_ref().map(|_| KeyContext::Docs)
+    }
+
+    #[cfg(test)]
+    fn is_busy(&self) -> bool {
+        self.packages.is_fetching() || self.downloading_docs.is_running()
+    }
+}
+
+impl Mods {
+    /// packages are filtered here since the view doesn't know the query or where the index is,
+    /// returns the amount of entries that couldn't be loaded
+    fn reload(&mut self, state: &GlobalState) -> AnyResult<usize> {
+        self.load_usage(state);
+        if let ModView::Packages(packages) = &mut self.mod_view {
+            *packages = self.packages.entries(state)?;
+            return Ok(0);
+        }
+
+        self.mod_view.reload(&state.install)
+    }
+
+    fn load_usage(&mut self, state: &GlobalState) {
+        self.usage = state
+            .disk_usage
+            .report()
+            .map(|report| (report, state.install.profile.clone()));
+    }
+
+    /// everything in the profile, not only what the view shows
+    fn profile_total(&self) -> Option<u64> {
+        let (report, profile) = self.usage.as_ref()?;
+        Some(report.profile(profile)?.total)
+    }
+
+    fn size(&self, index: usize) -> Option<&EntrySize> {
+        let (report, profile) = self.usage.as_ref()?;
+        self.mod_view.size(index, report.profile(profile)?)
+    }
+
+    /// the page always follows the selection so a resize never leaves it off screen
+    fn page_start(&self) -> usize {
+        let page_size = self.grid.get().page_size();
+        self.selected_mod / page_size * page_size
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected_mod = index.min(self.mod_view.len().saturating_sub(1));
+    }
+
+    fn next_page(&mut self) {
+        let page_size = self.grid.get().page_size();
+        let last_page = self.mod_view.len().saturating_sub(1) / page_size * page_size;
+        if self.page_start() < last_page {
+            self.select(self.page_start() + page_size);
+        }
+    }
+
+    fn previous_page(&mut self) {
+        let page_size = self.grid.get().page_size();
+        self.select(self.page_start().saturating_sub(page_size));
+    }
+
+    /// the view names down the left side, returns the area left for the grid
+    fn render_side_bar(&self, frame: &mut Frame<'_>, rect: Rect) -> Rect {
         let layout = Layout::new()
             .constraints([
-                Constraint::Percentage(5),
-                Constraint::Percentage(1),
-                Constraint::Percentage(94),
+                Constraint::Length(SIDE_WIDTH),
+                Constraint::Length(1),
+                Constraint::Min(0),
             ])
             .direction(Direction::Horizontal)
             .split(rect);
-        let mods_layout = Layout::new()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(20); MOD_DISPLAY_SIZE])
-            .split(layout[2]);
 
         let side_layout = Layout::new()
             .constraints([
@@ -42,178 +506,353 @@ impl TabUi for Mods {
                 Constraint::Length(2),
                 Constraint::Length(2),
                 Constraint::Length(2),
-                Constraint::Length(layout[0].height - 2 * 4),
+                Constraint::Min(0),
             ])
             .split(layout[0]);
-        ["Mods", "Plugins", "Packages", "cta"]
+        VIEWS
             .into_iter()
             .enumerate()
-            .zip(side_layout.into_iter().cloned())
+            .zip(side_layout.iter().cloned())
             .for_each(|((e, label), rect)| {
                 frame.render_widget(
                     Paragraph::new(label)
-                        .style(
-                            e.eq(&self.selected_view)
-                                .then(|| Style::default().red())
-                                .unwrap_or_else(|| Style::default().white()),
-                        )
-                        .alignment(Alignment::Center)
-                        .bg(BACKGROUND),
+                        .style(self.view_style(e))
+                        .alignment(Alignment::Center),
                     rect,
-                )
+                );
+                self.views.push(rect, e);
             });
-        frame.render_widget(Block::new().bg(BACKGROUND), side_layout[4]);
+        if let Some(total) = self.profile_total() {
+            frame.render_widget(
+                Paragraph::new(vec![Line::raw("profile"), Line::raw(format_size(total))])
+                    .fg(theme().muted)
+                    .alignment(Alignment::Center),
+                side_layout[4],
+            );
+        }
 
         frame.render_widget(
             Block::new()
-                .bg(BACKGROUND)
                 .borders(Borders::LEFT)
-                .white()
+                .fg(theme().text)
                 .title(self.mod_view.len().to_string()),
             layout[1],
         );
 
-        mods_layout
-            .into_iter()
-            .cloned()
-            .map(|vertical| {
-                Layout::new()
-                    .constraints([Constraint::Percentage(20); MOD_DISPLAY_SIZE])
-                    .split(vertical)
-            })
-            .enumerate()
-            .map(|(i, layout)| {
-                layout
-                    .into_iter()
-                    .enumerate()
-                    .map(move |(e, rect)| (e * MOD_DISPLAY_SIZE + i + self.page_offset, rect))
-                    .map(|(e, rect)| {
-                        self.mod_view
-                            .get_as_paragraph(e)
-                            .map(|paragraph| {
-                                (
-                                    Paragraph::new(paragraph)
-                                        .bg(BACKGROUND)
-                                        .wrap(Wrap { trim: true })
-                                        .fg(SELECT)
-                                        .alignment(Alignment::Center)
-                                        .bold()
-                                        .block(
-                                            Block::default()
-                                                .title(
-                                                    self.mod_view
-                                                        .get_title(e)
-                                                        .unwrap_or_else(|| "UNK"),
-                                                )
-                                                .title_style(Style::default().light_red())
-                                                .borders(Borders::all())
-                                                .style(
-                                                    self.selected_mod
-                                                        .eq(&e)
-                                                        .then(|| Style::default().red())
-                                                        .unwrap_or_default(),
-                                                ),
-                                        ),
-                                    rect.clone(),
-                                )
-                            })
-                            .unwrap_or_else(|| {
-                                (
-                                    Paragraph::new("EMPTY")
-                                        .bg(BACKGROUND)
-                                        .alignment(Alignment::Center)
-                                        .block(
-                                            Block::default().borders(Borders::all()).style(
-                                                self.selected_mod
-                                                    .eq(&e)
-                                                    .then(|| Style::default().red())
-                                                    .unwrap_or_default(),
-                                            ),
-                                        ),
-                                    rect.clone(),
-                                )
-                            })
-                    })
-                    .collect::<Vec<(Paragraph<'_>, Rect)>>()
+        layout[2]
+    }
+
+    /// the view names in a single line for small terminals, returns the area left for the list
+    fn render_view_bar(&self, frame: &mut Frame<'_>, rect: Rect) -> Rect {
+        let layout = Layout::new()
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+
+        let mut x = layout[0].x;
+        for (e, label) in VIEWS.into_iter().enumerate() {
+            let width = (label.len() as u16 + 1).min(layout[0].right().saturating_sub(x));
+            let area = Rect::new(x, layout[0].y, width, 1);
+            frame.render_widget(Paragraph::new(label).style(self.view_style(e)), area);
+            self.views.push(area, e);
+            x += width;
+        }
+        let count = match self.profile_total() {
+            Some(total) => format!("({}, {})", self.mod_view.len(), format_size(total)),
+            None => format!("({})", self.mod_view.len()),
+        };
+        if x + count.len() as u16 <= layout[0].right() {
+            frame.render_widget(
+                Paragraph::new(count).fg(theme().muted),
+                Rect::new(x, layout[0].y, layout[0].right() - x, 1),
+            );
+        }
+
+        layout[1]
+    }
+
+    fn view_style(&self, view: usize) -> Style {
+        if view == self.selected_view {
+            Style::default().fg(theme().selected)
+        } else {
+            Style::default().fg(theme().text)
+        }
+    }
+
+    fn switch_view(&mut self, view: usize, state: &GlobalState) -> AnyResult<()> {
+        self.selected_view = view;
+        (self.selected_mod, self.details) = (0, false);
+        self.mod_view.switch(self.selected_view)?;
+        let skipped = self.reload(state)?;
+        notify_skipped(state, skipped);
+
+        Ok(())
+    }
+
+    /// dev links are marked in front of the name
+    fn title(&self, index: usize) -> Line<'_> {
+        let mut spans = vec![Span::styled(
+            self.mod_view.get_title(index).unwrap_or("UNK"),
+            self.title_style(index),
+        )];
+        if self.mod_view.dev_source(index).is_some() {
+            spans.insert(0, Span::styled("dev ", Style::default().fg(theme().info)));
+        }
+
+        Line::from(spans)
+    }
+
+    fn title_style(&self, index: usize) -> Style {
+        if self.mod_view.is_enabled(index) {
+            Style::default().fg(theme().accent)
+        } else {
+            Style::default().fg(theme().disabled)
+        }
+    }
+
+    fn toggle_selected(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(name) = self
+            .mod_view
+            .get_title(self.selected_mod)
+            .map(str::to_owned)
+        else {
+            return Ok(());
+        };
+        if !matches!(self.mod_view, ModView::Mods(_)) {
+            return Ok(());
+        }
+
+        let enabled = !self.mod_view.is_enabled(self.selected_mod);
+        let description = format!("{} {name}", if enabled { "enable" } else { "disable" });
+        let toggled = name.clone();
+        let hooks = ChangeHooks::toggle(&state.install, name.clone(), enabled);
+        if state.apply_with(description, hooks, move |install| {
+            install.set_mod_enabled(&toggled, enabled)
+        })? {
+            self.reload(state)?;
+            state.notifier.info(format!(
+                "{} {name}",
+                if enabled { "enabled" } else { "disabled" }
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// cycles through the profiles next to the game
+    fn next_profile(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let profiles = state.install.profiles()?;
+        let Some(next) = profiles
+            .iter()
+            .position(|profile| *profile == state.install.profile)
+            .map_or(profiles.first(), |current| {
+                profiles.get((current + 1) % profiles.len())
             })
-            .flatten()
-            .for_each(|(text, rect)| frame.render_widget(text, rect));
+        else {
+            return Ok(());
+        };
+
+        state.install = state.install.with_profile(next);
+        self.reload(state)?;
+        self.select(self.selected_mod);
+        state.notifier.info(format!("using the {next} profile"));
+
+        Ok(())
     }
 
-    fn render_binds(&self) -> Vec<&'static str> {
-        vec![
-            "reload - ctr + r",
-            "next - n",
-            "previous - p",
-            "select - (↑/↓/→/←)/(h/j/k/l)",
-            "type - tab",
-        ]
+    /// installs the typed zip or folder into the current profile, a bad path keeps the input open
+    fn install_from_input(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(path) = self.typed_path() else {
+            return Ok(());
+        };
+        let plan = LocalPackage::load(&state.install, &path)?.plan(&state.install, &path)?;
+        self.path_input = None;
+        state.is_typing = false;
+
+        let replaced = plan
+            .mods
+            .iter()
+            .filter(|planned| planned.replaces)
+            .map(|planned| planned.dir.clone())
+            .collect::<Vec<String>>();
+        let description = plan.describe();
+        let mods = plan
+            .mods
+            .iter()
+            .map(|planned| planned.mod_json.name.clone())
+            .collect::<Vec<String>>();
+        let hooks = ChangeHooks::install(&state.install, mods);
+        if state.apply_with(description.clone(), hooks, move |install| {
+            plan.install(install)
+        })? {
+            self.reload(state)?;
+            self.select(self.selected_mod);
+            state
+                .notifier
+                .info(description.replacen("install", "installed", 1));
+            if !replaced.is_empty() {
+                state
+                    .notifier
+                    .warn(format!("replaced the installed {}", replaced.join(", ")));
+            }
+        }
+
+        Ok(())
     }
 
-    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
-        if let event::Event::Key(key_event) = crosstermevent {
-            if key_event.kind == event::KeyEventKind::Press && !state.is_typing {
-                match key_event.code {
-                    KeyCode::Char('r') if key_event.modifiers == KeyModifiers::CONTROL => {
-                        self.mod_view.reload(&PathBuf::from(GAME_PATH))?;
-                    }
-                    KeyCode::Char('n') => {
-                        self.page_offset = self
-                            .mod_view
-                            .len()
-                            .div_ceil(MOD_DISPLAY_SIZE.pow(2))
-                            .saturating_sub(1)
-                            .saturating_mul(MOD_DISPLAY_SIZE.pow(2))
-                            .min(self.page_offset + MOD_DISPLAY_SIZE.pow(2));
-                    }
-                    KeyCode::Char('p') => {
-                        self.page_offset = self.page_offset.saturating_sub(MOD_DISPLAY_SIZE.pow(2));
-                    }
-                    KeyCode::Char('h') | KeyCode::Left => {
-                        self.selected_mod =
-                            self.page_offset.max(self.selected_mod.saturating_sub(1))
-                    }
-                    KeyCode::Char('l') | KeyCode::Right => {
-                        self.selected_mod = self
-                            .page_offset
-                            .saturating_add(MOD_DISPLAY_SIZE.pow(2) - 1)
-                            .min(self.selected_mod + 1)
-                    }
-                    KeyCode::Char('j') | KeyCode::Down => {
-                        self.selected_mod = self
-                            .page_offset
-                            .saturating_add(MOD_DISPLAY_SIZE.pow(2) - 1)
-                            .min(self.selected_mod + MOD_DISPLAY_SIZE)
-                    }
-                    KeyCode::Char('k') | KeyCode::Up => {
-                        self.selected_mod = self
-                            .page_offset
-                            .max(self.selected_mod.saturating_sub(MOD_DISPLAY_SIZE))
-                    }
-                    KeyCode::Tab => {
-                        self.selected_view = self
-                            .selected_view
-                            .eq(&3) // there are only 4 views
-                            .then(|| 0)
-                            .unwrap_or_else(|| self.selected_view + 1);
-                        (self.page_offset, self.selected_mod) = (0, 0);
-                        self.mod_view
-                            .switch(self.selected_view)?
-                            .reload(&PathBuf::from(GAME_PATH))?;
-                    }
-                    _ => {}
-                }
+    /// links the typed folder into the current profile, the mod.json has to be valid first
+    fn link_from_input(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(path) = self.typed_path() else {
+            return Ok(());
+        };
+        // links are resolved from the mods dir so they have to be absolute
+        let source = state
+            .install
+            .fs
+            .canonicalize(&path)
+            .with_context(|| format!("{} doesn't exist", path.display()))?;
+        dev_link::validate(&state.install, &source)?;
+        let dir = source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .with_context(|| format!("{} has no name", source.display()))?;
+        self.path_input = None;
+        state.is_typing = false;
+
+        let kind = Rc::new(Cell::new(LinkKind::Symlink));
+        let (linked, linked_kind) = ((source.clone(), dir.clone()), kind.clone());
+        if state.apply(
+            format!("link {dir} to {}", source.display()),
+            move |install| {
+                linked_kind.set(dev_link::link(install, &linked.0, &linked.1)?);
+                Ok(())
+            },
+        )? {
+            self.reload(state)?;
+            self.select(self.selected_mod);
+            match kind.get() {
+                LinkKind::Symlink => state.notifier.info(format!(
+                    "linked {dir}, changes to it are picked up automatically"
+                )),
+                LinkKind::Copy => state.notifier.warn(format!(
+                    "couldn't link {dir} so it was copied, the copy is kept in sync while drydoct runs"
+                )),
             }
         }
 
         Ok(())
     }
 
-    fn on_focus(&mut self) -> AnyResult<()> {
-        // direct path for now
-        // TODO: add path search
-        self.mod_view.reload(&PathBuf::from(GAME_PATH))?;
+    /// zips the selected mod for thunderstore into the pack dir
+    fn pack_selected(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(mod_dir) = self.mod_view.source_dir(self.selected_mod) else {
+            return Ok(());
+        };
+
+        let fs = state.install.fs.as_ref();
+        let package = pack::pack(fs, mod_dir)?;
+        let path = package.write(fs, &state.pack_dir)?;
+        state.notifier.info(format!(
+            "packed {} files into {}",
+            package.files.len(),
+            path.display()
+        ));
+        if !package.generated.is_empty() {
+            state.notifier.warn(format!(
+                "generated {} from mod.json, add them to the mod to change them",
+                package.generated.join(" and ")
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// the docs of a package come from its newest archive so opening them can download it
+    /// shows what is there right away, the rest is downloaded in the background
+    fn open_docs(&mut self, state: &GlobalState) -> AnyResult<()> {
+        let (docs, pending) = match &self.mod_view {
+            ModView::Mods(mods) => {
+                let Some(nsmod) = mods.get(self.selected_mod) else {
+                    return Ok(());
+                };
+                ModDocs::for_mod(state, nsmod, self.packages.index())
+            }
+            ModView::Packages(packages) => {
+                let Some(entry) = packages.get(self.selected_mod) else {
+                    return Ok(());
+                };
+                let installed = reload_mods(&state.install)?.0;
+                let (docs, pending) = ModDocs::for_package(
+                    &entry.package,
+                    find_installed(&entry.package, &installed),
+                );
+                (docs, Some(pending))
+            }
+            _ => return Ok(()),
+        };
+        self.docs = Some(DocsViewer::new(docs, pending));
+        self.fetch_docs(state);
 
         Ok(())
     }
+
+    /// starts on what the open docs are waiting for
+    fn fetch_docs(&mut self, state: &GlobalState) {
+        let Some(docs) = self.docs.as_mut() else {
+            return;
+        };
+
+        match docs.pending() {
+            // a broken index only leaves the update check out
+            Some(Pending::Index(_)) if !self.packages.fetch_index(state) => docs.set_pending(None),
+            Some(Pending::Archive { package, .. }) => {
+                let (package_cache, package) = (state.package_cache.clone(), package.clone());
+                self.downloading_docs
+                    .start(move || fetch_latest(&package_cache, &package));
+            }
+            Some(Pending::Index(_)) | None => {}
+        }
+    }
+
+    fn typed_path(&self) -> Option<std::path::PathBuf> {
+        self.path_input.as_ref().map(|(input, _)| input.path())
+    }
+
+    fn render_details(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let area = rect.inner(&Margin {
+            vertical: rect.height / 6,
+            horizontal: rect.width / 6,
+        });
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(
+                self.mod_view
+                    .get_details(self.selected_mod, self.size(self.selected_mod))
+                    .unwrap_or_else(|| Text::raw("nothing selected")),
+            )
+            .wrap(Wrap { trim: false })
+            .block(
+                Block::default()
+                    .borders(Borders::all())
+                    .title(
+                        self.mod_view
+                            .get_title(self.selected_mod)
+                            .unwrap_or("details"),
+                    )
+                    .title_style(Style::default().fg(theme().accent))
+                    .border_style(Style::default().fg(theme().selected))
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            area,
+        );
+    }
+}
+
+fn notify_skipped(state: &GlobalState, skipped: usize) {
+    if skipped != 0 {
+        state.notifier.warn(format!(
+            "skipped {skipped} mods that failed to load, see the logs"
+        ));
+    }
 }
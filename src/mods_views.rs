@@ -1,20 +1,26 @@
-use anyhow::{Ok, Result as AnyResult};
+use anyhow::{Context, Ok, Result as AnyResult};
 use ratatui::{prelude::*, text::Line};
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 use thermite::{
     model::{Manifest, ModJSON},
     prelude::ThermiteError,
 };
 
+use crate::{
+    dev_link::linked_source,
+    disk_usage::{EntrySize, ProfileUsage},
+    install::GameInstall,
+    package_cache::format_size,
+    theme::theme,
+    thunderstore::Package,
+};
+
 #[derive(Debug)]
 pub enum ModView {
     Mods(Vec<InstalledModPartial>),
     Plugins(Vec<InstalledPlugins>),
-    Packages(Vec<()>),
-    CTA,
+    Packages(Vec<PackageEntry>),
+    Cta,
 }
 
 impl Default for ModView {
@@ -24,36 +30,22 @@ impl Default for ModView {
 }
 
 impl ModView {
-    pub fn reload(&mut self, game_path: &Path) -> AnyResult<()> {
-        match self {
-            ModView::Mods(mods) => *mods = reload_mods(game_path)?,
-            ModView::Plugins(plugins) => *plugins = reload_plugins(game_path)?,
-            ModView::Packages(_) => {}
-            ModView::CTA => {}
-        };
-
-        Ok(())
-    }
-
-    pub fn filter(&mut self, keyword: &str) {
+    /// returns the amount of entries that couldn't be loaded
+    pub fn reload(&mut self, install: &GameInstall) -> AnyResult<usize> {
+        let mut skipped = 0;
         match self {
             ModView::Mods(mods) => {
-                *mods = mods
-                    .clone()
-                    .into_iter()
-                    .filter(|nsmod| nsmod.mod_json.name.find(keyword).is_some())
-                    .collect();
+                (*mods, skipped) = reload_mods(install).context("failed to reload mods")?
             }
             ModView::Plugins(plugins) => {
-                *plugins = plugins
-                    .clone()
-                    .into_iter()
-                    .filter(|plugin| plugin.name.find(keyword).is_some())
-                    .collect();
+                *plugins = reload_plugins(install).context("failed to reload plugins")?
             }
+            // filtered by the mods tab, it knows the query and where the index is
             ModView::Packages(_) => {}
-            ModView::CTA => {}
+            ModView::Cta => {}
         };
+
+        Ok(skipped)
     }
 
     pub fn switch(&mut self, view_index: usize) -> AnyResult<&mut Self> {
@@ -61,7 +53,7 @@ impl ModView {
             0 => ModView::Mods(Vec::new()),
             1 => ModView::Plugins(Vec::new()),
             2 => ModView::Packages(Vec::new()),
-            3 => ModView::CTA,
+            3 => ModView::Cta,
             _ => {
                 log::warn!("somehow got invalid view index of {}", view_index);
                 ModView::Mods(Vec::new())
@@ -71,29 +63,230 @@ impl ModView {
         Ok(self)
     }
 
-    pub fn get_as_paragraph(&self, index: usize) -> Option<Text<'_>> {
+    /// what the entry takes on disk, only installed mods and plugins are measured
+    pub fn size<'a>(&self, index: usize, usage: &'a ProfileUsage) -> Option<&'a EntrySize> {
+        match self {
+            ModView::Mods(mods) => {
+                let path = &mods.get(index)?.path;
+                usage.mods.iter().find(|size| size.path == *path)
+            }
+            ModView::Plugins(plugins) => {
+                let name = &plugins.get(index)?.name;
+                usage.plugins.iter().find(|size| size.name == *name)
+            }
+            ModView::Packages(_) | ModView::Cta => None,
+        }
+    }
+
+    pub fn get_as_paragraph(&self, index: usize, size: Option<&EntrySize>) -> Option<Text<'_>> {
+        let size = size
+            .map(|size| Span::styled(format_size(size.size), Style::default().fg(theme().muted)));
         match self {
             ModView::Mods(mods) => {
                 let nsmod = mods.get(index)?;
+                let mut first = vec![
+                    Span::raw(&nsmod.mod_json.version),
+                    Span::raw("|"),
+                    Span::styled(
+                        nsmod.mod_json.load_priority.unwrap_or(999).to_string(),
+                        Style::default().fg(theme().info),
+                    ),
+                ];
+                if let Some(size) = size {
+                    first.extend([Span::raw("|"), size]);
+                }
+                Some(Text::from(vec![
+                    Line::from(first),
+                    Line::raw(&nsmod.mod_json.description),
+                ]))
+            }
+            ModView::Plugins(plugins) => plugins.get(index).map(|plugin| {
+                Text::from(
+                    [Line::raw(&plugin.name)]
+                        .into_iter()
+                        .chain(size.map(Line::from))
+                        .collect::<Vec<Line<'_>>>(),
+                )
+            }),
+            ModView::Packages(packages) => {
+                let entry = packages.get(index)?;
+                let latest = entry.package.versions.first();
                 Some(Text::from(vec![
                     Line::from(vec![
-                        Span::raw(&nsmod.mod_json.version),
+                        Span::raw(
+                            latest
+                                .map(|version| version.version_number.as_str())
+                                .unwrap_or("?"),
+                        ),
                         Span::raw("|"),
                         Span::styled(
-                            nsmod
-                                .mod_json
-                                .load_priority
-                                .unwrap_or_else(|| 999)
-                                .to_string(),
-                            Style::default().green(),
+                            entry.package.downloads().to_string(),
+                            Style::default().fg(theme().info),
                         ),
                     ]),
-                    Line::raw(&nsmod.mod_json.description),
+                    Line::raw(
+                        latest
+                            .map(|version| version.description.as_str())
+                            .unwrap_or_default(),
+                    ),
                 ]))
             }
-            ModView::Plugins(plugins) => plugins.get(index).map(|plugin| Text::raw(&plugin.name)),
-            ModView::Packages(_) => None,
-            ModView::CTA => None,
+            ModView::Cta => None,
+        }
+    }
+
+    pub fn get_details(&self, index: usize, size: Option<&EntrySize>) -> Option<Text<'_>> {
+        let field = |name: &'static str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{name}: "), Style::default().fg(theme().muted)),
+                Span::raw(value),
+            ])
+        };
+        let size = size
+            .map(|size| {
+                let mut lines = vec![field("size", format_size(size.size))];
+                if size.shared > 0 {
+                    lines.push(field(
+                        "shared",
+                        format!("{} with other profiles", format_size(size.shared)),
+                    ));
+                }
+                lines
+            })
+            .unwrap_or_default();
+        match self {
+            ModView::Mods(mods) => {
+                let nsmod = mods.get(index)?;
+                let mut lines = vec![
+                    field("version", nsmod.mod_json.version.clone()),
+                    field(
+                        "author",
+                        nsmod
+                            .author
+                            .as_deref()
+                            .map(str::trim)
+                            .unwrap_or("unknown")
+                            .to_owned(),
+                    ),
+                    field(
+                        "load priority",
+                        nsmod.mod_json.load_priority.unwrap_or(999).to_string(),
+                    ),
+                    field("path", nsmod.path.display().to_string()),
+                ];
+                lines.extend(size);
+                if let Some(source) = &nsmod.dev_source {
+                    lines.push(field("linked to", source.display().to_string()));
+                }
+                if let Some(manifest) = &nsmod.manifest {
+                    lines.push(field("package", manifest.name.clone()));
+                    lines.push(field("website", manifest.website_url.clone()));
+                    if !manifest.dependencies.is_empty() {
+                        lines.push(field("dependencies", manifest.dependencies.join(", ")));
+                    }
+                }
+                lines.push(Line::raw(""));
+                lines.push(Line::raw(nsmod.mod_json.description.as_str()));
+
+                Some(Text::from(lines))
+            }
+            ModView::Plugins(plugins) => plugins.get(index).map(|plugin| {
+                Text::from(
+                    [Line::raw(plugin.name.as_str())]
+                        .into_iter()
+                        .chain(size)
+                        .collect::<Vec<Line<'_>>>(),
+                )
+            }),
+            ModView::Packages(packages) => {
+                let entry = packages.get(index)?;
+                let package = &entry.package;
+                let yes_no = |value: bool| if value { "yes" } else { "no" }.to_owned();
+
+                let mut lines = vec![
+                    field("author", package.owner.clone()),
+                    field("categories", package.categories.join(", ")),
+                    field(
+                        "updated",
+                        package.date_updated.get(..10).unwrap_or("?").to_owned(),
+                    ),
+                    field("downloads", package.downloads().to_string()),
+                    field("versions", package.versions.len().to_string()),
+                    field("installed", yes_no(entry.installed)),
+                ];
+                if package.is_deprecated {
+                    lines.push(field("deprecated", yes_no(true)));
+                }
+                if package.has_nsfw_content {
+                    lines.push(field("nsfw", yes_no(true)));
+                }
+                if let Some(latest) = package.versions.first() {
+                    lines.push(field("latest", latest.version_number.clone()));
+                    lines.push(Line::raw(""));
+                    lines.push(Line::raw(latest.description.as_str()));
+                }
+
+                Some(Text::from(lines))
+            }
+            ModView::Cta => None,
+        }
+    }
+
+    /// a one line description for the compact list
+    pub fn get_summary(&self, index: usize) -> Option<String> {
+        match self {
+            ModView::Mods(mods) => mods.get(index).map(|nsmod| {
+                format!(
+                    "v{} {}",
+                    nsmod.mod_json.version,
+                    nsmod
+                        .mod_json
+                        .description
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                )
+            }),
+            ModView::Plugins(_) => Some(String::new()),
+            ModView::Packages(packages) => packages.get(index).map(|entry| {
+                let latest = entry.package.versions.first();
+                format!(
+                    "v{} {}",
+                    latest
+                        .map(|version| version.version_number.as_str())
+                        .unwrap_or("?"),
+                    latest
+                        .and_then(|version| version.description.lines().next())
+                        .unwrap_or_default()
+                )
+            }),
+            ModView::Cta => None,
+        }
+    }
+
+    /// only mods can be disabled
+    pub fn is_enabled(&self, index: usize) -> bool {
+        match self {
+            ModView::Mods(mods) => mods.get(index).map(|nsmod| nsmod.enabled).unwrap_or(true),
+            _ => true,
+        }
+    }
+
+    /// where a mod's files really are, its dev link folder if it has one
+    pub fn source_dir(&self, index: usize) -> Option<&Path> {
+        match self {
+            ModView::Mods(mods) => mods
+                .get(index)
+                .map(|nsmod| nsmod.dev_source.as_deref().unwrap_or(&nsmod.path)),
+            _ => None,
+        }
+    }
+
+    /// the folder a dev linked mod is kept in sync with
+    pub fn dev_source(&self, index: usize) -> Option<&Path> {
+        match self {
+            ModView::Mods(mods) => mods.get(index)?.dev_source.as_deref(),
+            _ => None,
         }
     }
 
@@ -101,8 +294,10 @@ impl ModView {
         match self {
             ModView::Mods(mods) => mods.get(index).map(|nsmod| nsmod.mod_json.name.as_str()),
             ModView::Plugins(plugins) => plugins.get(index).map(|plugin| plugin.name.as_str()),
-            ModView::Packages(_) => Some(""),
-            ModView::CTA => Some("CTA VIEW"),
+            ModView::Packages(packages) => {
+                packages.get(index).map(|entry| entry.package.name.as_str())
+            }
+            ModView::Cta => Some("Cta VIEW"),
         }
     }
 
@@ -110,8 +305,8 @@ impl ModView {
         match self {
             ModView::Mods(mods) => mods.len(),
             ModView::Plugins(plugins) => plugins.len(),
-            ModView::Packages(_) => 0,
-            ModView::CTA => 5,
+            ModView::Packages(packages) => packages.len(),
+            ModView::Cta => 5,
         }
     }
 }
@@ -122,23 +317,35 @@ pub struct InstalledModPartial {
     pub mod_json: ModJSON,
     pub author: Option<String>,
     pub path: PathBuf,
+    /// from the profile's `enabledmods.json`
+    pub enabled: bool,
+    /// set for dev links
+    pub dev_source: Option<PathBuf>,
+}
+
+/// a package from the thunderstore index and whether the profile has it
+#[derive(Debug, Clone)]
+pub struct PackageEntry {
+    pub package: Package,
+    pub installed: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct InstalledPlugins {
-    name: String, // too lazy to fetch everthing else XD
+    pub name: String, // too lazy to fetch everthing else XD
 }
 
-pub fn reload_plugins(game_path: &Path) -> AnyResult<Vec<InstalledPlugins>> {
-    Ok(game_path
-        .join("R2Northstar")
-        .join("plugins")
-        .as_path()
-        .canonicalize()?
-        .read_dir()?
-        .filter_map(|file| file.ok())
-        .filter(|file| file.file_type().map(|ty| ty.is_file()).unwrap_or_default())
-        .map(|file| file.path())
+pub fn reload_plugins(install: &GameInstall) -> AnyResult<Vec<InstalledPlugins>> {
+    let plugins_path = install.plugins_dir();
+    let fs = &install.fs;
+    let mut plugins = fs
+        .read_dir(
+            &fs.canonicalize(&plugins_path)
+                .with_context(|| format!("couldn't find {}", plugins_path.display()))?,
+        )?
+        .into_iter()
+        .filter(|file| !file.is_dir)
+        .map(|file| file.path)
         .filter(|path| {
             path.extension()
                 .map(|ext| ext.eq("dll"))
@@ -146,40 +353,68 @@ pub fn reload_plugins(game_path: &Path) -> AnyResult<Vec<InstalledPlugins>> {
         })
         .filter_map(|file| Some(file.file_name()?.to_str()?.to_owned()))
         .map(|name| InstalledPlugins { name })
-        .collect())
+        .collect::<Vec<InstalledPlugins>>();
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(plugins)
 }
-pub fn reload_mods(game_path: &Path) -> AnyResult<Vec<InstalledModPartial>> {
-    Ok(
-        find_mods(game_path.join("R2Northstar").join("mods").as_path())?
-            .into_iter()
-            .filter_map(|nsmod| nsmod.ok())
-            .collect(),
-    )
-    // Ok(find_mods(&mods_path)?
-    //     .into_iter()
-    //     .collect::<Result<Vec<InstalledModPartial>, ThermiteError>>()?)
+/// also returns the amount of mods that failed to load
+pub fn reload_mods(install: &GameInstall) -> AnyResult<(Vec<InstalledModPartial>, usize)> {
+    let mods_path = install.mods_dir();
+    let enabled_mods = install.enabled_mods()?;
+    let mut skipped = 0;
+    let mut mods = find_mods(install, mods_path.as_path())
+        .with_context(|| format!("couldn't read mods from {}", mods_path.display()))?
+        .into_iter()
+        .filter_map(|nsmod| {
+            nsmod
+                .map_err(|err| {
+                    log::warn!("skipping broken mod: {err}");
+                    skipped += 1;
+                })
+                .ok()
+        })
+        .map(|nsmod| InstalledModPartial {
+            enabled: enabled_mods.is_enabled(&nsmod.mod_json.name),
+            ..nsmod
+        })
+        .collect::<Vec<InstalledModPartial>>();
+    // read_dir order depends on the filesystem
+    mods.sort_by(|a, b| a.mod_json.name.cmp(&b.mod_json.name));
+
+    Ok((mods, skipped))
 }
 
-fn find_mods(dir: &Path) -> Result<Vec<Result<InstalledModPartial, ThermiteError>>, ThermiteError> {
+fn find_mods(
+    install: &GameInstall,
+    dir: &Path,
+) -> Result<Vec<Result<InstalledModPartial, ThermiteError>>, ThermiteError> {
+    let fs = &install.fs;
     Result::Ok(
-        dir.canonicalize()?
-            .read_dir()?
-            .filter_map(|file| file.ok())
-            .filter(|file| file.file_type().map(|ty| ty.is_dir()).unwrap_or_default())
-            .map(|dir| dir.path())
-            .map(extract_mod_info)
+        fs.read_dir(&fs.canonicalize(dir)?)?
+            .into_iter()
+            .filter(|file| file.is_dir)
+            .map(|dir| extract_mod_info(install, dir.path))
             .collect(),
     )
 }
 
-fn extract_mod_info(mod_dir: PathBuf) -> Result<InstalledModPartial, ThermiteError> {
+fn extract_mod_info(
+    install: &GameInstall,
+    mod_dir: PathBuf,
+) -> Result<InstalledModPartial, ThermiteError> {
+    let fs = &install.fs;
     Result::Ok(InstalledModPartial {
-        manifest: fs::read_to_string(mod_dir.join("manifest.json"))
+        manifest: fs
+            .read_to_string(&mod_dir.join("manifest.json"))
             .ok()
-            .map(|manifest| json5::from_str(&manifest).ok())
-            .flatten(),
-        mod_json: json5::from_str(fs::read_to_string(mod_dir.join("mod.json"))?.as_str())?,
-        author: fs::read_to_string(mod_dir.join("thunderstore_author.txt")).ok(),
+            .and_then(|manifest| json5::from_str(&manifest).ok()),
+        mod_json: json5::from_str(fs.read_to_string(&mod_dir.join("mod.json"))?.as_str())?,
+        author: fs
+            .read_to_string(&mod_dir.join("thunderstore_author.txt"))
+            .ok(),
+        dev_source: linked_source(install, &mod_dir),
         path: mod_dir,
+        enabled: true,
     })
 }
@@ -0,0 +1,92 @@
+use std::{
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+
+/// rects recorded while rendering so mouse events can be matched against them later,
+/// `render_ui` only gets `&self` so they live in a `RefCell`
+pub struct Hitboxes<T> {
+    boxes: RefCell<Vec<(Rect, T)>>,
+}
+
+impl<T> Default for Hitboxes<T> {
+    fn default() -> Self {
+        Self {
+            boxes: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: Clone> Hitboxes<T> {
+    /// should be called at the start of every render
+    pub fn clear(&self) {
+        self.boxes.borrow_mut().clear();
+    }
+
+    pub fn push(&self, rect: Rect, value: T) {
+        self.boxes.borrow_mut().push((rect, value));
+    }
+
+    /// the value of the last pushed rect under the mouse since that one is drawn on top
+    pub fn hit(&self, mouse: &MouseEvent) -> Option<T> {
+        self.hit_row(mouse).map(|(value, _)| value)
+    }
+
+    /// like `hit` but also returns the row of the mouse inside the rect
+    pub fn hit_row(&self, mouse: &MouseEvent) -> Option<(T, u16)> {
+        self.boxes
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(rect, _)| contains(*rect, mouse))
+            .map(|(rect, value)| (value.clone(), mouse.row - rect.y))
+    }
+}
+
+pub fn contains(rect: Rect, mouse: &MouseEvent) -> bool {
+    (rect.left()..rect.right()).contains(&mouse.column)
+        && (rect.top()..rect.bottom()).contains(&mouse.row)
+}
+
+pub fn is_left_click(mouse: &MouseEvent) -> bool {
+    mouse.kind == MouseEventKind::Down(MouseButton::Left)
+}
+
+/// tells single clicks from double clicks since crossterm only reports presses
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    last: Option<(Instant, u16, u16)>,
+    double: bool,
+}
+
+impl ClickTracker {
+    /// has to see every mouse event before the tabs do
+    pub fn register(&mut self, mouse: &MouseEvent) {
+        if !is_left_click(mouse) {
+            return;
+        }
+
+        let now = Instant::now();
+        self.double = self.last.is_some_and(|(time, column, row)| {
+            now.duration_since(time) <= DOUBLE_CLICK_TIME
+                && column == mouse.column
+                && row == mouse.row
+        });
+        // a third click starts a new pair
+        self.last = if self.double {
+            None
+        } else {
+            Some((now, mouse.column, mouse.row))
+        };
+    }
+
+    /// whether the last left click finished a double click
+    pub fn is_double(&self) -> bool {
+        self.double
+    }
+}
@@ -0,0 +1,30 @@
+use std::{fs, io::Read, path::Path, time::Duration};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+
+/// how long connecting or waiting for the next bytes may take, a big download can take longer
+/// as a whole as long as it keeps coming in
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// http(s) urls are requested, anything else is read as a local file so tests and
+/// offline setups can point drydoct at a copy
+pub fn fetch(url: &str) -> AnyResult<Vec<u8>> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        let path = Path::new(url.strip_prefix("file://").unwrap_or(url));
+        return fs::read(path).with_context(|| format!("couldn't read {}", path.display()));
+    }
+
+    let mut body = Vec::new();
+    ureq::AgentBuilder::new()
+        .timeout_connect(TIMEOUT)
+        .timeout_read(TIMEOUT)
+        .build()
+        .get(url)
+        .call()
+        .with_context(|| format!("couldn't reach {url}"))?
+        .into_reader()
+        .read_to_end(&mut body)
+        .with_context(|| format!("couldn't read the response of {url}"))?;
+
+    Ok(body)
+}
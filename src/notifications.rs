@@ -0,0 +1,326 @@
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event::{KeyEvent, MouseEvent, MouseEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    keymap::{Action, KeyContext, Keymap},
+    theme::theme,
+};
+
+const MAX_HISTORY: usize = 200;
+const MAX_TOASTS: usize = 4;
+const TOAST_WIDTH: u16 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    fn lifetime(&self) -> Duration {
+        match self {
+            Level::Info => Duration::from_secs(3),
+            Level::Warn => Duration::from_secs(5),
+            Level::Error => Duration::from_secs(8),
+        }
+    }
+
+    pub fn style(&self) -> Style {
+        match self {
+            Level::Info => Style::default().fg(theme().info),
+            Level::Warn => Style::default().fg(theme().warn),
+            Level::Error => Style::default().fg(theme().error),
+        }
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        };
+
+        write!(f, "{display}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    /// the causes of an error, outermost first
+    pub chain: Vec<String>,
+    /// what a command printed, shown below the message in the history
+    pub output: Vec<String>,
+    pub created: SystemTime,
+}
+
+impl Notification {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            message: message.into(),
+            chain: Vec::new(),
+            output: Vec::new(),
+            created: SystemTime::now(),
+        }
+    }
+
+    pub fn with_output(self, output: Vec<String>) -> Self {
+        Self { output, ..self }
+    }
+
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        let mut chain = err.chain().map(|cause| cause.to_string());
+
+        Self {
+            level: Level::Error,
+            message: chain.next().unwrap_or_default(),
+            chain: chain.collect(),
+            output: Vec::new(),
+            created: SystemTime::now(),
+        }
+    }
+
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        self.created + self.level.lifetime() < now
+    }
+
+    pub fn full_text(&self) -> String {
+        std::iter::once(self.message.clone())
+            .chain(self.chain.iter().map(|cause| format!("caused by: {cause}")))
+            .chain(self.output.iter().cloned())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// a cheap handle that can be cloned into tabs or background threads to push notifications
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    sender: Sender<Notification>,
+}
+
+impl Notifier {
+    pub fn push(&self, notification: Notification) {
+        // the receiver lives as long as the app so this can only fail during shutdown
+        _ = self.sender.send(notification);
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        self.push(Notification::new(Level::Info, message))
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        self.push(Notification::new(Level::Warn, message))
+    }
+
+    pub fn error(&self, err: &anyhow::Error) {
+        self.push(Notification::from_error(err))
+    }
+}
+
+pub struct NotificationCenter {
+    notifier: Notifier,
+    receiver: Receiver<Notification>,
+    history: VecDeque<Notification>,
+    pub show_history: bool,
+    selected: usize,
+}
+
+impl Default for NotificationCenter {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            notifier: Notifier { sender },
+            receiver,
+            history: VecDeque::new(),
+            show_history: false,
+            selected: 0,
+        }
+    }
+}
+
+impl NotificationCenter {
+    pub fn notifier(&self) -> Notifier {
+        self.notifier.clone()
+    }
+
+    pub fn push(&mut self, notification: Notification) {
+        match notification.level {
+            Level::Info => log::info!("{}", notification.full_text()),
+            Level::Warn => log::warn!("{}", notification.full_text()),
+            Level::Error => log::error!("{}", notification.full_text()),
+        }
+
+        self.history.push_front(notification);
+        self.history.truncate(MAX_HISTORY);
+    }
+
+    /// drains everything pushed through [`Notifier`]s since the last call
+    pub fn collect(&mut self) {
+        while let Result::Ok(notification) = self.receiver.try_recv() {
+            self.push(notification);
+        }
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = &Notification> {
+        self.history.iter()
+    }
+
+    /// the newest notifications that are still live, an error outlives the info after it
+    pub fn toasts(&self) -> impl Iterator<Item = &Notification> {
+        let now = SystemTime::now();
+        self.history
+            .iter()
+            .filter(move |notification| !notification.is_expired(now))
+            .take(MAX_TOASTS)
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.show_history = !self.show_history;
+        self.selected = 0;
+    }
+
+    /// returns true if the key was consumed by the history panel
+    pub fn handle_key(&mut self, key: KeyEvent, keymap: &Keymap) -> AnyResult<bool> {
+        if !self.show_history {
+            return Ok(false);
+        }
+
+        match keymap.action(KeyContext::Notifications, &key) {
+            Some(Action::Down) => {
+                self.selected = self.history.len().saturating_sub(1).min(self.selected + 1)
+            }
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            Some(Action::Top) => self.selected = 0,
+            Some(Action::Bottom) => self.selected = self.history.len().saturating_sub(1),
+            Some(Action::Copy) => self.copy_selected()?,
+            Some(Action::Clear) => {
+                self.history.clear();
+                self.selected = 0;
+            }
+            Some(Action::Close) => self.show_history = false,
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::ScrollDown => {
+                self.selected = self.history.len().saturating_sub(1).min(self.selected + 1)
+            }
+            MouseEventKind::ScrollUp => self.selected = self.selected.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn copy_selected(&mut self) -> AnyResult<()> {
+        let Some(notification) = self.history.get(self.selected) else {
+            return Ok(());
+        };
+
+        arboard::Clipboard::new()?.set_text(notification.full_text())?;
+        self.notifier.info("copied notification to clipboard");
+
+        Ok(())
+    }
+
+    pub fn render_toasts(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let width = TOAST_WIDTH.min(rect.width);
+        let mut y = rect.y;
+
+        for toast in self.toasts() {
+            let paragraph = Paragraph::new(toast.full_text())
+                .wrap(Wrap { trim: true })
+                .style(Style::default().fg(theme().text).bg(theme().surface))
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(toast.level.to_string())
+                        .border_style(toast.level.style()),
+                );
+            let height = (wrapped_height(&toast.full_text(), width.saturating_sub(2)) + 2)
+                .min(rect.bottom().saturating_sub(y));
+            if height < 3 {
+                break;
+            }
+
+            let area = Rect::new(rect.right() - width, y, width, height);
+            frame.render_widget(Clear, area);
+            frame.render_widget(paragraph, area);
+
+            y += height;
+        }
+    }
+
+    pub fn render_history(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let area = rect.inner(&Margin {
+            vertical: rect.height / 8,
+            horizontal: rect.width / 8,
+        });
+
+        let items = self
+            .history
+            .iter()
+            .map(|notification| {
+                let mut lines = vec![Line::from(vec![
+                    Span::styled(
+                        format!("[{}] ", notification.level),
+                        notification.level.style(),
+                    ),
+                    Span::raw(notification.message.as_str()),
+                ])];
+                lines.extend(notification.chain.iter().map(|cause| {
+                    Line::styled(
+                        format!("    caused by: {cause}"),
+                        Style::default().fg(theme().muted),
+                    )
+                }));
+                lines.extend(notification.output.iter().map(|line| {
+                    Line::styled(format!("    {line}"), Style::default().fg(theme().muted))
+                }));
+                ListItem::new(lines)
+            })
+            .collect::<Vec<ListItem<'_>>>();
+
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(format!("notifications ({})", self.history.len()))
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background)
+                        .fg(theme().text),
+                )
+                .highlight_style(Style::default().bg(theme().surface).bold()),
+            area,
+            &mut ListState::default().with_selected(Some(self.selected)),
+        );
+    }
+
+    pub fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::Notifications)
+    }
+}
+
+fn wrapped_height(text: &str, width: u16) -> u16 {
+    text.lines()
+        .map(|line| line.chars().count().max(1).div_ceil(width.max(1) as usize) as u16)
+        .sum()
+}
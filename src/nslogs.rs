@@ -0,0 +1,418 @@
+use std::{cell::Cell, path::PathBuf};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+use crossterm::event::{self, MouseEvent, MouseEventKind};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    install::GameInstall,
+    keymap::{Action, KeyContext, Keymap},
+    logging,
+    mods_views::reload_mods,
+    mouse::{is_left_click, Hitboxes},
+    nslogs_analyzer::{
+        analyze, find_log_files, parse_log, EntryLevel, Finding, LogEntry, LogFile, LogFileKind,
+        RuleSet,
+    },
+    theme::theme,
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    #[default]
+    Files,
+    Entries,
+    Findings,
+}
+
+#[derive(Default)]
+pub struct NsLogs {
+    files: Vec<LogFile>,
+    selected_file: usize,
+    /// the log that was actually parsed, differs from the selected file for crash dumps
+    analyzed: Option<PathBuf>,
+    entries: Vec<LogEntry>,
+    findings: Vec<Finding>,
+    rule_set: Option<RuleSet>,
+    pane: Pane,
+    selected_entry: usize,
+    selected_finding: usize,
+    errors_only: bool,
+    panes: Hitboxes<Pane>,
+    /// the scroll offset each pane was last drawn with, indexed by `Pane`
+    offsets: Cell<[usize; 3]>,
+}
+
+impl NsLogs {
+    fn visible_entries(&self) -> Vec<(usize, &LogEntry)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                !self.errors_only || entry.level >= EntryLevel::Error || entry.is_script_error()
+            })
+            .collect()
+    }
+
+    fn open_selected(&mut self, install: &GameInstall) -> AnyResult<()> {
+        let Some(file) = self.files.get(self.selected_file) else {
+            return Ok(());
+        };
+
+        // dumps are binary, the log written closest to the crash tells us more
+        let log = match file.kind {
+            LogFileKind::Log => file,
+            LogFileKind::CrashDump => self
+                .files
+                .iter()
+                .filter(|log| log.kind == LogFileKind::Log)
+                .min_by_key(|log| {
+                    log.modified
+                        .duration_since(file.modified)
+                        .or_else(|_| file.modified.duration_since(log.modified))
+                        .unwrap_or_default()
+                })
+                .context("no log found for this crash dump")?,
+        };
+
+        let content = install
+            .fs
+            .read(&log.path)
+            .with_context(|| format!("couldn't read {}", log.path.display()))?;
+        self.entries = parse_log(&String::from_utf8_lossy(&content));
+
+        if self.rule_set.is_none() {
+            self.rule_set = Some(RuleSet::load()?);
+        }
+        let (mods, _) = reload_mods(install).unwrap_or_default();
+        self.findings = analyze(
+            install,
+            &self.entries,
+            self.rule_set.as_ref().expect("rules were just loaded"),
+            &mods,
+        );
+
+        self.analyzed = Some(log.path.clone());
+        (self.selected_entry, self.selected_finding) = (0, 0);
+
+        Ok(())
+    }
+
+    fn jump_to_finding(&mut self) {
+        if let Some(finding) = self.findings.get(self.selected_finding) {
+            self.errors_only = false;
+            self.selected_entry = finding.entry;
+            self.pane = Pane::Entries;
+        }
+    }
+
+    /// how many lines each item of a pane takes up
+    fn item_heights(&self, pane: Pane) -> Vec<usize> {
+        match pane {
+            Pane::Files => vec![1; self.files.len()],
+            Pane::Entries => self
+                .visible_entries()
+                .iter()
+                .map(|(_, entry)| entry.message.lines().count().max(1))
+                .collect(),
+            Pane::Findings => vec![2; self.findings.len()],
+        }
+    }
+
+    fn set_offset(&self, pane: Pane, offset: usize) {
+        let mut offsets = self.offsets.get();
+        offsets[pane as usize] = offset;
+        self.offsets.set(offsets);
+    }
+
+    /// the item drawn at a row inside the pane's border
+    fn item_at(&self, pane: Pane, row: usize) -> Option<usize> {
+        let mut top = 0;
+        self.item_heights(pane)
+            .into_iter()
+            .enumerate()
+            .skip(self.offsets.get()[pane as usize])
+            .find(|(_, height)| {
+                top += height;
+                row < top
+            })
+            .map(|(index, _)| index)
+    }
+
+    fn select(&mut self, pane: Pane, index: usize) {
+        self.pane = pane;
+        match pane {
+            Pane::Files => self.selected_file = index,
+            Pane::Entries => self.selected_entry = index,
+            Pane::Findings => self.selected_finding = index,
+        }
+    }
+
+    fn open(&mut self, state: &GlobalState) -> AnyResult<()> {
+        match self.pane {
+            Pane::Files => {
+                self.open_selected(&state.install)?;
+                if !self.findings.is_empty() {
+                    state.notifier.warn(format!(
+                        "found {} known problems in the log",
+                        self.findings.len()
+                    ));
+                }
+            }
+            Pane::Findings => self.jump_to_finding(),
+            Pane::Entries => {}
+        }
+
+        Ok(())
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent, state: &GlobalState) -> AnyResult<()> {
+        let Some((pane, row)) = self.panes.hit_row(&mouse) else {
+            return Ok(());
+        };
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp => {
+                self.pane = pane;
+                self.move_selection(mouse.kind == MouseEventKind::ScrollDown);
+            }
+            _ if is_left_click(&mouse) => {
+                self.pane = pane;
+                // the first row is the border
+                if let Some(index) = (row as usize)
+                    .checked_sub(1)
+                    .and_then(|row| self.item_at(pane, row))
+                {
+                    self.select(pane, index);
+                    if state.clicks.is_double() {
+                        self.open(state)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        let (selected, len) = match self.pane {
+            Pane::Files => (&mut self.selected_file, self.files.len()),
+            Pane::Entries => {
+                let len = self.visible_entries().len();
+                (&mut self.selected_entry, len)
+            }
+            Pane::Findings => (&mut self.selected_finding, self.findings.len()),
+        };
+
+        *selected = if down {
+            len.saturating_sub(1).min(*selected + 1)
+        } else {
+            selected.saturating_sub(1)
+        };
+    }
+
+    fn pane_block(&self, title: String, pane: Pane) -> Block<'_> {
+        Block::default()
+            .borders(Borders::all())
+            .title(title)
+            .title_style(Style::default().fg(theme().accent))
+            .border_style(if self.pane == pane {
+                Style::default().fg(theme().selected)
+            } else {
+                Style::default().fg(theme().border)
+            })
+            .bg(theme().background)
+    }
+}
+
+impl TabUi for NsLogs {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let layout = Layout::new()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(25), Constraint::Percentage(75)])
+            .split(rect);
+        let right_layout = Layout::new()
+            .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+            .split(layout[1]);
+        self.panes.clear();
+        self.panes.push(layout[0], Pane::Files);
+        self.panes.push(right_layout[0], Pane::Entries);
+        self.panes.push(right_layout[1], Pane::Findings);
+
+        let files = self
+            .files
+            .iter()
+            .map(|file| {
+                let style = match file.kind {
+                    LogFileKind::Log => Style::default().fg(theme().text),
+                    LogFileKind::CrashDump => Style::default().fg(theme().accent),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(file.name(), style),
+                    Span::raw(format!(
+                        " {} {}KB",
+                        logging::format_time(file.modified),
+                        file.size.div_ceil(1024)
+                    ))
+                    .fg(theme().muted),
+                ]))
+            })
+            .collect::<Vec<ListItem<'_>>>();
+        let mut list_state = ListState::default().with_selected(Some(self.selected_file));
+        frame.render_stateful_widget(
+            List::new(files)
+                .block(self.pane_block(format!("logs ({})", self.files.len()), Pane::Files))
+                .highlight_style(Style::default().bg(theme().surface).bold()),
+            layout[0],
+            &mut list_state,
+        );
+        self.set_offset(Pane::Files, list_state.offset());
+
+        let visible = self.visible_entries();
+        let entries = visible
+            .iter()
+            .map(|(index, entry)| {
+                let style = if entry.is_script_error() || entry.level >= EntryLevel::Error {
+                    Style::default().fg(theme().error)
+                } else if entry.level == EntryLevel::Warn {
+                    Style::default().fg(theme().warn)
+                } else if self.findings.iter().any(|finding| finding.entry == *index) {
+                    Style::default().fg(theme().accent)
+                } else {
+                    Style::default().fg(theme().text)
+                };
+
+                let mut lines = entry.message.lines();
+                let mut text = vec![Line::from(vec![
+                    Span::raw(format!("{:>5} ", entry.line)).fg(theme().muted),
+                    Span::raw(format!("{} ", entry.time)).fg(theme().muted),
+                    Span::raw(format!("[{}] ", entry.subsystem)).fg(theme().info),
+                    Span::styled(lines.next().unwrap_or_default().to_owned(), style),
+                ])];
+                text.extend(lines.map(|line| Line::styled(format!("      {line}"), style)));
+
+                ListItem::new(text)
+            })
+            .collect::<Vec<ListItem<'_>>>();
+        let title = self
+            .analyzed
+            .as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "no log opened".to_owned());
+        let mut list_state = ListState::default().with_selected(Some(self.selected_entry));
+        frame.render_stateful_widget(
+            List::new(entries)
+                .block(self.pane_block(
+                    format!(
+                        "{title} ({}/{}){}",
+                        visible.len(),
+                        self.entries.len(),
+                        if self.errors_only { " errors only" } else { "" }
+                    ),
+                    Pane::Entries,
+                ))
+                .highlight_style(Style::default().bg(theme().surface).bold()),
+            right_layout[0],
+            &mut list_state,
+        );
+        self.set_offset(Pane::Entries, list_state.offset());
+
+        let findings = self
+            .findings
+            .iter()
+            .map(|finding| {
+                ListItem::new(vec![
+                    Line::from(vec![
+                        Span::raw(finding.rule.as_str()).fg(theme().error),
+                        Span::raw(format!(" at line {} ", self.entries[finding.entry].line)),
+                        Span::raw(if finding.culprits.is_empty() {
+                            "no culprit found".to_owned()
+                        } else {
+                            format!("likely culprits: {}", finding.culprits.join(", "))
+                        })
+                        .fg(theme().accent),
+                    ]),
+                    Line::styled(
+                        format!("  {}", finding.hint),
+                        Style::default().fg(theme().muted),
+                    ),
+                ])
+            })
+            .collect::<Vec<ListItem<'_>>>();
+        let mut list_state = ListState::default().with_selected(Some(self.selected_finding));
+        frame.render_stateful_widget(
+            List::new(findings)
+                .block(self.pane_block(
+                    format!(
+                        "findings ({}) - {} rules",
+                        self.findings.len(),
+                        self.rule_set.as_ref().map(RuleSet::len).unwrap_or_default()
+                    ),
+                    Pane::Findings,
+                ))
+                .highlight_style(Style::default().bg(theme().surface).bold()),
+            right_layout[1],
+            &mut list_state,
+        );
+        self.set_offset(Pane::Findings, list_state.offset());
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::NsLogs)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        if let event::Event::Key(key_event) = crosstermevent {
+            if key_event.kind == event::KeyEventKind::Press && !state.is_typing {
+                match state.keymap.action(KeyContext::NsLogs, &key_event) {
+                    Some(Action::Reload) => {
+                        self.rule_set = Some(RuleSet::load()?);
+                        self.open_selected(&state.install)?;
+                        state.notifier.info(format!(
+                            "loaded {} log rules",
+                            self.rule_set.as_ref().map(RuleSet::len).unwrap_or_default()
+                        ));
+                    }
+                    Some(Action::Down) => self.move_selection(true),
+                    Some(Action::Up) => self.move_selection(false),
+                    Some(Action::Left) => {
+                        self.pane = match self.pane {
+                            Pane::Files | Pane::Entries => Pane::Files,
+                            Pane::Findings => Pane::Entries,
+                        }
+                    }
+                    Some(Action::Right) => {
+                        self.pane = match self.pane {
+                            Pane::Files => Pane::Entries,
+                            Pane::Entries | Pane::Findings => Pane::Findings,
+                        }
+                    }
+                    Some(Action::ToggleErrors) => {
+                        self.errors_only = !self.errors_only;
+                        self.selected_entry = 0;
+                    }
+                    Some(Action::Open) => self.open(state)?,
+                    _ => {}
+                }
+            }
+        }
+
+        if let event::Event::Mouse(mouse) = crosstermevent {
+            if !state.is_typing {
+                self.handle_mouse(mouse, state)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.files = find_log_files(&state.install)?;
+        self.selected_file = self.selected_file.min(self.files.len().saturating_sub(1));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Ok, Result as AnyResult};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::{config::config_dir, install::GameInstall, mods_views::InstalledModPartial};
+
+static DEFAULT_RULES: &str = include_str!("../nslog_rules.json5");
+static LINE_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\[(?P<time>[^\]]*)\]\s*\[(?P<subsystem>[^\]]*)\]\s*\[(?P<level>[^\]]*)\]\s?(?P<message>.*)$")
+        .expect("line pattern should be valid")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFileKind {
+    Log,
+    CrashDump,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogFile {
+    pub path: PathBuf,
+    pub kind: LogFileKind,
+    pub modified: SystemTime,
+    pub size: u64,
+}
+
+impl LogFile {
+    pub fn name(&self) -> &str {
+        self.path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("UNK")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EntryLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Critical,
+}
+
+impl EntryLevel {
+    fn parse(level: &str) -> Self {
+        match level.trim().to_lowercase().as_str() {
+            "trace" => EntryLevel::Trace,
+            "debug" => EntryLevel::Debug,
+            "warn" | "warning" => EntryLevel::Warn,
+            "error" | "err" => EntryLevel::Error,
+            "critical" | "fatal" => EntryLevel::Critical,
+            _ => EntryLevel::Info,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub line: usize,
+    pub time: String,
+    pub subsystem: String,
+    pub level: EntryLevel,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// script errors and compile failures get highlighted in the view
+    pub fn is_script_error(&self) -> bool {
+        let message = self.message.to_uppercase();
+        message.contains("SCRIPT ERROR") || message.contains("COMPILE ERROR")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    /// regex matched against every entry, `path` and `mod` named groups are used to find the culprit
+    pub pattern: String,
+    pub hint: String,
+}
+
+#[derive(Debug)]
+pub struct RuleSet {
+    rules: Vec<(Rule, Regex)>,
+}
+
+impl RuleSet {
+    pub fn parse(rules: &str) -> AnyResult<Self> {
+        json5::from_str::<Vec<Rule>>(rules)?
+            .into_iter()
+            .map(|rule| {
+                let regex = Regex::new(&rule.pattern)
+                    .with_context(|| format!("invalid pattern in rule \"{}\"", rule.name))?;
+                Ok((rule, regex))
+            })
+            .collect::<AnyResult<Vec<(Rule, Regex)>>>()
+            .map(|rules| Self { rules })
+    }
+
+    /// the built in rules followed by the user's from `log_rules.json5` in the config dir
+    pub fn load() -> AnyResult<Self> {
+        let mut rule_set = Self::parse(DEFAULT_RULES).context("invalid built in log rules")?;
+
+        let path = user_rules_path();
+        if path.exists() {
+            let user_rules = Self::parse(&fs::read_to_string(&path)?)
+                .with_context(|| format!("invalid log rules in {}", path.display()))?;
+            rule_set.rules.extend(user_rules.rules);
+        }
+
+        Ok(rule_set)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub rule: String,
+    pub hint: String,
+    pub entry: usize,
+    pub culprits: Vec<String>,
+}
+
+pub fn user_rules_path() -> PathBuf {
+    config_dir().join("log_rules.json5")
+}
+
+/// every log and crash dump in `R2Northstar/logs`, newest first
+pub fn find_log_files(install: &GameInstall) -> AnyResult<Vec<LogFile>> {
+    let logs_path = install.logs_dir();
+    let mut files = install
+        .fs
+        .read_dir(&logs_path)
+        .with_context(|| format!("couldn't read logs from {}", logs_path.display()))?
+        .into_iter()
+        .filter_map(|file| {
+            let path = file.path;
+            let name = path.file_name()?.to_str()?.to_lowercase();
+            let kind = if name.starts_with("nslog") && name.ends_with(".txt") {
+                LogFileKind::Log
+            } else if name.ends_with(".dmp") {
+                LogFileKind::CrashDump
+            } else {
+                return None;
+            };
+            let metadata = install.fs.metadata(&path).ok()?;
+
+            Some(LogFile {
+                kind,
+                modified: metadata.modified,
+                size: metadata.len,
+                path,
+            })
+        })
+        .collect::<Vec<LogFile>>();
+
+    files.sort_by_key(|file| std::cmp::Reverse(file.modified));
+
+    Ok(files)
+}
+
+/// lines that don't start a new entry are appended to the previous one
+pub fn parse_log(log: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+
+    for (line_number, line) in log.lines().enumerate() {
+        match LINE_PATTERN.captures(line) {
+            Some(captures) => entries.push(LogEntry {
+                line: line_number + 1,
+                time: captures["time"].to_owned(),
+                subsystem: captures["subsystem"].to_owned(),
+                level: EntryLevel::parse(&captures["level"]),
+                message: captures["message"].to_owned(),
+            }),
+            None if line.trim().is_empty() => {}
+            None => match entries.last_mut() {
+                Some(entry) => {
+                    entry.message.push('\n');
+                    entry.message.push_str(line);
+                }
+                None => entries.push(LogEntry {
+                    line: line_number + 1,
+                    time: String::new(),
+                    subsystem: String::new(),
+                    level: EntryLevel::Info,
+                    message: line.to_owned(),
+                }),
+            },
+        }
+    }
+
+    entries
+}
+
+pub fn analyze(
+    install: &GameInstall,
+    entries: &[LogEntry],
+    rule_set: &RuleSet,
+    mods: &[InstalledModPartial],
+) -> Vec<Finding> {
+    let scripts = mods
+        .iter()
+        .map(|nsmod| {
+            (
+                nsmod.mod_json.name.as_str(),
+                mod_scripts(install, &nsmod.path),
+            )
+        })
+        .collect::<Vec<(&str, Vec<String>)>>();
+
+    entries
+        .iter()
+        .enumerate()
+        .flat_map(|(index, entry)| {
+            rule_set
+                .rules
+                .iter()
+                .filter_map(|(rule, regex)| Some((rule, regex.captures(&entry.message)?)))
+                .map(|(rule, captures)| {
+                    let mut culprits = Vec::new();
+
+                    if let Some(script) = captures.name("path") {
+                        let script = script.as_str().replace('\\', "/").to_lowercase();
+                        culprits.extend(
+                            scripts
+                                .iter()
+                                .filter(|(_, paths)| {
+                                    paths.iter().any(|path| {
+                                        path.ends_with(&script) || script.ends_with(path.as_str())
+                                    })
+                                })
+                                .map(|(name, _)| name.to_string()),
+                        );
+                    }
+
+                    if let Some(name) = captures.name("mod") {
+                        // the log names the mod directly so trust it even if it isn't installed anymore
+                        culprits.push(
+                            mods.iter()
+                                .find(|nsmod| {
+                                    nsmod
+                                        .mod_json
+                                        .name
+                                        .eq_ignore_ascii_case(name.as_str().trim())
+                                })
+                                .map(|nsmod| nsmod.mod_json.name.clone())
+                                .unwrap_or_else(|| name.as_str().trim().to_owned()),
+                        );
+                    }
+
+                    // fall back to any installed mod mentioned by name
+                    if culprits.is_empty() {
+                        culprits.extend(
+                            mods.iter()
+                                .filter(|nsmod| entry.message.contains(&nsmod.mod_json.name))
+                                .map(|nsmod| nsmod.mod_json.name.clone()),
+                        );
+                    }
+
+                    culprits.sort();
+                    culprits.dedup();
+
+                    Finding {
+                        rule: rule.name.clone(),
+                        hint: rule.hint.clone(),
+                        entry: index,
+                        culprits,
+                    }
+                })
+                .collect::<Vec<Finding>>()
+        })
+        .collect()
+}
+
+/// every script under `mod/scripts/vscripts`, lowercase and relative to it
+fn mod_scripts(install: &GameInstall, mod_path: &Path) -> Vec<String> {
+    fn walk(install: &GameInstall, dir: &Path, root: &Path, scripts: &mut Vec<String>) {
+        let Result::Ok(read_dir) = install.fs.read_dir(dir) else {
+            return;
+        };
+
+        for file in read_dir {
+            let path = file.path;
+            if file.is_dir {
+                walk(install, &path, root, scripts);
+            } else if let Result::Ok(relative) = path.strip_prefix(root) {
+                scripts.push(relative.to_string_lossy().replace('\\', "/").to_lowercase());
+            }
+        }
+    }
+
+    let root = mod_path.join("mod").join("scripts").join("vscripts");
+    let mut scripts = Vec::new();
+    walk(install, &root, &root, &mut scripts);
+
+    scripts
+}
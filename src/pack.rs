@@ -0,0 +1,381 @@
+use std::{
+    io::{Cursor, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+use serde_json::{json, Value};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::{dev_link::MARKER_NAME, game_fs::GameFs, local_install::parse_mod_json};
+
+pub const MANIFEST_NAME: &str = "manifest.json";
+pub const README_NAME: &str = "README.md";
+pub const ICON_NAME: &str = "icon.png";
+/// one pattern per line in the mod dir, added to [`DEFAULT_IGNORE`]
+pub const IGNORE_NAME: &str = ".packignore";
+/// dev files that never belong in a package, `*` matches anything in a name
+pub const DEFAULT_IGNORE: [&str; 12] = [
+    ".git",
+    ".github",
+    ".gitignore",
+    ".gitattributes",
+    ".vscode",
+    ".idea",
+    ".DS_Store",
+    "Thumbs.db",
+    "*.psd",
+    "*.blend",
+    IGNORE_NAME,
+    MARKER_NAME,
+];
+const ICON_SIZE: u32 = 256;
+const MAX_DESCRIPTION: usize = 250;
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// a zip ready for thunderstore
+#[derive(Debug)]
+pub struct Package {
+    /// `<name>-<version>.zip`
+    pub file_name: String,
+    pub bytes: Vec<u8>,
+    /// the files of the mod that went into `mods/<dir>/`
+    pub files: Vec<String>,
+    /// the package files that weren't in the mod dir and were made from its mod.json
+    pub generated: Vec<&'static str>,
+}
+
+impl Package {
+    /// writes the zip into `out_dir` and returns where it went
+    pub fn write(&self, fs: &dyn GameFs, out_dir: &Path) -> AnyResult<PathBuf> {
+        let path = out_dir.join(&self.file_name);
+        fs.create_dir_all(out_dir)
+            .with_context(|| format!("couldn't create {}", out_dir.display()))?;
+        fs.write(&path, &self.bytes)
+            .with_context(|| format!("couldn't write {}", path.display()))?;
+
+        Ok(path)
+    }
+}
+
+/// validates the mod dir and lays it out as `mods/<dir>/` next to the manifest, readme and icon,
+/// every problem found is listed with its file and field
+pub fn pack(fs: &dyn GameFs, mod_dir: &Path) -> AnyResult<Package> {
+    // `drydoct pack .` from inside the mod, `.` and `..` only get a name once they're resolved
+    let mod_dir = &fs
+        .canonicalize(mod_dir)
+        .with_context(|| format!("{} doesn't exist", mod_dir.display()))?;
+    let dir = mod_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("{} has no name", mod_dir.display()))?;
+    let file = |name: &str| mod_dir.join(name);
+
+    let mod_json = parse_mod_json(
+        dir,
+        &fs.read(&file("mod.json"))
+            .with_context(|| format!("{} is missing", file("mod.json").display()))?,
+    )?;
+
+    let mut problems = Vec::new();
+    let mut generated = Vec::new();
+
+    let manifest = match fs.read(&file(MANIFEST_NAME)) {
+        Result::Ok(contents) => contents,
+        Err(_) => {
+            generated.push(MANIFEST_NAME);
+            let manifest = json!({
+                "name": mod_json.name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_"),
+                "version_number": mod_json.version,
+                "website_url": "",
+                "description": mod_json.description.chars().take(MAX_DESCRIPTION).collect::<String>(),
+                "dependencies": [],
+            });
+            serde_json::to_vec_pretty(&manifest).expect("a json value always serializes")
+        }
+    };
+    let manifest_label = if generated.contains(&MANIFEST_NAME) {
+        format!("{} (made from mod.json)", file(MANIFEST_NAME).display())
+    } else {
+        file(MANIFEST_NAME).display().to_string()
+    };
+    let (name, version) = validate_manifest(&manifest_label, &manifest, &mut problems);
+
+    let readme = match fs.read(&file(README_NAME)) {
+        Result::Ok(contents) => {
+            if std::str::from_utf8(&contents).is_err() {
+                problems.push(format!("{} isn't utf-8", file(README_NAME).display()));
+            }
+            contents
+        }
+        Err(_) => {
+            generated.push(README_NAME);
+            format!("# {}\n\n{}\n", mod_json.name, mod_json.description).into_bytes()
+        }
+    };
+
+    match fs.read(&file(ICON_NAME)) {
+        Result::Ok(icon) => {
+            if let Err(problem) = check_icon(&icon) {
+                problems.push(format!("{}: {problem}", file(ICON_NAME).display()));
+            }
+        }
+        Err(_) => problems.push(format!(
+            "{} is missing, thunderstore needs a {ICON_SIZE}x{ICON_SIZE} png",
+            file(ICON_NAME).display()
+        )),
+    }
+
+    if !problems.is_empty() {
+        bail!(
+            "{dir} can't be packed:\n{}",
+            problems
+                .iter()
+                .map(|problem| format!("  {problem}"))
+                .collect::<Vec<String>>()
+                .join("\n")
+        );
+    }
+
+    let ignore = ignore_list(fs, mod_dir);
+    let mut files = Vec::new();
+    collect_files(fs, mod_dir, mod_dir, &ignore, &mut files)?;
+    files.sort();
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut add = |name: &str, contents: &[u8]| -> AnyResult<()> {
+        writer.start_file(name, options)?;
+        writer.write_all(contents)?;
+        Ok(())
+    };
+    add(MANIFEST_NAME, &manifest)?;
+    add(README_NAME, &readme)?;
+    add(ICON_NAME, &fs.read(&file(ICON_NAME))?)?;
+    for relative in &files {
+        let contents = fs
+            .read(&mod_dir.join(relative))
+            .with_context(|| format!("couldn't read {}", mod_dir.join(relative).display()))?;
+        add(&format!("mods/{dir}/{relative}"), &contents)?;
+    }
+    let bytes = writer.finish()?.into_inner();
+
+    Ok(Package {
+        file_name: format!("{name}-{version}.zip"),
+        bytes,
+        files,
+        generated,
+    })
+}
+
+/// returns the name and version, anything wrong is added to `problems`
+fn validate_manifest(path: &str, contents: &[u8], problems: &mut Vec<String>) -> (String, String) {
+    let manifest = match serde_json::from_slice::<Value>(contents) {
+        Result::Ok(Value::Object(manifest)) => manifest,
+        Result::Ok(_) => {
+            problems.push(format!("{path} has to be an object"));
+            return Default::default();
+        }
+        Err(err) => {
+            problems.push(format!("{path} isn't valid json: {err}"));
+            return Default::default();
+        }
+    };
+    let mut field = |key: &str| match manifest.get(key) {
+        Some(Value::String(value)) => Some(value.clone()),
+        Some(_) => {
+            problems.push(format!("{path}: `{key}` has to be a string"));
+            None
+        }
+        None => {
+            problems.push(format!("{path}: `{key}` is missing"));
+            None
+        }
+    };
+
+    let name = field("name");
+    let version = field("version_number");
+    let description = field("description");
+    field("website_url");
+
+    if let Some(name) = name.as_ref().filter(|name| {
+        name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    }) {
+        problems.push(format!(
+            "{path}: `name` \"{name}\" can only have letters, numbers and underscores"
+        ));
+    }
+    if let Some(version) = version.as_ref().filter(|version| !is_version(version)) {
+        problems.push(format!(
+            "{path}: `version_number` \"{version}\" isn't MAJOR.MINOR.PATCH"
+        ));
+    }
+    if description.is_some_and(|description| description.chars().count() > MAX_DESCRIPTION) {
+        problems.push(format!(
+            "{path}: `description` is longer than {MAX_DESCRIPTION} characters"
+        ));
+    }
+    match manifest.get("dependencies") {
+        Some(Value::Array(dependencies)) => {
+            for (index, dependency) in dependencies.iter().enumerate() {
+                if !dependency.as_str().is_some_and(is_dependency) {
+                    problems.push(format!(
+                        "{path}: `dependencies[{index}]` {dependency} isn't Author-Name-MAJOR.MINOR.PATCH"
+                    ));
+                }
+            }
+        }
+        Some(_) => problems.push(format!("{path}: `dependencies` has to be a list")),
+        None => problems.push(format!("{path}: `dependencies` is missing")),
+    }
+
+    (name.unwrap_or_default(), version.unwrap_or_default())
+}
+
+fn is_version(version: &str) -> bool {
+    let parts = version.split('.').collect::<Vec<&str>>();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_dependency(dependency: &str) -> bool {
+    let mut parts = dependency.rsplitn(2, '-');
+    let (Some(version), Some(package)) = (parts.next(), parts.next()) else {
+        return false;
+    };
+
+    is_version(version)
+        && package
+            .split_once('-')
+            .is_some_and(|(author, name)| !author.is_empty() && !name.is_empty())
+}
+
+/// only the header is read, the size is in the IHDR chunk right after the signature
+fn check_icon(icon: &[u8]) -> Result<(), String> {
+    if icon.len() < 24 || icon[..8] != PNG_SIGNATURE || &icon[12..16] != b"IHDR" {
+        return Err("isn't a png".to_owned());
+    }
+
+    let width = u32::from_be_bytes(icon[16..20].try_into().expect("4 bytes"));
+    let height = u32::from_be_bytes(icon[20..24].try_into().expect("4 bytes"));
+    if (width, height) != (ICON_SIZE, ICON_SIZE) {
+        return Err(format!(
+            "is {width}x{height}, it has to be {ICON_SIZE}x{ICON_SIZE}"
+        ));
+    }
+
+    Result::Ok(())
+}
+
+/// the defaults and whatever is in the mod's `.packignore`, comments start with `#`
+fn ignore_list(fs: &dyn GameFs, mod_dir: &Path) -> Vec<String> {
+    let custom = fs
+        .read_to_string(&mod_dir.join(IGNORE_NAME))
+        .unwrap_or_default();
+
+    DEFAULT_IGNORE
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .chain(
+            custom
+                .lines()
+                .map(|line| line.trim().trim_end_matches('/'))
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_owned),
+        )
+        .collect()
+}
+
+/// a pattern with a `/` matches the path from the mod dir, anything else any single name in it
+fn is_ignored(relative: &str, ignore: &[String]) -> bool {
+    ignore.iter().any(|pattern| {
+        if pattern.contains('/') {
+            matches_glob(pattern.trim_start_matches('/'), relative)
+        } else {
+            relative.split('/').any(|name| matches_glob(pattern, name))
+        }
+    })
+}
+
+fn matches_glob(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            text.starts_with(prefix)
+                && (prefix.len()..=text.len())
+                    .filter(|index| text.is_char_boundary(*index))
+                    .any(|index| matches_glob(rest, &text[index..]))
+        }
+    }
+}
+
+/// everything in the mod dir except the package files and ignored ones, `/` separated and sorted
+fn collect_files(
+    fs: &dyn GameFs,
+    root: &Path,
+    dir: &Path,
+    ignore: &[String],
+    files: &mut Vec<String>,
+) -> AnyResult<()> {
+    for entry in fs
+        .read_dir(dir)
+        .with_context(|| format!("couldn't read {}", dir.display()))?
+    {
+        let relative = entry
+            .path
+            .strip_prefix(root)
+            .expect("read_dir returns children")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if is_ignored(&relative, ignore)
+            || [MANIFEST_NAME, README_NAME, ICON_NAME].contains(&relative.as_str())
+        {
+            continue;
+        }
+
+        if entry.is_dir {
+            collect_files(fs, root, &entry.path, ignore, files)?;
+        } else {
+            files.push(relative);
+        }
+    }
+
+    Ok(())
+}
+
+/// `drydoct pack <mod dir> [--out <dir>]`, the zip goes into the current dir by default
+pub fn run_cli(args: &[String], fs: &dyn GameFs) -> AnyResult<String> {
+    let mut mod_dir = None;
+    let mut out_dir = PathBuf::from(".");
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out" | "-o" => {
+                out_dir = args.next().context("--out needs a directory")?.into();
+            }
+            _ if mod_dir.is_none() => mod_dir = Some(PathBuf::from(arg)),
+            _ => bail!("unexpected argument {arg}, usage: drydoct pack <mod dir> [--out <dir>]"),
+        }
+    }
+    let mod_dir = mod_dir.context("usage: drydoct pack <mod dir> [--out <dir>]")?;
+
+    let package = pack(fs, &mod_dir)?;
+    let path = package.write(fs, &out_dir)?;
+    let mut report = format!(
+        "packed {} files into {}",
+        package.files.len(),
+        path.display()
+    );
+    if !package.generated.is_empty() {
+        report += &format!(
+            "\ngenerated {} from mod.json, add them to the mod to change them",
+            package.generated.join(" and ")
+        );
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,302 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_fs::GameFs,
+    integrity::sha256,
+    net,
+    thunderstore::{Package, PackageVersion},
+};
+
+/// what is in the cache and in which order it was used
+const INDEX_NAME: &str = "index.json";
+/// in megabytes
+pub const DEFAULT_LIMIT: u64 = 2048;
+
+/// downloaded thunderstore archives stored by their sha256 so every profile installs from the
+/// same copy, it isn't part of the game dir so the app always goes through [`RealFs`] and neither
+/// dry runs nor the journal see it
+///
+/// [`RealFs`]: crate::game_fs::RealFs
+#[derive(Debug, Clone)]
+pub struct PackageCache {
+    pub dir: PathBuf,
+    /// in bytes, the least recently used archives are removed when the cache grows past it
+    pub limit: u64,
+    /// archives only come from the cache, nothing is downloaded
+    pub offline: bool,
+}
+
+/// an archive in the cache, `key` is `<author>-<name>-<version>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    pub key: String,
+    pub sha256: String,
+    pub size: u64,
+    /// seconds since the unix epoch
+    pub last_used: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CacheIndex {
+    /// least recently used first
+    entries: Vec<CacheEntry>,
+}
+
+/// what cleaning the cache got rid of
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cleaned {
+    pub corrupt: usize,
+    pub evicted: usize,
+    /// files no entry points at anymore
+    pub stray: usize,
+}
+
+impl PackageCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            limit: DEFAULT_LIMIT * 1024 * 1024,
+            offline: false,
+        }
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.dir.join(INDEX_NAME)
+    }
+
+    pub fn archive_path(&self, entry: &CacheEntry) -> PathBuf {
+        self.dir.join(format!("{}.zip", entry.sha256))
+    }
+
+    /// an index that can't be read is started over, the archives it pointed at are cleaned up later
+    fn load(&self, fs: &dyn GameFs) -> CacheIndex {
+        let Result::Ok(index) = fs.read_to_string(&self.index_path()) else {
+            return CacheIndex::default();
+        };
+
+        serde_json::from_str(&index).unwrap_or_else(|err| {
+            log::warn!("starting the package cache over, its index is broken: {err}");
+            CacheIndex::default()
+        })
+    }
+
+    fn save(&self, fs: &dyn GameFs, index: &CacheIndex) -> AnyResult<()> {
+        fs.create_dir_all(&self.dir)
+            .with_context(|| format!("couldn't create {}", self.dir.display()))?;
+        fs.write(
+            &self.index_path(),
+            serde_json::to_string_pretty(index)
+                .expect("the cache index always serializes")
+                .as_bytes(),
+        )
+        .with_context(|| format!("couldn't write {}", self.index_path().display()))
+    }
+
+    /// least recently used first
+    pub fn entries(&self, fs: &dyn GameFs) -> Vec<CacheEntry> {
+        self.load(fs).entries
+    }
+
+    /// the archive and its path if the cache has an intact copy, without marking it as used
+    pub fn peek(&self, fs: &dyn GameFs, key: &str) -> Option<(PathBuf, Vec<u8>)> {
+        let index = self.load(fs);
+        let entry = index.entries.iter().find(|entry| entry.key == key)?;
+        let path = self.archive_path(entry);
+        let bytes = fs.read(&path).ok()?;
+
+        (sha256(&bytes) == entry.sha256).then_some((path, bytes))
+    }
+
+    /// the cached archive, a corrupt copy is dropped so it can be downloaded again
+    pub fn get(&self, fs: &dyn GameFs, key: &str) -> AnyResult<Option<(PathBuf, Vec<u8>)>> {
+        let mut index = self.load(fs);
+        let Some(position) = index.entries.iter().position(|entry| entry.key == key) else {
+            return Ok(None);
+        };
+
+        let mut entry = index.entries.remove(position);
+        let path = self.archive_path(&entry);
+        match fs.read(&path) {
+            Result::Ok(bytes) if sha256(&bytes) == entry.sha256 => {
+                entry.last_used = now();
+                index.entries.push(entry);
+                self.save(fs, &index)?;
+                Ok(Some((path, bytes)))
+            }
+            _ => {
+                log::warn!("{key} is missing or corrupt in the package cache");
+                // every key sharing the file is just as broken
+                index.entries.retain(|other| other.sha256 != entry.sha256);
+                self.forget(fs, &mut index, &entry)?;
+                self.save(fs, &index)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// stores an archive under `key`, older archives are evicted to make room for it
+    pub fn put(&self, fs: &dyn GameFs, key: &str, bytes: &[u8]) -> AnyResult<PathBuf> {
+        let mut index = self.load(fs);
+        if let Some(position) = index.entries.iter().position(|entry| entry.key == key) {
+            let old = index.entries.remove(position);
+            self.forget(fs, &mut index, &old)?;
+        }
+
+        let entry = CacheEntry {
+            key: key.to_owned(),
+            sha256: sha256(bytes),
+            size: bytes.len() as u64,
+            last_used: now(),
+        };
+        let path = self.archive_path(&entry);
+        fs.create_dir_all(&self.dir)
+            .with_context(|| format!("couldn't create {}", self.dir.display()))?;
+        // the same archive under another key is already there
+        if !index
+            .entries
+            .iter()
+            .any(|other| other.sha256 == entry.sha256)
+        {
+            fs.write(&path, bytes)
+                .with_context(|| format!("couldn't write {}", path.display()))?;
+        }
+        index.entries.push(entry);
+        self.evict(fs, &mut index)?;
+        self.save(fs, &index)?;
+
+        Ok(path)
+    }
+
+    pub fn remove(&self, fs: &dyn GameFs, key: &str) -> AnyResult<()> {
+        let mut index = self.load(fs);
+        if let Some(position) = index.entries.iter().position(|entry| entry.key == key) {
+            let entry = index.entries.remove(position);
+            self.forget(fs, &mut index, &entry)?;
+        }
+
+        self.save(fs, &index)
+    }
+
+    /// drops corrupt archives and ones over the limit, then removes files nothing points at
+    pub fn clean(&self, fs: &dyn GameFs) -> AnyResult<Cleaned> {
+        let mut cleaned = Cleaned::default();
+        let mut index = self.load(fs);
+
+        let (intact, corrupt): (Vec<CacheEntry>, Vec<CacheEntry>) =
+            index.entries.drain(..).partition(|entry| {
+                fs.read(&self.archive_path(entry))
+                    .is_ok_and(|bytes| sha256(&bytes) == entry.sha256)
+            });
+        index.entries = intact;
+        cleaned.corrupt = corrupt.len();
+        cleaned.evicted = self.evict(fs, &mut index)?;
+        self.save(fs, &index)?;
+
+        let kept = index
+            .entries
+            .iter()
+            .map(|entry| self.archive_path(entry))
+            .chain([self.index_path()])
+            .collect::<Vec<PathBuf>>();
+        for file in fs
+            .read_dir(&self.dir)
+            .with_context(|| format!("couldn't read {}", self.dir.display()))?
+        {
+            if !file.is_dir && !kept.contains(&file.path) {
+                fs.remove_file(&file.path)
+                    .with_context(|| format!("couldn't remove {}", file.path.display()))?;
+                cleaned.stray += 1;
+            }
+        }
+
+        Ok(cleaned)
+    }
+
+    /// removes the least recently used archives until the cache fits, the newest one always stays
+    fn evict(&self, fs: &dyn GameFs, index: &mut CacheIndex) -> AnyResult<usize> {
+        let mut evicted = 0;
+        while index.entries.len() > 1
+            && index.entries.iter().map(|entry| entry.size).sum::<u64>() > self.limit
+        {
+            let entry = index.entries.remove(0);
+            log::info!("evicting {} from the package cache", entry.key);
+            self.forget(fs, index, &entry)?;
+            evicted += 1;
+        }
+
+        Ok(evicted)
+    }
+
+    /// removes the file of an entry that's no longer in the index unless another entry shares it
+    fn forget(&self, fs: &dyn GameFs, index: &mut CacheIndex, entry: &CacheEntry) -> AnyResult<()> {
+        let path = self.archive_path(entry);
+        if index
+            .entries
+            .iter()
+            .any(|other| other.sha256 == entry.sha256)
+            || !fs.exists(&path)
+        {
+            return Ok(());
+        }
+
+        fs.remove_file(&path)
+            .with_context(|| format!("couldn't remove {}", path.display()))
+    }
+
+    /// the archive of a package version, downloaded into the cache first when it isn't there,
+    /// the path is none when it couldn't be cached
+    pub fn fetch(
+        &self,
+        fs: &dyn GameFs,
+        package: &Package,
+        version: &PackageVersion,
+    ) -> AnyResult<(Option<PathBuf>, Vec<u8>)> {
+        let key = package.cache_key(&version.version_number);
+        if let Some((path, bytes)) = self.get(fs, &key)? {
+            return Ok((Some(path), bytes));
+        }
+        if self.offline {
+            bail!("{key} isn't in the package cache and drydoct is offline");
+        }
+
+        let bytes = net::fetch(&version.download_url)
+            .with_context(|| format!("couldn't download {key}.zip"))?;
+        // a failed write only means it's downloaded again next time
+        let path = self
+            .put(fs, &key, &bytes)
+            .map_err(|err| log::warn!("couldn't cache {key}: {err:#}"))
+            .ok();
+
+        Ok((path, bytes))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// `12.3 MB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
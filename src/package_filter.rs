@@ -0,0 +1,288 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+
+use crate::thunderstore::Package;
+
+/// saved filters can use other saved filters but not forever
+const MAX_DEPTH: usize = 8;
+const KEYS: &str = "category, author, updated, downloads, installed, deprecated or nsfw";
+
+/// a parsed package query, `key:value` terms filter and every other word has to be in the
+/// name, author or description
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageFilter {
+    pub words: Vec<String>,
+    /// every one has to match a category
+    pub categories: Vec<String>,
+    /// any of them
+    pub authors: Vec<String>,
+    /// `YYYY-MM-DD`
+    pub updated_since: Option<String>,
+    pub min_downloads: Option<u64>,
+    pub installed: Option<bool>,
+    pub show_deprecated: bool,
+    pub show_nsfw: bool,
+}
+
+impl PackageFilter {
+    /// `@name` is replaced by the saved filter called `name`, `today` is in days since the unix
+    /// epoch and only used for relative dates like `updated:30d`
+    pub fn parse(query: &str, saved: &BTreeMap<String, String>, today: u64) -> AnyResult<Self> {
+        let mut filter = Self::default();
+        filter.add(query, saved, today, 0)?;
+
+        Ok(filter)
+    }
+
+    fn add(
+        &mut self,
+        query: &str,
+        saved: &BTreeMap<String, String>,
+        today: u64,
+        depth: usize,
+    ) -> AnyResult<()> {
+        if depth > MAX_DEPTH {
+            bail!("saved filters use each other in a loop");
+        }
+
+        for term in terms(query) {
+            if let Some(name) = term.strip_prefix('@') {
+                let query = saved
+                    .get(name)
+                    .with_context(|| format!("there is no saved filter called {name}"))?;
+                self.add(query, saved, today, depth + 1)?;
+                continue;
+            }
+
+            let Some((key, value)) = term.split_once(':') else {
+                self.words.push(term.to_lowercase());
+                continue;
+            };
+            let value = value.to_lowercase();
+            match key.to_lowercase().as_str() {
+                "category" | "cat" => self.categories.push(value),
+                "author" | "owner" => self.authors.push(value),
+                "updated" => self.updated_since = Some(parse_since(&value, today)?),
+                "downloads" => self.min_downloads = Some(parse_count(&value)?),
+                "installed" => self.installed = Some(parse_bool(key, &value)?),
+                "deprecated" => self.show_deprecated = parse_shown(key, &value)?,
+                "nsfw" => self.show_nsfw = parse_shown(key, &value)?,
+                _ => bail!("unknown filter \"{key}\", try {KEYS}"),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn matches(&self, package: &Package, installed: bool) -> bool {
+        let description = package
+            .versions
+            .first()
+            .map(|version| version.description.to_lowercase())
+            .unwrap_or_default();
+        let (name, owner) = (package.name.to_lowercase(), package.owner.to_lowercase());
+        let categories = package
+            .categories
+            .iter()
+            .map(|category| category.to_lowercase())
+            .collect::<Vec<String>>();
+
+        self.words
+            .iter()
+            .all(|word| name.contains(word) || owner.contains(word) || description.contains(word))
+            && self
+                .categories
+                .iter()
+                .all(|wanted| categories.iter().any(|category| category.contains(wanted)))
+            && (self.authors.is_empty() || self.authors.contains(&owner))
+            && self.updated_since.as_ref().is_none_or(|since| {
+                package
+                    .date_updated
+                    .get(..10)
+                    .is_some_and(|date| date >= since.as_str())
+            })
+            && self
+                .min_downloads
+                .is_none_or(|min| package.downloads() >= min)
+            && self.installed.is_none_or(|wanted| wanted == installed)
+            && (self.show_deprecated || !package.is_deprecated)
+            && (self.show_nsfw || !package.has_nsfw_content)
+    }
+
+    /// every part of the filter in words, for the filter panel
+    pub fn describe(&self) -> Vec<(&'static str, String)> {
+        let any = |values: &[String]| {
+            if values.is_empty() {
+                "any".to_owned()
+            } else {
+                values.join(", ")
+            }
+        };
+        let shown = |shown: bool| if shown { "shown" } else { "hidden" }.to_owned();
+
+        vec![
+            ("words", any(&self.words)),
+            ("category", any(&self.categories)),
+            ("author", any(&self.authors)),
+            (
+                "updated since",
+                self.updated_since
+                    .clone()
+                    .unwrap_or_else(|| "any".to_owned()),
+            ),
+            (
+                "downloads",
+                self.min_downloads
+                    .map(|min| format!("at least {min}"))
+                    .unwrap_or_else(|| "any".to_owned()),
+            ),
+            (
+                "installed",
+                match self.installed {
+                    Some(true) => "yes",
+                    Some(false) => "no",
+                    None => "any",
+                }
+                .to_owned(),
+            ),
+            ("deprecated", shown(self.show_deprecated)),
+            ("nsfw", shown(self.show_nsfw)),
+        ]
+    }
+}
+
+/// splits on whitespace outside of double quotes, the quotes are dropped
+fn terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push(std::mem::take(&mut term));
+                }
+            }
+            c => term.push(c),
+        }
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+
+    terms
+}
+
+fn parse_since(value: &str, today: u64) -> AnyResult<String> {
+    if let Some(days) = value.strip_suffix('d') {
+        let days = days
+            .parse::<u64>()
+            .with_context(|| format!("\"{value}\" isn't a number of days"))?;
+        return Ok(civil_date(today.saturating_sub(days)));
+    }
+
+    let is_date = value.len() == 10
+        && value.char_indices().all(|(index, c)| match index {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        });
+    if !is_date {
+        bail!("updated takes a date like 2024-01-31 or a number of days like 30d, not \"{value}\"");
+    }
+
+    Ok(value.to_owned())
+}
+
+/// `1500`, `10k` or `2m`
+fn parse_count(value: &str) -> AnyResult<u64> {
+    let (number, multiplier) = match value.chars().last() {
+        Some('k') => (&value[..value.len() - 1], 1_000.0),
+        Some('m') => (&value[..value.len() - 1], 1_000_000.0),
+        _ => (value, 1.0),
+    };
+
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|number| *number >= 0.0)
+        .map(|number| (number * multiplier) as u64)
+        .with_context(|| format!("downloads takes a number like 1500 or 10k, not \"{value}\""))
+}
+
+fn parse_bool(key: &str, value: &str) -> AnyResult<bool> {
+    match value {
+        "yes" | "true" | "y" => Ok(true),
+        "no" | "false" | "n" => Ok(false),
+        _ => bail!("{key} takes yes or no, not \"{value}\""),
+    }
+}
+
+fn parse_shown(key: &str, value: &str) -> AnyResult<bool> {
+    match value {
+        "show" | "shown" | "yes" => Ok(true),
+        "hide" | "hidden" | "no" => Ok(false),
+        _ => bail!("{key} takes show or hide, not \"{value}\""),
+    }
+}
+
+/// days since the unix epoch
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / (60 * 60 * 24))
+        .unwrap_or_default()
+}
+
+/// `YYYY-MM-DD` of a day since the unix epoch, the proleptic gregorian calendar like thunderstore's
+/// dates
+pub fn civil_date(days: u64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// named queries, a missing file means nothing is saved yet
+pub fn load_saved(path: &Path) -> AnyResult<BTreeMap<String, String>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    json5::from_str(
+        &fs::read_to_string(path).with_context(|| format!("couldn't read {}", path.display()))?,
+    )
+    .with_context(|| format!("invalid saved filters in {}", path.display()))
+}
+
+pub fn save_saved(path: &Path, saved: &BTreeMap<String, String>) -> AnyResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("couldn't create {}", parent.display()))?;
+    }
+
+    fs::write(
+        path,
+        serde_json::to_string_pretty(saved).expect("a map of strings always serializes"),
+    )
+    .with_context(|| format!("couldn't write {}", path.display()))
+}
@@ -0,0 +1,390 @@
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::GlobalState,
+    background::Background,
+    keymap::{Action, KeyContext, Keymap},
+    mods_views::{reload_mods, InstalledModPartial, PackageEntry},
+    package_filter::{self, PackageFilter},
+    theme::theme,
+    thunderstore::{fetch_index, Package},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Search,
+    Filter,
+    SaveAs,
+}
+
+impl Prompt {
+    fn title(self) -> &'static str {
+        match self {
+            Prompt::Search => "search packages",
+            Prompt::Filter => "filter packages",
+            Prompt::SaveAs => "save the filter as",
+        }
+    }
+}
+
+/// what a key did to the browser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserEvent {
+    Ignored,
+    Handled,
+    /// the shown packages have to be filtered again
+    Changed,
+}
+
+/// the thunderstore index behind the packages view and the query that narrows it down
+#[derive(Default)]
+pub struct PackageBrowser {
+    index: Option<Vec<Package>>,
+    fetching: Background<Vec<Package>>,
+    /// why the index couldn't be fetched, it isn't tried again until it's forgotten
+    error: Option<String>,
+    /// loaded the first time a filter is parsed
+    saved: Option<BTreeMap<String, String>>,
+    query: String,
+    search: String,
+    /// what is being typed and what it replaces, put back on escape
+    prompt: Option<(Prompt, String, String)>,
+    /// how many packages the index had when it was last filtered
+    total: usize,
+}
+
+impl PackageBrowser {
+    /// the index is fetched again the next time the view is filtered
+    pub fn forget_index(&mut self) {
+        self.index = None;
+        self.error = None;
+    }
+
+    /// whether the index just came in and the view has to be filtered again
+    pub fn tick(&mut self) -> AnyResult<bool> {
+        match self.fetching.poll() {
+            None => Ok(false),
+            Some(Result::Ok(index)) => {
+                self.index = Some(index);
+                Ok(true)
+            }
+            Some(Err(err)) => {
+                self.error = Some(format!("{err:#}"));
+                Err(err.context("couldn't fetch the package index"))
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn is_fetching(&self) -> bool {
+        self.fetching.is_running()
+    }
+
+    pub fn is_typing(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    fn saved(&mut self, state: &GlobalState) -> AnyResult<&BTreeMap<String, String>> {
+        if self.saved.is_none() {
+            self.saved = Some(package_filter::load_saved(&state.saved_filters)?);
+        }
+
+        Ok(self.saved.as_ref().expect("loaded above"))
+    }
+
+    /// the query with the search words added, they narrow it down the same way
+    fn filter(&mut self, query: &str, state: &GlobalState) -> AnyResult<PackageFilter> {
+        let search = self.search.clone();
+        let mut filter = PackageFilter::parse(query, self.saved(state)?, package_filter::today())?;
+        filter
+            .words
+            .extend(search.split_whitespace().map(str::to_lowercase));
+
+        Ok(filter)
+    }
+
+    /// the thunderstore index once it's fetched
+    pub fn index(&self) -> Option<&[Package]> {
+        self.index.as_deref()
+    }
+
+    /// fetches the index in the background unless it's there or failed, returns whether it's
+    /// on its way
+    pub fn fetch_index(&mut self, state: &GlobalState) -> bool {
+        if self.index.is_none() && !self.fetching.is_running() && self.error.is_none() {
+            let package_index = state.package_index.clone();
+            self.fetching.start(move || fetch_index(&package_index));
+        }
+
+        self.fetching.is_running()
+    }
+
+    /// empty until the index is fetched in the background
+    pub fn entries(&mut self, state: &GlobalState) -> AnyResult<Vec<PackageEntry>> {
+        let filter = self.filter(&self.query.clone(), state)?;
+        let installed = reload_mods(&state.install)?.0;
+        let Some(index) = self.index.as_deref() else {
+            self.fetch_index(state);
+            self.total = 0;
+            return Ok(Vec::new());
+        };
+        self.total = index.len();
+
+        Ok(index
+            .iter()
+            .map(|package| (package, find_installed(package, &installed).is_some()))
+            .filter(|(package, installed)| filter.matches(package, *installed))
+            .map(|(package, installed)| PackageEntry {
+                package: package.clone(),
+                installed,
+            })
+            .collect())
+    }
+
+    pub fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        state: &mut GlobalState,
+    ) -> AnyResult<BrowserEvent> {
+        if let Some((prompt, text, _)) = self.prompt.as_mut() {
+            let prompt = *prompt;
+            match key.code {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Backspace => _ = text.pop(),
+                KeyCode::Enter => return self.submit(state),
+                KeyCode::Esc => {
+                    let (_, _, old) = self.prompt.take().expect("checked above");
+                    state.is_typing = false;
+                    match prompt {
+                        Prompt::Search => self.search = old,
+                        Prompt::Filter => self.query = old,
+                        Prompt::SaveAs => return Ok(BrowserEvent::Handled),
+                    }
+                    return Ok(BrowserEvent::Changed);
+                }
+                _ => return Ok(BrowserEvent::Handled),
+            }
+
+            // the view follows the typing as long as what's typed makes sense
+            let text = text.clone();
+            return Ok(match prompt {
+                Prompt::Search => {
+                    self.search = text;
+                    BrowserEvent::Changed
+                }
+                Prompt::Filter if self.filter(&text, state).is_ok() => {
+                    self.query = text;
+                    BrowserEvent::Changed
+                }
+                Prompt::Filter | Prompt::SaveAs => BrowserEvent::Handled,
+            });
+        }
+
+        if state.is_typing {
+            return Ok(BrowserEvent::Ignored);
+        }
+
+        match state.keymap.action(KeyContext::Packages, &key) {
+            Some(Action::Search) => self.open(Prompt::Search, self.search.clone(), state),
+            Some(Action::Filter) => self.open(Prompt::Filter, self.query.clone(), state),
+            Some(Action::SaveFilter) => {
+                if self.query.trim().is_empty() {
+                    bail!("there is no filter to save");
+                }
+                self.open(Prompt::SaveAs, String::new(), state)
+            }
+            Some(Action::NextFilter) => {
+                let current = self.query.strip_prefix('@').unwrap_or_default().to_owned();
+                let saved = self.saved(state)?;
+                let next = saved
+                    .keys()
+                    .find(|name| **name > current)
+                    .or_else(|| saved.keys().next())
+                    .context("no saved filters yet")?
+                    .clone();
+                state
+                    .notifier
+                    .info(format!("filtering with {next}: {}", saved[&next]));
+                self.query = format!("@{next}");
+                Ok(BrowserEvent::Changed)
+            }
+            Some(Action::Clear) => {
+                self.query.clear();
+                self.search.clear();
+                Ok(BrowserEvent::Changed)
+            }
+            _ => Ok(BrowserEvent::Ignored),
+        }
+    }
+
+    fn open(
+        &mut self,
+        prompt: Prompt,
+        text: String,
+        state: &mut GlobalState,
+    ) -> AnyResult<BrowserEvent> {
+        self.prompt = Some((prompt, text.clone(), text));
+        state.is_typing = true;
+
+        Ok(BrowserEvent::Handled)
+    }
+
+    /// a bad filter or name keeps the prompt open
+    fn submit(&mut self, state: &mut GlobalState) -> AnyResult<BrowserEvent> {
+        let Some((prompt, text, _)) = self.prompt.clone() else {
+            return Ok(BrowserEvent::Ignored);
+        };
+
+        match prompt {
+            Prompt::Search => self.search = text,
+            Prompt::Filter => {
+                self.filter(&text, state)?;
+                self.query = text;
+            }
+            Prompt::SaveAs => {
+                let name = text.trim().to_owned();
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    bail!("filter names can't be empty or have spaces");
+                }
+                let mut saved = self.saved(state)?.clone();
+                saved.insert(name.clone(), self.query.clone());
+                package_filter::save_saved(&state.saved_filters, &saved)?;
+                self.saved = Some(saved);
+                state
+                    .notifier
+                    .info(format!("saved the filter as {name}, use it with @{name}"));
+            }
+        }
+        self.prompt = None;
+        state.is_typing = false;
+
+        Ok(BrowserEvent::Changed)
+    }
+
+    /// the query above the packages, returns the area left for them
+    pub fn render_bar(&self, frame: &mut Frame<'_>, rect: Rect, shown: usize) -> Rect {
+        let layout = Layout::new()
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(rect);
+
+        let value = |value: &str| {
+            Span::styled(
+                if value.is_empty() { "none" } else { value }.to_owned(),
+                Style::default().fg(theme().accent),
+            )
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(" filter ").fg(theme().muted),
+                value(&self.query),
+                Span::raw("  search ").fg(theme().muted),
+                value(&self.search),
+                Span::raw(match &self.error {
+                    _ if self.fetching.is_running() => "  loading the package index".to_owned(),
+                    Some(_) => "  couldn't load the package index".to_owned(),
+                    None => format!("  {shown}/{} packages", self.total),
+                })
+                .fg(theme().muted),
+            ]))
+            .bg(theme().background)
+            .fg(theme().text),
+            layout[0],
+        );
+
+        layout[1]
+    }
+
+    /// the prompt being typed in, a filter shows what it was understood as
+    pub fn render_prompt(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let Some((prompt, text, _)) = &self.prompt else {
+            return;
+        };
+        let area = rect.inner(&Margin {
+            vertical: rect.height / 5,
+            horizontal: rect.width / 8,
+        });
+
+        let mut lines = vec![Line::from(vec![
+            Span::raw(text.as_str()),
+            Span::styled("_", Style::default().fg(theme().accent)),
+        ])];
+        if *prompt == Prompt::Filter {
+            let saved = self.saved.clone().unwrap_or_default();
+            lines.push(Line::raw(""));
+            match PackageFilter::parse(text, &saved, package_filter::today()) {
+                Result::Ok(filter) => {
+                    lines.extend(filter.describe().into_iter().map(|(name, value)| {
+                        Line::from(vec![
+                            Span::styled(format!("{name:<14}"), Style::default().fg(theme().muted)),
+                            Span::raw(value),
+                        ])
+                    }))
+                }
+                Err(err) => lines.push(Line::styled(
+                    format!("{err:#}"),
+                    Style::default().fg(theme().error),
+                )),
+            }
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                "category: author: updated:2024-01-31|30d downloads:10k installed:yes|no deprecated:show nsfw:show",
+                Style::default().fg(theme().muted),
+            ));
+            if !saved.is_empty() {
+                lines.push(Line::styled(
+                    format!(
+                        "saved: {}",
+                        saved
+                            .keys()
+                            .map(|name| format!("@{name}"))
+                            .collect::<Vec<String>>()
+                            .join(" ")
+                    ),
+                    Style::default().fg(theme().muted),
+                ));
+            }
+        }
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::all())
+                    .title(prompt.title())
+                    .title_style(Style::default().fg(theme().accent))
+                    .border_style(Style::default().fg(theme().selected))
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            area,
+        );
+    }
+
+    pub fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        match &self.prompt {
+            Some((Prompt::SaveAs, _, _)) => {
+                vec!["save - enter".to_owned(), "cancel - esc".to_owned()]
+            }
+            Some(_) => vec!["done - enter".to_owned(), "cancel - esc".to_owned()],
+            None => keymap.hints(KeyContext::Packages),
+        }
+    }
+}
+
+/// by the manifest thunderstore put next to it or else by the name of the mod
+pub fn find_installed<'a>(
+    package: &Package,
+    installed: &'a [InstalledModPartial],
+) -> Option<&'a InstalledModPartial> {
+    installed.iter().find(|nsmod| {
+        nsmod
+            .manifest
+            .as_ref()
+            .is_some_and(|manifest| manifest.name == package.name)
+            || package.is_mod(&nsmod.mod_json.name)
+    })
+}
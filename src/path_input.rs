@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{game_fs::GameFs, theme::theme};
+
+/// what a key did to the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathInputEvent {
+    Edited,
+    Submit,
+    Cancel,
+}
+
+/// a single line path input that completes like a shell does on tab
+#[derive(Debug, Clone, Default)]
+pub struct PathInput {
+    pub value: String,
+    /// what the last tab could have completed to when it wasn't the only option
+    candidates: Vec<String>,
+}
+
+impl PathInput {
+    pub fn handle_key(&mut self, key: KeyEvent, fs: &dyn GameFs) -> PathInputEvent {
+        match key.code {
+            KeyCode::Enter => return PathInputEvent::Submit,
+            KeyCode::Esc => return PathInputEvent::Cancel,
+            KeyCode::Tab => {
+                self.complete(fs);
+                return PathInputEvent::Edited;
+            }
+            KeyCode::Char(c) => self.value.push(c),
+            KeyCode::Backspace => _ = self.value.pop(),
+            _ => {}
+        }
+
+        self.candidates.clear();
+        PathInputEvent::Edited
+    }
+
+    /// the typed path with a leading `~` pointing at the home dir
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(expand_home(self.value.trim()))
+    }
+
+    /// completes the last component as far as every match agrees
+    pub fn complete(&mut self, fs: &dyn GameFs) {
+        let value = expand_home(&self.value);
+        let (dir, prefix) = match value.rfind(['/', '\\']) {
+            Some(split) => (&value[..=split], &value[split + 1..]),
+            None => ("", value.as_str()),
+        };
+
+        let mut matches = fs
+            .read_dir(&PathBuf::from(if dir.is_empty() { "." } else { dir }))
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|entry| {
+                let name = entry.path.file_name()?.to_str()?.to_owned();
+                name.starts_with(prefix)
+                    .then(|| if entry.is_dir { name + "/" } else { name })
+            })
+            .collect::<Vec<String>>();
+        matches.sort();
+
+        let Some(first) = matches.first() else {
+            self.candidates.clear();
+            return;
+        };
+        let common = matches.iter().fold(first.as_str(), |common, name| {
+            let same = common
+                .char_indices()
+                .zip(name.chars())
+                .find(|((_, a), b)| a != b)
+                .map_or(common.len().min(name.len()), |((index, _), _)| index);
+            &common[..same]
+        });
+
+        self.value = format!("{dir}{common}");
+        self.candidates = if matches.len() > 1 {
+            matches
+        } else {
+            Vec::new()
+        };
+    }
+
+    pub fn render(&self, frame: &mut Frame<'_>, rect: Rect, title: &str) {
+        let area = rect.inner(&Margin {
+            vertical: rect.height / 3,
+            horizontal: rect.width / 8,
+        });
+
+        let mut lines = vec![Line::from(vec![
+            Span::raw(self.value.as_str()),
+            Span::styled("_", Style::default().fg(theme().accent)),
+        ])];
+        if !self.candidates.is_empty() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                self.candidates.join("  "),
+                Style::default().fg(theme().muted),
+            ));
+        }
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .borders(Borders::all())
+                    .title(title)
+                    .title_style(Style::default().fg(theme().accent))
+                    .border_style(Style::default().fg(theme().selected))
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            area,
+        );
+    }
+
+    pub fn render_binds(&self) -> Vec<String> {
+        vec![
+            "complete - tab".to_owned(),
+            "open - enter".to_owned(),
+            "cancel - esc".to_owned(),
+        ]
+    }
+}
+
+fn expand_home(value: &str) -> String {
+    match (value.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            format!("{}{rest}", home.display())
+        }
+        _ => value.to_owned(),
+    }
+}
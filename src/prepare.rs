@@ -0,0 +1,427 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    game_fs::GameFs,
+    install::GameInstall,
+    integrity::Record,
+    local_install::{InstallPlan, LocalPackage},
+    master_server::{ModInfo, ModStatus, Server, ServerMod},
+    mod_store::{copy_mod, ModStore},
+    mods_views::InstalledModPartial,
+    package_cache::PackageCache,
+    thunderstore::{find_dependency, find_package, Package, PackageVersion},
+};
+
+/// recreated every time a server is prepared into a throwaway profile
+pub const THROWAWAY_PROFILE: &str = "R2Northstar-server";
+/// the mods that come with northstar itself, they can't be installed from thunderstore
+pub const CORE_PREFIX: &str = "Northstar.";
+
+/// the required mods of a server from pasted json, a whole server, its `modInfo` or a list of mods
+pub fn parse_required_mods(json: &str) -> AnyResult<Vec<ServerMod>> {
+    const EXPECTED: &str =
+        "expected a server, its modInfo or a list of mods with a Name and Version";
+
+    let value = serde_json::from_str::<Value>(json).context(EXPECTED)?;
+    let mods = match &value {
+        Value::Object(object) if object.contains_key("modInfo") => {
+            let server = Server::deserialize(value).context(EXPECTED)?;
+            server.required_mods().cloned().collect()
+        }
+        Value::Object(object) if object.contains_key("Mods") => ModInfo::deserialize(value)
+            .context(EXPECTED)?
+            .mods
+            .into_iter()
+            .filter(|server_mod| server_mod.required_on_client)
+            .collect(),
+        // a hand made list only has the mods that are needed
+        Value::Array(_) => Vec::<ServerMod>::deserialize(value).context(EXPECTED)?,
+        _ => bail!(EXPECTED),
+    };
+    if mods.iter().any(|server_mod| server_mod.name.is_empty()) {
+        bail!("every mod needs a Name");
+    }
+
+    Ok(mods)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Ready,
+    Enable,
+    Install {
+        /// boxed, the index entry is much bigger than the other steps
+        package: Box<Package>,
+        version: PackageVersion,
+        /// the installed version it replaces
+        replaces: Option<String>,
+        /// the mod that depends on it when the server didn't ask for it
+        needed_by: Option<String>,
+    },
+    /// why it can't be done
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedStep {
+    pub name: String,
+    pub version: String,
+    pub step: Step,
+    /// the installed mod, copied into a throwaway profile when it's ready or only disabled
+    path: Option<PathBuf>,
+    /// the installed mod an install takes the place of, the new one can extract to another dir
+    replaced: Option<PathBuf>,
+}
+
+impl std::fmt::Display for PlannedStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}: ", self.name, self.version)?;
+        match &self.step {
+            Step::Ready => write!(f, "ready"),
+            Step::Enable => write!(f, "enable"),
+            Step::Install {
+                package,
+                replaces,
+                needed_by,
+                ..
+            } => {
+                write!(f, "install {}-{}", package.owner, package.name)?;
+                if let Some(replaces) = replaces {
+                    write!(f, " over {replaces}")?;
+                }
+                if let Some(needed_by) = needed_by {
+                    write!(f, " for {needed_by}")?;
+                }
+                Result::Ok(())
+            }
+            Step::Unavailable(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+/// what it takes for a profile to join a server
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreparePlan {
+    pub server: String,
+    pub steps: Vec<PlannedStep>,
+    /// installed northstar mods, a throwaway profile needs them to start at all
+    core: Vec<PathBuf>,
+}
+
+/// a plan with its archives downloaded, ready to be applied
+#[derive(Debug, Clone)]
+pub struct FetchedPlan {
+    pub plan: PreparePlan,
+    installs: Vec<InstallPlan>,
+    /// the profile it goes into
+    pub throwaway: bool,
+}
+
+impl PreparePlan {
+    pub fn new(
+        server: &str,
+        required: &[ServerMod],
+        installed: &[InstalledModPartial],
+        index: &[Package],
+    ) -> Self {
+        let installed_path = |name: &str| {
+            installed
+                .iter()
+                .find(|nsmod| nsmod.mod_json.name == name)
+                .map(|nsmod| nsmod.path.clone())
+        };
+
+        let mut steps = required
+            .iter()
+            .map(|server_mod| {
+                let step = match ModStatus::of(server_mod, installed) {
+                    ModStatus::Installed => Step::Ready,
+                    ModStatus::Disabled => Step::Enable,
+                    _ if server_mod.name.starts_with(CORE_PREFIX) => Step::Unavailable(format!(
+                        "comes with northstar, update it to get {}",
+                        server_mod.version
+                    )),
+                    ModStatus::OtherVersion(installed_version) => {
+                        install_step(index, server_mod, Some(installed_version))
+                    }
+                    ModStatus::Missing => install_step(index, server_mod, None),
+                };
+
+                PlannedStep {
+                    name: server_mod.name.clone(),
+                    version: server_mod.version.clone(),
+                    path: matches!(step, Step::Ready | Step::Enable)
+                        .then(|| installed_path(&server_mod.name))
+                        .flatten(),
+                    replaced: matches!(
+                        step,
+                        Step::Install {
+                            replaces: Some(_),
+                            ..
+                        }
+                    )
+                    .then(|| installed_path(&server_mod.name))
+                    .flatten(),
+                    step,
+                }
+            })
+            .collect::<Vec<PlannedStep>>();
+
+        // dependencies of what gets installed, unless something with their name is there already
+        let mut index_of_step = 0;
+        while index_of_step < steps.len() {
+            if let Step::Install {
+                package, version, ..
+            } = &steps[index_of_step].step
+            {
+                let needed_by = package.name.clone();
+                let dependencies = version
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| !dependency.starts_with("northstar-Northstar-"))
+                    .filter_map(|dependency| find_dependency(index, dependency))
+                    .filter(|(package, _)| {
+                        !steps.iter().any(|step| match &step.step {
+                            Step::Install { package: other, .. } => **other == **package,
+                            _ => false,
+                        }) && !installed
+                            .iter()
+                            .any(|nsmod| package.is_mod(&nsmod.mod_json.name))
+                    })
+                    .map(|(package, version)| PlannedStep {
+                        name: package.name.clone(),
+                        version: version.version_number.clone(),
+                        step: Step::Install {
+                            package: Box::new(package.clone()),
+                            version: version.clone(),
+                            replaces: None,
+                            needed_by: Some(needed_by.clone()),
+                        },
+                        path: None,
+                        replaced: None,
+                    })
+                    .collect::<Vec<PlannedStep>>();
+                steps.extend(dependencies);
+            }
+            index_of_step += 1;
+        }
+
+        Self {
+            server: server.to_owned(),
+            steps,
+            core: installed
+                .iter()
+                .filter(|nsmod| nsmod.mod_json.name.starts_with(CORE_PREFIX))
+                .map(|nsmod| nsmod.path.clone())
+                .collect(),
+        }
+    }
+
+    /// installs that would run and how many mods get enabled
+    pub fn counts(&self) -> (usize, usize) {
+        self.steps
+            .iter()
+            .fold((0, 0), |(installs, enables), step| match step.step {
+                Step::Install { .. } => (installs + 1, enables),
+                Step::Enable => (installs, enables + 1),
+                _ => (installs, enables),
+            })
+    }
+
+    pub fn unavailable(&self) -> impl Iterator<Item = &PlannedStep> {
+        self.steps
+            .iter()
+            .filter(|step| matches!(step.step, Step::Unavailable(_)))
+    }
+
+    /// downloads every archive into the package cache so applying doesn't touch the network,
+    /// `cache_fs` is where the cache is, the app passes [`RealFs`](crate::game_fs::RealFs)
+    pub fn fetch(
+        self,
+        install: &GameInstall,
+        package_cache: &PackageCache,
+        cache_fs: &dyn GameFs,
+        throwaway: bool,
+    ) -> AnyResult<FetchedPlan> {
+        let target = if throwaway {
+            install.with_profile(THROWAWAY_PROFILE)
+        } else {
+            install.clone()
+        };
+
+        let installs = self
+            .steps
+            .iter()
+            .filter_map(|step| match &step.step {
+                Step::Install {
+                    package, version, ..
+                } => Some((package, version)),
+                _ => None,
+            })
+            .map(|(package, version)| {
+                let key = package.cache_key(&version.version_number);
+                let (path, bytes) = package_cache.fetch(cache_fs, package, version)?;
+                let mut plan = LocalPackage::from_zip(&package.name, bytes)
+                    .and_then(|local| local.plan(&target, Path::new(&format!("{key}.zip"))))
+                    .with_context(|| format!("couldn't install {key}"))?;
+                // an archive that couldn't be cached has nowhere to be repaired from
+                plan.source = path;
+                plan.author = Some(package.owner.clone());
+                Ok(plan)
+            })
+            .collect::<AnyResult<Vec<InstallPlan>>>()?;
+
+        Ok(FetchedPlan {
+            plan: self,
+            installs,
+            throwaway,
+        })
+    }
+}
+
+impl FetchedPlan {
+    pub fn profile<'a>(&self, install: &'a GameInstall) -> &'a str {
+        if self.throwaway {
+            THROWAWAY_PROFILE
+        } else {
+            &install.profile
+        }
+    }
+
+    pub fn describe(&self, install: &GameInstall) -> String {
+        format!("prepare {} for {}", self.profile(install), self.plan.server)
+    }
+
+    pub fn apply(&self, install: &GameInstall) -> AnyResult<()> {
+        if !self.throwaway {
+            for replaced in self
+                .plan
+                .steps
+                .iter()
+                .filter_map(|step| step.replaced.as_ref())
+            {
+                remove_replaced(install, replaced)?;
+            }
+            for plan in &self.installs {
+                plan.install(install)?;
+            }
+            for step in &self.plan.steps {
+                if step.step == Step::Enable {
+                    install.set_mod_enabled(&step.name, true)?;
+                }
+            }
+
+            return Ok(());
+        }
+
+        let target = install.with_profile(THROWAWAY_PROFILE);
+        if install.fs.exists(&target.profile_dir()) {
+            install
+                .fs
+                .remove_dir_all(&target.profile_dir())
+                .with_context(|| format!("couldn't remove the old {THROWAWAY_PROFILE}"))?;
+        }
+        if let Some(store) = ModStore::of(install) {
+            store.release(install, &target.profile_dir())?;
+        }
+        install
+            .fs
+            .create_dir_all(&target.mods_dir())
+            .with_context(|| format!("couldn't create {}", target.mods_dir().display()))?;
+
+        // a fresh profile has no enabledmods.json so everything copied is enabled
+        let copies = self
+            .plan
+            .core
+            .iter()
+            .chain(self.plan.steps.iter().filter_map(|step| step.path.as_ref()))
+            .collect::<BTreeSet<&PathBuf>>();
+        for mod_dir in copies {
+            let dir = mod_dir.file_name().context("mods have a dir name")?;
+            copy_mod(install, mod_dir, &target.mods_dir().join(dir))?;
+        }
+        for plan in &self.installs {
+            plan.install(&target)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn install_step(index: &[Package], server_mod: &ServerMod, replaces: Option<String>) -> Step {
+    let Some(package) = find_package(index, &server_mod.name, server_mod.author.as_deref()) else {
+        return Step::Unavailable("not on thunderstore".to_owned());
+    };
+    let Some(version) = package.version(&server_mod.version) else {
+        return Step::Unavailable(format!(
+            "{} isn't on thunderstore, {} is the latest",
+            server_mod.version,
+            package
+                .versions
+                .first()
+                .map(|version| version.version_number.as_str())
+                .unwrap_or("nothing")
+        ));
+    };
+
+    Step::Install {
+        package: Box::new(package.clone()),
+        version: version.clone(),
+        replaces,
+        needed_by: None,
+    }
+}
+
+/// the old version goes first, northstar would load both if the new one is in another dir
+fn remove_replaced(install: &GameInstall, replaced: &Path) -> AnyResult<()> {
+    let dir = replaced.file_name().context("mods have a dir name")?;
+    // the mods are listed with the canonical mods dir
+    let mod_dir = install.mods_dir().join(dir);
+    install
+        .fs
+        .remove_dir_all(&mod_dir)
+        .with_context(|| format!("couldn't remove {}", mod_dir.display()))?;
+    if let Some(store) = ModStore::of(install) {
+        store.release(install, &mod_dir)?;
+    }
+
+    let record = Record::path(install, &dir.to_string_lossy());
+    if install.fs.exists(&record) {
+        install
+            .fs
+            .remove_file(&record)
+            .with_context(|| format!("couldn't remove {}", record.display()))?;
+    }
+
+    Ok(())
+}
+
+/// copies a folder inside the install, both sides go through its fs
+pub fn copy_dir(install: &GameInstall, from: &Path, to: &Path) -> AnyResult<()> {
+    for entry in install
+        .fs
+        .read_dir(from)
+        .with_context(|| format!("couldn't read {}", from.display()))?
+    {
+        let target = to.join(entry.path.file_name().context("entries have a name")?);
+        if entry.is_dir {
+            copy_dir(install, &entry.path, &target)?;
+            continue;
+        }
+
+        let contents = install
+            .fs
+            .read(&entry.path)
+            .with_context(|| format!("couldn't read {}", entry.path.display()))?;
+        install.write(&target, &contents)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,395 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Ok, Result as AnyResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    install::GameInstall,
+    integrity::sha256,
+    master_server::ServerMod,
+    mod_store::{copy_mod, ModStore},
+    mods_views::{reload_mods, reload_plugins, InstalledModPartial},
+};
+
+/// what exported mod lists end with, the pack dir is searched for them
+pub const LIST_EXTENSION: &str = ".modlist.json";
+
+/// what a profile has, exported so someone else can compare their profile against it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModList {
+    pub profile: String,
+    pub mods: Vec<ListedMod>,
+    pub plugins: Vec<ListedPlugin>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListedMod {
+    pub name: String,
+    pub version: String,
+    pub enabled: bool,
+    pub load_priority: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListedPlugin {
+    pub name: String,
+    pub sha256: String,
+}
+
+/// one side of a diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Side {
+    Profile(String),
+    /// an exported mod list, only the state of its mods can be copied from it
+    List(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiffKind {
+    /// only one side has the mod
+    Mod,
+    Version,
+    Enabled,
+    LoadPriority,
+    Plugin,
+}
+
+/// something the two sides don't agree on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub kind: DiffKind,
+    pub name: String,
+    /// what each side has, none when it doesn't have the mod or plugin
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+impl ModList {
+    pub fn of(install: &GameInstall) -> AnyResult<Self> {
+        let (mods, _) = reload_mods(install)?;
+        // profiles northstar never ran don't have a plugins folder yet
+        let plugins = if install.fs.exists(&install.plugins_dir()) {
+            reload_plugins(install)?
+        } else {
+            Vec::new()
+        };
+        let plugins = plugins
+            .into_iter()
+            .map(|plugin| {
+                let path = install.plugins_dir().join(&plugin.name);
+                let contents = install
+                    .fs
+                    .read(&path)
+                    .with_context(|| format!("couldn't read {}", path.display()))?;
+                Ok(ListedPlugin {
+                    name: plugin.name,
+                    sha256: sha256(&contents),
+                })
+            })
+            .collect::<AnyResult<Vec<ListedPlugin>>>()?;
+
+        Ok(Self {
+            profile: install.profile.clone(),
+            mods: mods
+                .into_iter()
+                .map(|nsmod| ListedMod {
+                    name: nsmod.mod_json.name,
+                    version: nsmod.mod_json.version,
+                    enabled: nsmod.enabled,
+                    load_priority: nsmod.mod_json.load_priority,
+                })
+                .collect(),
+            plugins,
+        })
+    }
+
+    pub fn load(install: &GameInstall, path: &Path) -> AnyResult<Self> {
+        serde_json::from_str(&install.read_to_string(path)?)
+            .with_context(|| format!("{} isn't a mod list", path.display()))
+    }
+
+    /// writes the list of the install's profile into `dir`
+    pub fn export(install: &GameInstall, dir: &Path) -> AnyResult<PathBuf> {
+        let list = Self::of(install)?;
+        let path = dir.join(format!("{}{LIST_EXTENSION}", list.profile));
+        install
+            .fs
+            .create_dir_all(dir)
+            .with_context(|| format!("couldn't create {}", dir.display()))?;
+        install
+            .fs
+            .write(
+                &path,
+                serde_json::to_string_pretty(&list)
+                    .expect("mod lists always serialize")
+                    .as_bytes(),
+            )
+            .with_context(|| format!("couldn't write {}", path.display()))?;
+
+        Ok(path)
+    }
+
+    pub fn find(&self, name: &str) -> Option<&ListedMod> {
+        self.mods.iter().find(|listed| listed.name == name)
+    }
+
+    /// the mod thunderstore has to provide to copy `difference` from this list, none when
+    /// nothing has to be installed
+    pub fn install_for(&self, difference: &Difference) -> Option<ServerMod> {
+        if !matches!(difference.kind, DiffKind::Mod | DiffKind::Version) {
+            return None;
+        }
+
+        self.find(&difference.name).map(|listed| ServerMod {
+            name: listed.name.clone(),
+            version: listed.version.clone(),
+            required_on_client: true,
+            author: None,
+        })
+    }
+
+    /// makes `to` agree with the list, anything that needs files from thunderstore goes through
+    /// [`ModList::install_for`] instead
+    pub fn copy_to(&self, to: &GameInstall, difference: &Difference) -> AnyResult<()> {
+        let listed = self.find(&difference.name);
+        match difference.kind {
+            DiffKind::Enabled => {
+                let listed = listed.context("the mod list doesn't have it")?;
+                to.set_mod_enabled(&listed.name, listed.enabled)
+            }
+            DiffKind::Mod if listed.is_none() => remove_mod(to, &difference.name),
+            DiffKind::Mod | DiffKind::Version => {
+                bail!("{} has to be installed from thunderstore", difference.name)
+            }
+            DiffKind::LoadPriority => {
+                bail!(
+                    "a mod list can't change load priorities, copy the mod from a profile instead"
+                )
+            }
+            DiffKind::Plugin
+                if !self
+                    .plugins
+                    .iter()
+                    .any(|plugin| plugin.name == difference.name) =>
+            {
+                remove_plugin(to, &difference.name)
+            }
+            DiffKind::Plugin => {
+                bail!("a mod list only has the hash of a plugin, copy it from a profile instead")
+            }
+        }
+    }
+}
+
+impl Side {
+    pub fn label(&self) -> String {
+        match self {
+            Side::Profile(profile) => profile.clone(),
+            Side::List(path) => path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn load(&self, install: &GameInstall) -> AnyResult<ModList> {
+        match self {
+            Side::Profile(profile) => ModList::of(&install.with_profile(profile)),
+            Side::List(path) => ModList::load(install, path),
+        }
+    }
+
+    /// the other profiles of the install and the mod lists in `list_dir`
+    pub fn others(install: &GameInstall, list_dir: &Path) -> AnyResult<Vec<Self>> {
+        let lists = install
+            .fs
+            .read_dir(list_dir)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|entry| !entry.is_dir && entry.path.to_string_lossy().ends_with(LIST_EXTENSION))
+            .map(|entry| Side::List(entry.path));
+
+        Ok(install
+            .profiles()?
+            .into_iter()
+            .filter(|profile| *profile != install.profile)
+            .map(Side::Profile)
+            .chain(lists)
+            .collect())
+    }
+}
+
+impl std::fmt::Display for DiffKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            DiffKind::Mod => "mod",
+            DiffKind::Version => "version",
+            DiffKind::Enabled => "enabled",
+            DiffKind::LoadPriority => "load priority",
+            DiffKind::Plugin => "plugin",
+        };
+
+        write!(f, "{display}")
+    }
+}
+
+/// mods by name then plugins, a mod both sides have can differ in several ways at once
+pub fn diff(a: &ModList, b: &ModList) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    let names = a
+        .mods
+        .iter()
+        .chain(&b.mods)
+        .map(|listed| listed.name.as_str())
+        .collect::<BTreeSet<&str>>();
+
+    for name in names {
+        let difference = |kind, a: Option<String>, b: Option<String>| Difference {
+            kind,
+            name: name.to_owned(),
+            a,
+            b,
+        };
+        let (in_a, in_b) = (a.find(name), b.find(name));
+        let (Some(in_a), Some(in_b)) = (in_a, in_b) else {
+            let version = |listed: Option<&ListedMod>| listed.map(|listed| listed.version.clone());
+            differences.push(difference(DiffKind::Mod, version(in_a), version(in_b)));
+            continue;
+        };
+
+        if in_a.version != in_b.version {
+            differences.push(difference(
+                DiffKind::Version,
+                Some(in_a.version.clone()),
+                Some(in_b.version.clone()),
+            ));
+        }
+        if in_a.enabled != in_b.enabled {
+            let state = |enabled| if enabled { "enabled" } else { "disabled" }.to_owned();
+            differences.push(difference(
+                DiffKind::Enabled,
+                Some(state(in_a.enabled)),
+                Some(state(in_b.enabled)),
+            ));
+        }
+        if in_a.load_priority != in_b.load_priority {
+            let priority = |priority: Option<i32>| {
+                priority
+                    .map(|priority| priority.to_string())
+                    .unwrap_or_else(|| "none".to_owned())
+            };
+            differences.push(difference(
+                DiffKind::LoadPriority,
+                Some(priority(in_a.load_priority)),
+                Some(priority(in_b.load_priority)),
+            ));
+        }
+    }
+
+    let plugins = a
+        .plugins
+        .iter()
+        .chain(&b.plugins)
+        .map(|plugin| plugin.name.as_str())
+        .collect::<BTreeSet<&str>>();
+    for name in plugins {
+        let hash = |list: &ModList| {
+            list.plugins
+                .iter()
+                .find(|plugin| plugin.name == name)
+                .map(|plugin| plugin.sha256.clone())
+        };
+        let (in_a, in_b) = (hash(a), hash(b));
+        if in_a != in_b {
+            differences.push(Difference {
+                kind: DiffKind::Plugin,
+                name: name.to_owned(),
+                a: in_a,
+                b: in_b,
+            });
+        }
+    }
+
+    differences
+}
+
+/// makes `to` agree with `from` on `difference`, a mod is copied whole so its version and load
+/// priority come with it
+pub fn copy_between(
+    from: &GameInstall,
+    to: &GameInstall,
+    difference: &Difference,
+) -> AnyResult<()> {
+    let name = &difference.name;
+    if difference.kind == DiffKind::Plugin {
+        let path = from.plugins_dir().join(name);
+        if !from.fs.exists(&path) {
+            return remove_plugin(to, name);
+        }
+        let contents = from
+            .fs
+            .read(&path)
+            .with_context(|| format!("couldn't read {}", path.display()))?;
+        return to.write(&to.plugins_dir().join(name), &contents);
+    }
+
+    let Some(source) = find_mod(from, name)? else {
+        return remove_mod(to, name);
+    };
+    if difference.kind != DiffKind::Enabled {
+        remove_mod(to, name)?;
+        let dir = source.path.file_name().context("mods have a dir name")?;
+        to.fs
+            .create_dir_all(&to.mods_dir())
+            .with_context(|| format!("couldn't create {}", to.mods_dir().display()))?;
+        copy_mod(to, &source.path, &to.mods_dir().join(dir))?;
+    }
+
+    to.set_mod_enabled(name, source.enabled)
+}
+
+fn find_mod(install: &GameInstall, name: &str) -> AnyResult<Option<InstalledModPartial>> {
+    Ok(reload_mods(install)?
+        .0
+        .into_iter()
+        .find(|nsmod| nsmod.mod_json.name == name))
+}
+
+/// a mod the other side doesn't have is removed, whatever dir it is in
+fn remove_mod(install: &GameInstall, name: &str) -> AnyResult<()> {
+    let Some(nsmod) = find_mod(install, name)? else {
+        return Ok(());
+    };
+
+    // the mods view lists them with the canonical mods dir
+    let mod_dir = install
+        .mods_dir()
+        .join(nsmod.path.file_name().context("mods have a dir name")?);
+    install
+        .fs
+        .remove_dir_all(&mod_dir)
+        .with_context(|| format!("couldn't remove {}", mod_dir.display()))?;
+    if let Some(store) = ModStore::of(install) {
+        store.release(install, &mod_dir)?;
+    }
+
+    Ok(())
+}
+
+fn remove_plugin(install: &GameInstall, name: &str) -> AnyResult<()> {
+    let path = install.plugins_dir().join(name);
+    if !install.fs.exists(&path) {
+        return Ok(());
+    }
+
+    install
+        .fs
+        .remove_file(&path)
+        .with_context(|| format!("couldn't remove {}", path.display()))
+}
@@ -0,0 +1,350 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Ok, Result as AnyResult};
+use crossterm::event;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    background::Background,
+    game_fs::RealFs,
+    hooks::ChangeHooks,
+    keymap::{Action, KeyContext, Keymap},
+    master_server::ServerMod,
+    mods_views::reload_mods,
+    prepare::{FetchedPlan, PreparePlan},
+    profile_diff::{copy_between, diff, Difference, ModList, Side},
+    theme::theme,
+    thunderstore::{fetch_index, Package},
+};
+
+/// the current profile against another profile or a mod list, differences can be copied either way
+#[derive(Default)]
+pub struct ProfileDiffView {
+    profile: String,
+    /// where exported mod lists are written and looked for
+    list_dir: PathBuf,
+    others: Vec<Side>,
+    other: usize,
+    differences: Vec<Difference>,
+    selected: usize,
+    /// fetched the first time a mod is copied from a mod list
+    index: Option<Vec<Package>>,
+    fetching_index: Background<Vec<Package>>,
+    /// the copy waiting on the index or on its archives
+    copying: Option<ListCopy>,
+    downloading: Background<FetchedPlan>,
+}
+
+/// a copy from a mod list that has to install from thunderstore
+struct ListCopy {
+    description: String,
+    from: String,
+    list: ModList,
+    difference: Difference,
+    server_mod: ServerMod,
+}
+
+impl ProfileDiffView {
+    fn reload(&mut self, state: &GlobalState) -> AnyResult<()> {
+        let other = self.others.get(self.other).cloned();
+        self.profile = state.install.profile.clone();
+        self.list_dir = state.pack_dir.clone();
+        self.others = Side::others(&state.install, &self.list_dir)?;
+        // the same side stays selected when it's still there
+        self.other = other
+            .and_then(|other| self.others.iter().position(|side| *side == other))
+            .unwrap_or_default();
+
+        self.differences = match self.others.get(self.other) {
+            Some(other) => diff(&ModList::of(&state.install)?, &other.load(&state.install)?),
+            None => Vec::new(),
+        };
+        self.selected = self.selected.min(self.differences.len().saturating_sub(1));
+
+        Ok(())
+    }
+
+    fn export(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        let path = ModList::export(&state.install, &state.pack_dir)?;
+        state
+            .notifier
+            .info(format!("exported {} to {}", self.profile, path.display()));
+
+        self.reload(state)
+    }
+
+    /// from the other side into the current profile or the other way around
+    fn copy(&mut self, state: &mut GlobalState, into_current: bool) -> AnyResult<()> {
+        let (Some(difference), Some(other)) = (
+            self.differences.get(self.selected).cloned(),
+            self.others.get(self.other).cloned(),
+        ) else {
+            return Ok(());
+        };
+
+        let (from, to) = if into_current {
+            (other.label(), self.profile.clone())
+        } else {
+            (self.profile.clone(), other.label())
+        };
+        let description = format!(
+            "copy the {} of {} from {from} to {to}",
+            difference.kind, difference.name
+        );
+
+        let done = match other {
+            Side::Profile(profile) if into_current => state.apply(description, move |install| {
+                copy_between(&install.with_profile(&profile), install, &difference)
+            })?,
+            Side::Profile(profile) => state.apply(description, move |install| {
+                copy_between(install, &install.with_profile(&profile), &difference)
+            })?,
+            Side::List(_) if !into_current => {
+                bail!("{to} is a mod list, export the profile again to change it")
+            }
+            Side::List(path) => {
+                let list = ModList::load(&state.install, &path)?;
+                match list.install_for(&difference) {
+                    Some(server_mod) => {
+                        self.copying = Some(ListCopy {
+                            description,
+                            from,
+                            list,
+                            difference,
+                            server_mod,
+                        });
+                        self.download(state)?;
+                        false
+                    }
+                    None => state.apply(description, move |install| {
+                        list.copy_to(install, &difference)
+                    })?,
+                }
+            }
+        };
+        if done {
+            self.reload(state)?;
+        }
+
+        Ok(())
+    }
+
+    /// downloads the archives of the waiting copy in the background, the index is fetched
+    /// first when it isn't there yet
+    fn download(&mut self, state: &GlobalState) -> AnyResult<()> {
+        let Some(copy) = &self.copying else {
+            return Ok(());
+        };
+        let Some(index) = self.index.as_deref() else {
+            if !self.fetching_index.is_running() {
+                let package_index = state.package_index.clone();
+                self.fetching_index
+                    .start(move || fetch_index(&package_index));
+            }
+            return Ok(());
+        };
+
+        let (installed, _) = reload_mods(&state.install)?;
+        let plan = PreparePlan::new(
+            &copy.from,
+            std::slice::from_ref(&copy.server_mod),
+            &installed,
+            index,
+        );
+        if let Some(step) = plan.unavailable().next() {
+            self.copying = None;
+            bail!("couldn't copy {step}");
+        }
+
+        let (install, package_cache) = (state.install.clone(), state.package_cache.clone());
+        self.downloading
+            .start(move || plan.fetch(&install, &package_cache, &RealFs, false));
+
+        Ok(())
+    }
+
+    fn apply_copy(&mut self, fetched: FetchedPlan, state: &mut GlobalState) -> AnyResult<()> {
+        let Some(copy) = self.copying.take() else {
+            return Ok(());
+        };
+
+        let name = copy.difference.name;
+        let enabled = copy.list.find(&name).is_some_and(|listed| listed.enabled);
+        let hooks = ChangeHooks::install(&state.install, vec![name.clone()]);
+        if state.apply_with(copy.description, hooks, move |install| {
+            fetched.apply(install)?;
+            install.set_mod_enabled(&name, enabled)
+        })? {
+            self.reload(state)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_copying(&self) -> bool {
+        self.fetching_index.is_running() || self.downloading.is_running()
+    }
+}
+
+impl TabUi for ProfileDiffView {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let block = Block::default()
+            .borders(Borders::all())
+            .title_style(Style::default().fg(theme().accent))
+            .bg(theme().background)
+            .fg(theme().text);
+        let Some(other) = self.others.get(self.other) else {
+            frame.render_widget(
+                Paragraph::new(format!(
+                    "there is nothing to compare {} with, add a profile or put a mod list in {}",
+                    self.profile,
+                    self.list_dir.display()
+                ))
+                .wrap(Wrap { trim: false })
+                .fg(theme().muted)
+                .block(block.title("diff")),
+                rect,
+            );
+            return;
+        };
+
+        let block = block.title(format!(
+            "{} and {} ({}/{}){}",
+            self.profile,
+            other.label(),
+            self.other + 1,
+            self.others.len(),
+            if self.fetching_index.is_running() {
+                " - fetching the package index"
+            } else if self.downloading.is_running() {
+                " - downloading"
+            } else {
+                ""
+            }
+        ));
+        if self.differences.is_empty() {
+            frame.render_widget(
+                Paragraph::new("no differences")
+                    .fg(theme().muted)
+                    .block(block),
+                rect,
+            );
+            return;
+        }
+
+        let side = |value: &Option<String>| match value {
+            Some(value) => Cell::from(value.clone()).fg(theme().text),
+            None => Cell::from("missing").fg(theme().muted),
+        };
+        let rows = self
+            .differences
+            .iter()
+            .map(|difference| {
+                Row::new(vec![
+                    Cell::from(difference.kind.to_string()).fg(theme().muted),
+                    Cell::from(difference.name.as_str()).fg(theme().text),
+                    side(&difference.a),
+                    side(&difference.b),
+                ])
+            })
+            .collect::<Vec<Row<'_>>>();
+
+        frame.render_stateful_widget(
+            Table::new(rows)
+                .header(
+                    Row::new(vec![
+                        String::new(),
+                        "name".to_owned(),
+                        self.profile.clone(),
+                        other.label(),
+                    ])
+                    .style(Style::default().fg(theme().accent)),
+                )
+                .widths(&[
+                    Constraint::Length(14),
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(30),
+                    Constraint::Percentage(30),
+                ])
+                .block(block)
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            rect,
+            &mut TableState::default().with_selected(Some(self.selected)),
+        );
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        keymap.hints(KeyContext::Diff)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        let event::Event::Key(key_event) = crosstermevent else {
+            return Ok(());
+        };
+        if key_event.kind != event::KeyEventKind::Press || state.is_typing {
+            return Ok(());
+        }
+
+        match state.keymap.action(KeyContext::Diff, &key_event) {
+            Some(Action::Down) => {
+                self.selected = self
+                    .differences
+                    .len()
+                    .saturating_sub(1)
+                    .min(self.selected + 1)
+            }
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            // one copy from a mod list at a time
+            Some(Action::CopyLeft | Action::CopyRight) if self.is_copying() => {}
+            Some(Action::CopyLeft) => self.copy(state, true)?,
+            Some(Action::CopyRight) => self.copy(state, false)?,
+            Some(Action::NextProfile) if !self.others.is_empty() => {
+                self.other = (self.other + 1) % self.others.len();
+                self.selected = 0;
+                self.reload(state)?;
+            }
+            Some(Action::Export) => self.export(state)?,
+            Some(Action::Reload) => self.reload(state)?,
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.reload(state)
+    }
+
+    fn tick(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        match self.fetching_index.poll() {
+            None => {}
+            Some(Result::Ok(index)) => {
+                self.index = Some(index);
+                self.download(state)?;
+            }
+            Some(Err(err)) => {
+                self.copying = None;
+                return Err(err.context("couldn't fetch the package index"));
+            }
+        }
+        match self.downloading.poll() {
+            None => {}
+            Some(Result::Ok(fetched)) => self.apply_copy(fetched, state)?,
+            Some(Err(err)) => {
+                let copy = self.copying.take();
+                return Err(match copy {
+                    Some(copy) => err.context(format!("couldn't {}", copy.description)),
+                    None => err,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn is_busy(&self) -> bool {
+        self.is_copying()
+    }
+}
@@ -0,0 +1,521 @@
+use anyhow::{Ok, Result as AnyResult};
+use crossterm::event::{self, KeyCode};
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    background::Background,
+    game_fs::RealFs,
+    hooks::ChangeHooks,
+    keymap::{Action, KeyContext, Keymap},
+    master_server::{fetch_servers, ModStatus, Server, ServerMod},
+    mods_views::{reload_mods, InstalledModPartial},
+    prepare::{parse_required_mods, FetchedPlan, PreparePlan, Step, THROWAWAY_PROFILE},
+    theme::theme,
+    thunderstore::{fetch_index, Package},
+};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    Players,
+    Name,
+    Map,
+    Region,
+    /// servers the profile can join right away first
+    Ready,
+}
+
+impl SortBy {
+    fn next(self) -> Self {
+        match self {
+            SortBy::Players => SortBy::Name,
+            SortBy::Name => SortBy::Map,
+            SortBy::Map => SortBy::Region,
+            SortBy::Region => SortBy::Ready,
+            SortBy::Ready => SortBy::Players,
+        }
+    }
+}
+
+impl std::fmt::Display for SortBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortBy::Players => "players",
+            SortBy::Name => "name",
+            SortBy::Map => "map",
+            SortBy::Region => "region",
+            SortBy::Ready => "missing mods",
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct Servers {
+    servers: Vec<Server>,
+    /// the list being fetched from the master server
+    fetching: Background<Vec<Server>>,
+    /// why the last fetch failed, shown until the list is reloaded
+    error: Option<String>,
+    /// the list is only fetched by itself the first time the tab is shown
+    requested: bool,
+    installed: Vec<InstalledModPartial>,
+    /// indices into `servers` after filtering and sorting
+    shown: Vec<usize>,
+    selected: usize,
+    sort_by: SortBy,
+    search: String,
+    searching: bool,
+    /// hides full and password protected servers
+    joinable_only: bool,
+    /// only fetched once something is prepared
+    index: Option<Vec<Package>>,
+    fetching_index: Background<Vec<Package>>,
+    /// the server and its mods waiting on the index to be prepared
+    waiting: Option<(String, Vec<ServerMod>)>,
+    /// the plan being looked at and whether it goes into a throwaway profile
+    prepare: Option<(PreparePlan, bool)>,
+    /// the archives of the confirmed plan
+    downloading: Background<FetchedPlan>,
+}
+
+impl Servers {
+    fn selected_server(&self) -> Option<&Server> {
+        self.shown
+            .get(self.selected)
+            .map(|index| &self.servers[*index])
+    }
+
+    fn missing_mods(&self, server: &Server) -> usize {
+        server
+            .required_mods()
+            .filter(|server_mod| !ModStatus::of(server_mod, &self.installed).is_ready())
+            .count()
+    }
+
+    /// fetches the list again in the background, the old one stays until it's there
+    fn reload(&mut self, state: &GlobalState) {
+        let master_server = state.master_server.clone();
+        self.fetching.start(move || fetch_servers(&master_server));
+        self.requested = true;
+        self.error = None;
+    }
+
+    /// plans right away once the index is there, until then the index is fetched in the
+    /// background
+    fn prepare(&mut self, server: &str, required: &[ServerMod], state: &GlobalState) {
+        let Some(index) = self.index.as_deref() else {
+            self.waiting = Some((server.to_owned(), required.to_vec()));
+            if !self.fetching_index.is_running() {
+                let package_index = state.package_index.clone();
+                self.fetching_index
+                    .start(move || fetch_index(&package_index));
+            }
+            return;
+        };
+
+        self.prepare = Some((
+            PreparePlan::new(server, required, &self.installed, index),
+            false,
+        ));
+    }
+
+    /// downloads the archives of the plan in the background, it stays up until they're there
+    fn download_prepare(&mut self, state: &GlobalState) {
+        let Some((plan, throwaway)) = self.prepare.clone() else {
+            return;
+        };
+
+        let (install, package_cache) = (state.install.clone(), state.package_cache.clone());
+        self.downloading
+            .start(move || plan.fetch(&install, &package_cache, &RealFs, throwaway));
+    }
+
+    fn apply_prepare(&mut self, fetched: FetchedPlan, state: &mut GlobalState) -> AnyResult<()> {
+        self.prepare = None;
+        let (installs, enables) = fetched.plan.counts();
+        let unavailable = fetched.plan.unavailable().count();
+        let throwaway = fetched.throwaway;
+        let mods = fetched
+            .plan
+            .steps
+            .iter()
+            .filter(|step| matches!(step.step, Step::Install { .. }))
+            .map(|step| step.name.clone())
+            .collect::<Vec<String>>();
+
+        let profile = fetched.profile(&state.install).to_owned();
+        let target = state.install.with_profile(&profile);
+        let description = fetched.describe(&state.install);
+        let hooks = if mods.is_empty() {
+            ChangeHooks::default()
+        } else {
+            ChangeHooks::install(&target, mods)
+        };
+        if !state.apply_with(description, hooks, move |install| fetched.apply(install))? {
+            return Ok(());
+        }
+
+        state.notifier.info(format!(
+            "prepared {profile}, installed {installs} and enabled {enables} mods"
+        ));
+        if unavailable > 0 {
+            state.notifier.warn(format!(
+                "{unavailable} required mods couldn't be installed, see the plan for why"
+            ));
+        }
+        if throwaway {
+            state.install = state.install.with_profile(THROWAWAY_PROFILE);
+            state
+                .notifier
+                .info(format!("using the {THROWAWAY_PROFILE} profile"));
+        }
+        self.installed = reload_mods(&state.install)?.0;
+
+        Ok(())
+    }
+
+    fn update_shown(&mut self) {
+        let mut shown = self
+            .servers
+            .iter()
+            .enumerate()
+            .filter(|(_, server)| {
+                (!self.joinable_only || !(server.is_full() || server.has_password))
+                    && (self.search.is_empty() || server.matches(&self.search))
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<usize>>();
+
+        let servers = &self.servers;
+        match self.sort_by {
+            SortBy::Players => {
+                shown.sort_by_key(|index| std::cmp::Reverse(servers[*index].player_count))
+            }
+            SortBy::Name => shown.sort_by_key(|index| servers[*index].name.to_lowercase()),
+            SortBy::Map => shown.sort_by_key(|index| &servers[*index].map),
+            SortBy::Region => shown.sort_by_key(|index| &servers[*index].region),
+            SortBy::Ready => shown.sort_by_cached_key(|index| self.missing_mods(&servers[*index])),
+        }
+
+        self.shown = shown;
+        self.selected = self.selected.min(self.shown.len().saturating_sub(1));
+    }
+}
+
+impl TabUi for Servers {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let layout = Layout::new()
+            .constraints([
+                Constraint::Min(3),
+                Constraint::Length(1),
+                // plans need more room than a server's required mods
+                Constraint::Length(if self.prepare.is_some() { 12 } else { 8 }),
+            ])
+            .split(rect);
+
+        let rows =
+            self.shown
+                .iter()
+                .map(|index| {
+                    let server = &self.servers[*index];
+                    let required = server.required_mods().count();
+                    let missing = self.missing_mods(server);
+                    let mut name = Line::raw(server.name.as_str());
+                    if server.has_password {
+                        name.spans.push(Span::styled(
+                            " password",
+                            Style::default().fg(theme().muted),
+                        ));
+                    }
+
+                    Row::new(vec![
+                        Cell::from(name),
+                        Cell::from(server.map.as_str()),
+                        Cell::from(server.playlist.as_str()),
+                        Cell::from(format!("{}/{}", server.player_count, server.max_players)).fg(
+                            if server.is_full() {
+                                theme().muted
+                            } else {
+                                theme().text
+                            },
+                        ),
+                        Cell::from(server.region.as_str()).fg(theme().muted),
+                        match missing {
+                            0 => Cell::from(required.to_string()),
+                            missing => Cell::from(format!("{required} ({missing} missing)"))
+                                .fg(theme().error),
+                        },
+                    ])
+                    .fg(theme().text)
+                })
+                .collect::<Vec<Row<'_>>>();
+
+        frame.render_stateful_widget(
+            Table::new(rows)
+                .header(
+                    Row::new(vec!["name", "map", "playlist", "players", "region", "mods"])
+                        .style(Style::default().fg(theme().accent)),
+                )
+                .widths(&[
+                    Constraint::Percentage(35),
+                    Constraint::Percentage(17),
+                    Constraint::Percentage(12),
+                    Constraint::Percentage(8),
+                    Constraint::Percentage(12),
+                    Constraint::Percentage(16),
+                ])
+                .block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(match (&self.error, self.fetching.is_running()) {
+                            (_, true) => "servers - loading".to_owned(),
+                            (Some(_), false) => "servers - couldn't load them".to_owned(),
+                            (None, false) => format!(
+                                "servers ({}/{}) - sorted by {}",
+                                self.shown.len(),
+                                self.servers.len(),
+                                self.sort_by
+                            ),
+                        })
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background),
+                )
+                .highlight_style(Style::default().bg(theme().surface).fg(theme().selected)),
+            layout[0],
+            &mut TableState::default().with_selected(Some(self.selected)),
+        );
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::raw(format!("joinable only: {} ", self.joinable_only)),
+                Span::raw("search: "),
+                Span::styled(
+                    format!("{}{}", self.search, if self.searching { "_" } else { "" }),
+                    Style::default().fg(theme().accent),
+                ),
+            ]))
+            .bg(theme().surface)
+            .fg(theme().text),
+            layout[1],
+        );
+
+        if let Some((plan, throwaway)) = &self.prepare {
+            let lines = plan
+                .steps
+                .iter()
+                .map(|step| {
+                    let color = match step.step {
+                        Step::Ready => theme().enabled,
+                        Step::Enable | Step::Install { .. } => theme().accent,
+                        Step::Unavailable(_) => theme().error,
+                    };
+                    Line::styled(step.to_string(), Style::default().fg(color))
+                })
+                .collect::<Vec<Line<'_>>>();
+            frame.render_widget(Clear, layout[2]);
+            frame.render_widget(
+                Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+                    Block::default()
+                        .borders(Borders::all())
+                        .title(format!(
+                            "prepare for {} - into {}{}",
+                            plan.server,
+                            if *throwaway {
+                                THROWAWAY_PROFILE
+                            } else {
+                                "the current profile"
+                            },
+                            if self.downloading.is_running() {
+                                " - downloading"
+                            } else {
+                                ""
+                            }
+                        ))
+                        .title_style(Style::default().fg(theme().accent))
+                        .bg(theme().background)
+                        .fg(theme().text),
+                ),
+                layout[2],
+            );
+            return;
+        }
+
+        let details = match self.selected_server() {
+            _ if self.waiting.is_some() => Text::raw("fetching the package index to prepare"),
+            Some(server) => {
+                let mut lines = vec![Line::raw(server.description.as_str())];
+                lines.extend(server.required_mods().map(|server_mod| {
+                    let (status, color) = match ModStatus::of(server_mod, &self.installed) {
+                        ModStatus::Installed => ("installed".to_owned(), theme().enabled),
+                        ModStatus::Disabled => ("disabled".to_owned(), theme().warn),
+                        ModStatus::OtherVersion(version) => {
+                            (format!("{version} is installed"), theme().error)
+                        }
+                        ModStatus::Missing => ("missing".to_owned(), theme().error),
+                    };
+                    Line::from(vec![
+                        Span::raw(format!("{} {} ", server_mod.name, server_mod.version)),
+                        Span::styled(status, Style::default().fg(color)),
+                    ])
+                }));
+                Text::from(lines)
+            }
+            None => Text::raw(match &self.error {
+                Some(error) => error.as_str(),
+                None if self.fetching.is_running() => "loading the servers",
+                None => "no servers",
+            }),
+        };
+        frame.render_widget(
+            Paragraph::new(details).wrap(Wrap { trim: true }).block(
+                Block::default()
+                    .borders(Borders::all())
+                    .title("required mods")
+                    .bg(theme().background)
+                    .fg(theme().text),
+            ),
+            layout[2],
+        );
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        if self.prepare.is_some() {
+            return keymap.hints(KeyContext::Prepare);
+        }
+        if self.searching {
+            return vec!["done - enter/esc".to_owned()];
+        }
+
+        keymap.hints(KeyContext::Servers)
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        let event::Event::Key(key_event) = crosstermevent else {
+            return Ok(());
+        };
+        if key_event.kind != event::KeyEventKind::Press {
+            return Ok(());
+        }
+
+        if self.searching {
+            match key_event.code {
+                KeyCode::Char(c) => self.search.push(c),
+                KeyCode::Backspace => _ = self.search.pop(),
+                KeyCode::Enter | KeyCode::Esc => {
+                    self.searching = false;
+                    state.is_typing = false;
+                }
+                _ => {}
+            }
+            self.update_shown();
+
+            return Ok(());
+        }
+
+        if state.is_typing {
+            return Ok(());
+        }
+
+        if let Some((_, throwaway)) = self.prepare.as_mut() {
+            match state.keymap.action(KeyContext::Prepare, &key_event) {
+                _ if self.downloading.is_running() => {}
+                Some(Action::Confirm) => self.download_prepare(state),
+                Some(Action::ToggleThrowaway) => *throwaway = !*throwaway,
+                Some(Action::Close) => self.prepare = None,
+                _ => {}
+            }
+
+            return Ok(());
+        }
+
+        match state.keymap.action(KeyContext::Servers, &key_event) {
+            Some(Action::Down) => {
+                self.selected = self.shown.len().saturating_sub(1).min(self.selected + 1)
+            }
+            Some(Action::Up) => self.selected = self.selected.saturating_sub(1),
+            Some(Action::Sort) => {
+                self.sort_by = self.sort_by.next();
+                self.update_shown();
+            }
+            Some(Action::ToggleJoinable) => {
+                self.joinable_only = !self.joinable_only;
+                self.update_shown();
+            }
+            Some(Action::Search) => {
+                self.searching = true;
+                state.is_typing = true;
+                self.search.clear();
+                self.update_shown();
+            }
+            Some(Action::Reload) => self.reload(state),
+            Some(Action::Prepare) => {
+                if let Some(server) = self.selected_server().cloned() {
+                    let required = server.required_mods().cloned().collect::<Vec<ServerMod>>();
+                    self.prepare(&server.name, &required, state);
+                }
+            }
+            Some(Action::Paste) => {
+                let required = parse_required_mods(&arboard::Clipboard::new()?.get_text()?)?;
+                self.prepare("the pasted mods", &required, state);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.installed = reload_mods(&state.install)?.0;
+        if !self.requested {
+            self.reload(state);
+        }
+        self.update_shown();
+
+        Ok(())
+    }
+
+    fn tick(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        match self.fetching_index.poll() {
+            None => {}
+            Some(Result::Ok(index)) => {
+                self.index = Some(index);
+                if let Some((server, required)) = self.waiting.take() {
+                    self.prepare(&server, &required, state);
+                }
+            }
+            Some(Err(err)) => {
+                self.waiting = None;
+                return Err(err.context("couldn't fetch the package index"));
+            }
+        }
+        match self.downloading.poll() {
+            None => {}
+            Some(Result::Ok(fetched)) => self.apply_prepare(fetched, state)?,
+            // the plan stays up so it can be tried again
+            Some(Err(err)) => return Err(err),
+        }
+
+        match self.fetching.poll() {
+            None => {}
+            Some(Result::Ok(servers)) => {
+                self.servers = servers;
+                self.index = None;
+                self.update_shown();
+            }
+            Some(Err(err)) => {
+                self.error = Some(format!("couldn't load the servers: {err:#}"));
+                return Err(err.context("couldn't load the servers"));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn is_busy(&self) -> bool {
+        self.fetching.is_running()
+            || self.fetching_index.is_running()
+            || self.downloading.is_running()
+    }
+}
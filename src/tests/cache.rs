@@ -0,0 +1,186 @@
+//! the package cache in `MemoryFs`, downloads come from a temp dir since urls can be paths
+
+use std::{fs, path::Path};
+
+use crossterm::event::KeyCode;
+use tempfile::TempDir;
+
+use super::{GameDir, TestApp, HEIGHT, WIDTH};
+use crate::{
+    config::Config,
+    game_fs::{GameFs, MemoryFs, RealFs},
+    integrity::sha256,
+    package_cache::PackageCache,
+    thunderstore::{Package, PackageVersion},
+};
+
+const CACHE: &str = "/home/pilot/.cache/drydoct/packages";
+
+fn keys(cache: &PackageCache, fs: &MemoryFs) -> Vec<String> {
+    cache
+        .entries(fs)
+        .into_iter()
+        .map(|entry| entry.key)
+        .collect()
+}
+
+/// a package whose archive is downloaded from `dir`
+fn package(dir: &Path, contents: &[u8]) -> (Package, PackageVersion) {
+    let url = dir.join("Grapple-1.0.0.zip");
+    fs::write(&url, contents).unwrap();
+    let version = PackageVersion {
+        version_number: "1.0.0".to_owned(),
+        download_url: url.display().to_string(),
+        ..PackageVersion::default()
+    };
+    let package = Package {
+        name: "Grapple".to_owned(),
+        owner: "pilot".to_owned(),
+        versions: vec![version.clone()],
+        ..Package::default()
+    };
+
+    (package, version)
+}
+
+#[test]
+fn archives_are_stored_once_by_their_hash() {
+    let fs = MemoryFs::default();
+    let cache = PackageCache::new(CACHE);
+
+    let first = cache.put(&fs, "pilot-Grapple-1.0.0", b"zip").unwrap();
+    let second = cache.put(&fs, "mirror-Grapple-1.0.0", b"zip").unwrap();
+    assert_eq!(first, second);
+    assert_eq!(
+        first,
+        Path::new(CACHE).join(format!("{}.zip", sha256(b"zip")))
+    );
+
+    cache.remove(&fs, "pilot-Grapple-1.0.0").unwrap();
+    assert!(fs.exists(&second), "the other key still uses it");
+    cache.remove(&fs, "mirror-Grapple-1.0.0").unwrap();
+    assert!(!fs.exists(&second));
+    assert!(keys(&cache, &fs).is_empty());
+}
+
+#[test]
+fn the_least_recently_used_archives_are_evicted() {
+    let fs = MemoryFs::default();
+    let cache = PackageCache {
+        limit: 25,
+        ..PackageCache::new(CACHE)
+    };
+
+    cache.put(&fs, "a", &[1; 10]).unwrap();
+    cache.put(&fs, "b", &[2; 10]).unwrap();
+    assert!(cache.get(&fs, "a").unwrap().is_some());
+    cache.put(&fs, "c", &[3; 10]).unwrap();
+    assert_eq!(keys(&cache, &fs), ["a", "c"]);
+
+    // an archive bigger than the limit is still kept until the next one comes along
+    cache.put(&fs, "huge", &[4; 30]).unwrap();
+    assert_eq!(keys(&cache, &fs), ["huge"]);
+    assert_eq!(fs.read_dir(Path::new(CACHE)).unwrap().len(), 2);
+}
+
+#[test]
+fn corrupt_archives_are_downloaded_again() {
+    let downloads = TempDir::new().unwrap();
+    let (package, version) = package(downloads.path(), b"the real zip");
+    let fs = MemoryFs::default();
+    let cache = PackageCache::new(CACHE);
+
+    let path = cache.fetch(&fs, &package, &version).unwrap().0.unwrap();
+    fs.write(&path, b"half a zip").unwrap();
+    assert_eq!(cache.peek(&fs, "pilot-Grapple-1.0.0"), None);
+
+    let (again, bytes) = cache.fetch(&fs, &package, &version).unwrap();
+    assert_eq!(bytes, b"the real zip");
+    assert_eq!(again, Some(path.clone()));
+    assert_eq!(fs.read(&path).unwrap(), b"the real zip");
+}
+
+#[test]
+fn offline_installs_only_come_from_the_cache() {
+    let downloads = TempDir::new().unwrap();
+    let (package, version) = package(downloads.path(), b"zip");
+    let fs = MemoryFs::default();
+    let cache = PackageCache {
+        offline: true,
+        ..PackageCache::new(CACHE)
+    };
+
+    assert_eq!(
+        cache
+            .fetch(&fs, &package, &version)
+            .unwrap_err()
+            .to_string(),
+        "pilot-Grapple-1.0.0 isn't in the package cache and drydoct is offline"
+    );
+
+    PackageCache::new(CACHE)
+        .fetch(&fs, &package, &version)
+        .unwrap();
+    fs::remove_file(downloads.path().join("Grapple-1.0.0.zip")).unwrap();
+    assert_eq!(cache.fetch(&fs, &package, &version).unwrap().1, b"zip");
+}
+
+#[test]
+fn the_cache_is_listed_and_cleaned_from_utils() {
+    let game = GameDir::new();
+    let dir = game.root.join("cache");
+    fs::create_dir_all(&dir).unwrap();
+    // written by hand so the last used dates stay the same
+    let mut entries = Vec::new();
+    for (key, contents, last_used) in [
+        ("pilot-Grapple-1.0.0", "grapple".repeat(300), 1_700_000_000),
+        (
+            "someone-Holo_Pilots-2.0.0",
+            "holo".repeat(100),
+            1_710_000_000,
+        ),
+        ("other-Broken-1.0.0", "broken".to_owned(), 1_705_000_000),
+    ] {
+        let hash = sha256(contents.as_bytes());
+        let written = if key.contains("Broken") {
+            "changed"
+        } else {
+            &contents
+        };
+        fs::write(dir.join(format!("{hash}.zip")), written).unwrap();
+        entries.push(format!(
+            r#"{{ "key": "{key}", "sha256": "{hash}", "size": {}, "last_used": {last_used} }}"#,
+            contents.len()
+        ));
+    }
+    fs::write(
+        dir.join("index.json"),
+        format!(r#"{{ "entries": [{}] }}"#, entries.join(",")),
+    )
+    .unwrap();
+    fs::write(dir.join("leftover.zip"), "stray").unwrap();
+    let config = Config {
+        package_cache: Some(dir.clone()),
+        package_cache_limit: Some(1),
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    test_app.keys("3").repeat(KeyCode::Tab, 4);
+    insta::assert_snapshot!("cache", test_app.screen());
+
+    let screen = test_app.keys("c").screen();
+    assert!(
+        screen.contains("removed 1 archives from the package cache, 6 B freed"),
+        "{screen}"
+    );
+    assert!(!dir.join("leftover.zip").exists());
+
+    let screen = test_app.keys("d").screen();
+    assert!(!screen.contains("Holo_Pilots"), "{screen}");
+    assert_eq!(PackageCache::new(dir).entries(&RealFs).len(), 1);
+
+    // the cache isn't part of the game dir, there is nothing to undo
+    let screen = test_app.key(KeyCode::F(4)).screen();
+    assert!(screen.contains("journal (0)"), "{screen}");
+}
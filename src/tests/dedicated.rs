@@ -0,0 +1,175 @@
+//! a dedicated profile next to the client one and a shell script standing in for the server
+
+use std::{
+    path::Path,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::KeyCode;
+
+use super::{install, mod_json, GameDir, TestApp, HEIGHT, ROOT, WIDTH};
+use crate::{
+    autoexec::AutoExec,
+    config::Config,
+    dedicated::{DedicatedServer, ModState, ProcessStatus, DEDICATED_PROFILE},
+    game_fs::{GameFs, MemoryFs},
+    install::GameInstall,
+};
+
+fn server(command: &[&str]) -> (Arc<MemoryFs>, GameInstall, DedicatedServer) {
+    let (fs, client) = install(
+        MemoryFs::default()
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Northstar.CustomServers/mod.json"),
+                mod_json("Northstar.CustomServers", "1.0.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Grapple/mod.json"),
+                mod_json("Grapple", "1.0.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Skins/mod.json"),
+                mod_json("Skins", "1.0.0"),
+            ),
+    );
+    let server = DedicatedServer {
+        install: client.with_profile(DEDICATED_PROFILE),
+        command: command.iter().map(|arg| (*arg).to_owned()).collect(),
+    };
+
+    (fs, client, server)
+}
+
+#[test]
+fn enabling_a_client_mod_copies_it_into_the_dedicated_profile() {
+    let (fs, client, server) = server(&[]);
+    let states = |server: &DedicatedServer| {
+        server
+            .mod_set(&client)
+            .unwrap()
+            .into_iter()
+            .map(|nsmod| (nsmod.name, nsmod.state))
+            .collect::<Vec<(String, ModState)>>()
+    };
+
+    assert!(states(&server)
+        .iter()
+        .all(|(_, state)| *state == ModState::NotCopied));
+
+    server.set_enabled(&client, "Grapple", true).unwrap();
+    server.set_enabled(&client, "Skins", false).unwrap();
+    assert_eq!(
+        states(&server),
+        [
+            ("Grapple".to_owned(), ModState::Enabled),
+            ("Northstar.CustomServers".to_owned(), ModState::Enabled),
+            ("Skins".to_owned(), ModState::NotCopied),
+        ]
+    );
+
+    server.set_enabled(&client, "Grapple", false).unwrap();
+    assert_eq!(states(&server)[0].1, ModState::Disabled);
+    assert!(fs.exists(Path::new(&format!(
+        "{ROOT}/{DEDICATED_PROFILE}/mods/Grapple/mod.json"
+    ))));
+    assert!(client.enabled_mods().unwrap().is_enabled("Grapple"));
+}
+
+#[test]
+fn launch_args_and_the_autoexec_are_edited_in_place() {
+    let (fs, _, server) = server(&[]);
+    assert_eq!(server.args().unwrap(), "");
+
+    server.set_args(" +setplaylist tdm \n").unwrap();
+    let mut autoexec = AutoExec::parse(
+        server.autoexec_path(),
+        "ns_server_name \"old\"\n// keep me\n",
+    );
+    autoexec.set_line(0, "ns_server_name \"new\"");
+    autoexec.insert_line(2, "ns_server_password \"\"");
+    assert_eq!(autoexec.remove_line(5), None);
+    autoexec.save(&server.install).unwrap();
+
+    assert_eq!(server.args().unwrap(), "+setplaylist tdm");
+    assert_eq!(
+        fs.read_to_string(&server.autoexec_path()).unwrap(),
+        "ns_server_name \"new\"\n// keep me\nns_server_password \"\"\n"
+    );
+    assert!(server
+        .autoexec_path()
+        .starts_with(format!("{ROOT}/{DEDICATED_PROFILE}")));
+}
+
+#[cfg(unix)]
+#[test]
+fn the_process_is_tailed_until_it_exits() {
+    let (_, _, mut server) = server(&["sh", "-c", "echo started $@; exit 3", "stub"]);
+    server.install.root = std::env::temp_dir();
+
+    let process = server.start().unwrap();
+    let start = Instant::now();
+    while process.status() == ProcessStatus::Running || process.console(10).is_empty() {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "the stub never exited"
+        );
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    assert_eq!(process.status(), ProcessStatus::Exited(Some(3)));
+    assert_eq!(
+        process.console(10),
+        [format!("started -dedicated -profile={DEDICATED_PROFILE}")]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn a_dedicated_server_is_set_up_and_run_from_utils() {
+    let game = GameDir::new()
+        .with_mod("Northstar.CustomServers", "1.19.0", "")
+        .with_mod("Grapple", "1.0.0", "");
+    let config = Config {
+        dedicated_command: Some(
+            [
+                "sh",
+                "-c",
+                "echo args $@; cat ns_startup_args_dedi.txt; echo; echo ready; exec sleep 30",
+                "stub",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+        ),
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    test_app
+        .keys("3")
+        .repeat(KeyCode::Tab, 3)
+        .keys(" l")
+        .key(KeyCode::Enter)
+        .keys("+setplaylist tdm")
+        .key(KeyCode::Enter)
+        .keys("a")
+        .keys("ns_server_name \"stub\"")
+        .key(KeyCode::Enter);
+    insta::assert_snapshot!("dedicated", test_app.screen());
+    assert!(test_app
+        .game_path(&format!(
+            "{DEDICATED_PROFILE}/mods/Northstar.CustomServers/mod.json"
+        ))
+        .exists());
+
+    let screen = test_app.keys("s").wait_for("ready").screen();
+    assert!(screen.contains("console - running"), "{screen}");
+    assert!(
+        screen.contains(&format!("args -dedicated -profile={DEDICATED_PROFILE}")),
+        "{screen}"
+    );
+    assert!(screen.contains("│+setplaylist tdm"), "{screen}");
+    let screen = test_app.keys("s").wait_for("console - stopped").screen();
+    assert!(screen.contains("stopped the dedicated server"), "{screen}");
+}
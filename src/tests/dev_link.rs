@@ -0,0 +1,156 @@
+//! dev links copied over `MemoryFs` and linked for real in the game dir fixture
+
+use std::{fs, path::Path, sync::Arc, thread, time::Duration};
+
+use crossterm::event::KeyCode;
+
+use super::{install, mod_json, GameDir, TestApp, HEIGHT, ROOT, WIDTH};
+use crate::{
+    dev_link::{self, DevWatcher, LinkKind, MARKER_NAME},
+    game_fs::{GameFs, MemoryFs},
+    install::GameInstall,
+    journal::Journal,
+    mods_views::reload_mods,
+};
+
+const SOURCE: &str = "/home/pilot/src/Grapple";
+
+fn grapple() -> (Arc<MemoryFs>, GameInstall) {
+    install(
+        MemoryFs::default()
+            .with_file(format!("{SOURCE}/mod.json"), mod_json("Grapple", "1.0.0"))
+            .with_file(format!("{SOURCE}/mod/scripts/grapple.nut"), "x")
+            .with_file(format!("{SOURCE}/.git/HEAD"), "ref: refs/heads/main"),
+    )
+}
+
+fn installed(relative: &str) -> std::path::PathBuf {
+    Path::new(ROOT)
+        .join("R2Northstar/mods/Grapple")
+        .join(relative)
+}
+
+#[test]
+fn links_fall_back_to_a_marked_copy() {
+    let (fs, install) = grapple();
+
+    let kind = dev_link::link(&install, Path::new(SOURCE), "Grapple").unwrap();
+
+    assert_eq!(kind, LinkKind::Copy);
+    assert!(fs.exists(&installed("mod/scripts/grapple.nut")));
+    assert!(!fs.exists(&installed(".git/HEAD")));
+    assert_eq!(fs.read_to_string(&installed(MARKER_NAME)).unwrap(), SOURCE);
+
+    let (mods, _) = reload_mods(&install).unwrap();
+    assert_eq!(mods[0].dev_source.as_deref(), Some(Path::new(SOURCE)));
+}
+
+#[test]
+fn changes_are_validated_and_copied_again_through_the_journal() {
+    let (fs, install) = grapple();
+    dev_link::link(&install, Path::new(SOURCE), "Grapple").unwrap();
+    let mut watcher = DevWatcher::default();
+    watcher.watch(&install);
+    let source = |relative: &str| Path::new(SOURCE).join(relative);
+
+    assert!(
+        watcher.reload(&install, &[source(".git/HEAD")]).is_empty(),
+        "ignored dirs aren't a change"
+    );
+
+    fs.write(
+        &source("mod.json"),
+        mod_json("Grapple", "10.0.0").as_bytes(),
+    )
+    .unwrap();
+    let reloads = watcher.reload(&install, &[source("mod.json")]);
+    assert_eq!(reloads.len(), 1);
+    assert_eq!(reloads[0].result.as_ref().unwrap().version, "10.0.0");
+    let mut journal = Journal::default();
+    journal
+        .run(&install, "sync Grapple", |install| reloads[0].sync(install))
+        .unwrap();
+    assert_eq!(
+        fs.read_to_string(&installed("mod.json")).unwrap(),
+        mod_json("Grapple", "10.0.0")
+    );
+
+    // a sync is undone like anything else
+    journal.undo(&install, 1).unwrap();
+    assert_eq!(
+        fs.read_to_string(&installed("mod.json")).unwrap(),
+        mod_json("Grapple", "1.0.0")
+    );
+
+    fs.write(&source("mod.json"), b"{ broken").unwrap();
+    let reloads = watcher.reload(&install, &[source("mod.json")]);
+    assert_eq!(reloads[0].dir, "Grapple");
+    assert!(format!("{:#}", reloads[0].result.as_ref().unwrap_err())
+        .starts_with("Grapple/mod.json isn't a valid mod.json"));
+}
+
+#[test]
+fn links_refuse_broken_and_existing_mods() {
+    let (fs, install) = grapple();
+    dev_link::link(&install, Path::new(SOURCE), "Grapple").unwrap();
+
+    assert_eq!(
+        dev_link::link(&install, Path::new(SOURCE), "Grapple")
+            .unwrap_err()
+            .to_string(),
+        "Grapple is already installed in R2Northstar"
+    );
+
+    fs.write(&Path::new(SOURCE).join("mod.json"), b"{ broken")
+        .unwrap();
+    assert!(dev_link::link(&install, Path::new(SOURCE), "Other").is_err());
+    assert!(!fs.exists(&Path::new(ROOT).join("R2Northstar/mods/Other")));
+}
+
+#[test]
+fn copied_links_are_undone_through_the_journal() {
+    let (fs, install) = grapple();
+    let mut journal = Journal::default();
+
+    journal
+        .run(&install, "link Grapple", |install| {
+            dev_link::link(install, Path::new(SOURCE), "Grapple").map(|_| ())
+        })
+        .unwrap();
+    journal.undo(&install, 1).unwrap();
+
+    assert!(!fs.exists(&installed("")));
+    assert!(fs.exists(&Path::new(SOURCE).join("mod.json")));
+}
+
+#[cfg(unix)]
+#[test]
+fn linked_mods_are_marked_and_reloaded() {
+    let game = GameDir::new()
+        .with_mod("Anchor", "1.0.0", "already there")
+        .with_file("dev/Grapple/mod.json", &mod_json("Grapple", "1.0.0"));
+    let source = game.root.join("dev/Grapple");
+    let mut test_app = TestApp::new(game);
+
+    test_app
+        .keys("2L")
+        .keys(&source.display().to_string())
+        .key(KeyCode::Enter);
+
+    insta::assert_snapshot!("dev_link", test_app.screen());
+    let link = test_app.game_path("R2Northstar/mods/Grapple");
+    assert!(link.is_symlink());
+
+    // the new link shows up in the mods dir and is watched from the next frame on
+    thread::sleep(Duration::from_millis(100));
+    test_app.resize(WIDTH, HEIGHT);
+    fs::write(source.join("mod.json"), mod_json("Grapple", "10.0.0")).unwrap();
+    let screen = test_app
+        .wait_for("reloaded Grapple from its dev link")
+        .screen();
+    assert!(screen.contains("10.0.0"), "{screen}");
+
+    test_app.key(KeyCode::F(4)).keys("u");
+    assert!(!link.exists());
+    assert!(source.join("mod.json").exists());
+}
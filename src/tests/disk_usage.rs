@@ -0,0 +1,51 @@
+//! sizes of mods, plugins and whole profiles, hard links between profiles counted once
+
+use std::sync::Arc;
+
+use super::{GameDir, TestApp, HEIGHT, WIDTH};
+use crate::{disk_usage::measure, game_fs::RealFs, install::GameInstall};
+
+fn game() -> GameDir {
+    GameDir::new()
+        .with_mod("Grapple", "1.0.0", "a grapple")
+        .with_file("R2Northstar/mods/Grapple/rope.bin", &"r".repeat(1000))
+        .with_file("R2Northstar/mods/readme.txt", "not a mod")
+        .with_file("R2Northstar/logs/nslog.txt", &"l".repeat(100))
+        .with_plugin("Discord.dll")
+}
+
+#[cfg(unix)]
+#[test]
+fn hard_links_are_counted_once() {
+    let game = game().with_file("R2Vanilla/mods/Grapple/mod.json", "{}");
+    std::fs::hard_link(
+        game.root.join("R2Northstar/mods/Grapple/rope.bin"),
+        game.root.join("R2Vanilla/mods/Grapple/rope.bin"),
+    )
+    .unwrap();
+    let install = GameInstall::new(game.root.clone(), Arc::new(RealFs));
+
+    let report = measure(&install).unwrap();
+    let northstar = report.profile("R2Northstar").unwrap();
+    let vanilla = report.profile("R2Vanilla").unwrap();
+
+    let grapple = &northstar.mods[0];
+    assert_eq!(grapple.name, "Grapple");
+    assert_eq!(grapple.shared, 1000);
+    assert!(grapple.size > 1000, "{grapple:?}");
+    assert_eq!(northstar.plugins[0].size, 4);
+    // the loose file in mods and the log
+    assert_eq!(northstar.other, "not a mod".len() as u64 + 100);
+    assert_eq!(vanilla.mods[0].size, 1000 + 2);
+    assert_eq!(vanilla.mods[0].shared, 1000);
+    assert_eq!(report.total, northstar.total + 2);
+    assert_eq!(report.saved(), 1000);
+}
+
+#[test]
+fn the_disk_section_charts_the_current_profile() {
+    let mut test_app = TestApp::with_size(game(), WIDTH, HEIGHT);
+
+    test_app.keys("3").repeat(crossterm::event::KeyCode::Tab, 5);
+    insta::assert_snapshot!("disk_usage", test_app.screen());
+}
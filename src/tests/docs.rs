@@ -0,0 +1,176 @@
+//! markdown in the terminal and the changelog entries an update brings
+
+use crossterm::event::KeyCode;
+use ratatui::{style::Modifier, text::Line};
+
+use super::{zip, GameDir, TestApp, HEIGHT, WIDTH};
+use crate::{
+    config::Config, docs::changes_between, game_fs::RealFs, markdown, package_cache::PackageCache,
+};
+
+const CHANGELOG: &str = "\
+# Changelog
+
+## v1.2.0
+- longer rope
+
+### Fixes
+- no more falling through floors
+
+## v1.1.0
+- faster reel in
+
+```
+## 0.9.0 in a code block isn't a version
+```
+
+## 1.0.0
+- first release
+";
+
+fn text(line: &Line<'_>) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+#[test]
+fn markdown_is_styled_line_by_line() {
+    let lines = markdown::render(
+        "# Grapple\n\nA *better* grapple, see [the wiki](https://wiki.example) or <https://example.com>.\n\n\
+         1. install it\n2. grapple\n   - everywhere\n\n```rust\nlet rope = 2;\n```\n\n> careful\n",
+    );
+
+    assert_eq!(
+        lines.iter().map(text).collect::<Vec<String>>(),
+        [
+            "Grapple",
+            "",
+            "A better grapple, see the wiki (https://wiki.example) or https://example.com.",
+            "",
+            "1. install it",
+            "2. grapple",
+            "  • everywhere",
+            "",
+            "rust",
+            "  let rope = 2;",
+            "",
+            "│ careful",
+        ]
+    );
+    let heading = &lines[0].spans[0].style;
+    assert!(heading
+        .add_modifier
+        .contains(Modifier::BOLD | Modifier::UNDERLINED));
+    let emphasis = lines[2]
+        .spans
+        .iter()
+        .find(|span| span.content == "better")
+        .unwrap();
+    assert!(emphasis.style.add_modifier.contains(Modifier::ITALIC));
+}
+
+#[test]
+fn only_the_entries_after_the_installed_version_are_changes() {
+    assert_eq!(
+        changes_between(CHANGELOG, "1.0.0", "1.2.0").unwrap(),
+        "## v1.2.0\n- longer rope\n\n### Fixes\n- no more falling through floors\n\n\
+         ## v1.1.0\n- faster reel in\n\n```\n## 0.9.0 in a code block isn't a version\n```"
+    );
+    assert_eq!(
+        changes_between(CHANGELOG, "1.1", "1.2.0").unwrap(),
+        "## v1.2.0\n- longer rope\n\n### Fixes\n- no more falling through floors"
+    );
+    assert_eq!(changes_between(CHANGELOG, "1.2.0", "1.2.0").unwrap(), "");
+    assert_eq!(
+        changes_between("# Changelog\n- things", "1.0.0", "1.2.0"),
+        None
+    );
+}
+
+/// grapple 1.0.0 is installed and the index has 1.2.0
+fn grapple_update() -> (GameDir, Config) {
+    let game = GameDir::new()
+        .with_mod("Grapple", "1.0.0", "a grapple")
+        .with_file(
+            "R2Northstar/mods/Grapple/readme.md",
+            "# Grapple\n\nthe installed readme",
+        );
+    let download = game.root.join("downloads").join("pilot-Grapple-1.2.0.zip");
+    std::fs::create_dir_all(download.parent().unwrap()).unwrap();
+    std::fs::write(
+        &download,
+        zip(&[
+            ("README.md", "# Grapple\n\nthe new readme"),
+            ("CHANGELOG.md", CHANGELOG),
+        ]),
+    )
+    .unwrap();
+    let packages = format!(
+        r#"[{{ "name": "Grapple", "owner": "pilot", "versions": [
+            {{ "version_number": "1.2.0", "download_url": "{}" }},
+            {{ "version_number": "1.0.0", "download_url": "" }}
+        ] }}]"#,
+        download.display().to_string().replace('\\', "/")
+    );
+    let game = game.with_file("packages.json", &packages);
+    let config = Config {
+        package_cache: Some(game.root.join("cache")),
+        ..Config::default()
+    };
+
+    (game, config)
+}
+
+#[test]
+fn an_update_shows_its_changes_first() {
+    let (game, config) = grapple_update();
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    test_app.keys("2d");
+    insta::assert_snapshot!("docs_changes", test_app.screen());
+
+    let screen = test_app.key(KeyCode::Tab).screen();
+    assert!(screen.contains("the installed readme"), "{screen}");
+    let screen = test_app.key(KeyCode::Tab).screen();
+    assert!(screen.contains("first release"), "{screen}");
+
+    // opening the package shows the same update from the packages view
+    let screen = test_app
+        .keys("d")
+        .repeat(KeyCode::Tab, 2)
+        .keys("d")
+        .screen();
+    assert!(screen.contains("changes 1.0.0 → 1.2.0"), "{screen}");
+    assert!(screen.contains("longer rope"), "{screen}");
+}
+
+#[test]
+fn the_newest_archive_is_cached_on_a_dry_run_too() {
+    let (game, config) = grapple_update();
+    let cache = PackageCache::new(game.root.join("cache"));
+    let config = Config {
+        dry_run: true,
+        ..config
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    let screen = test_app.keys("2d").screen();
+    assert!(screen.contains("changes 1.0.0 → 1.2.0"), "{screen}");
+    assert!(!screen.contains("would"), "{screen}");
+    assert_eq!(cache.entries(&RealFs)[0].key, "pilot-Grapple-1.2.0");
+}
+
+#[test]
+fn esc_closes_the_docs_instead_of_quitting() {
+    let (game, config) = grapple_update();
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    let screen = test_app.keys("2d").key(KeyCode::Esc).screen();
+    assert!(!screen.contains("longer rope"), "{screen}");
+    assert!(!test_app.app.should_quit);
+
+    test_app.key(KeyCode::Esc);
+    assert!(test_app.app.should_quit);
+}
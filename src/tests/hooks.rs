@@ -0,0 +1,140 @@
+//! commands from the config that run on installs, toggles and around the dedicated server
+
+use std::{fs, sync::Arc};
+
+use crossterm::event::KeyCode;
+
+use super::{mod_json, GameDir, TestApp, HEIGHT, WIDTH};
+use crate::{
+    config::Config,
+    game_fs::RealFs,
+    hooks::{Hook, HookDetails, HookEvent, Hooks},
+    install::GameInstall,
+    notifications::NotificationCenter,
+};
+
+fn hook(event: HookEvent, script: &str, timeout: Option<u64>) -> Hook {
+    Hook {
+        event,
+        command: ["sh", "-c", script].map(str::to_owned).to_vec(),
+        timeout,
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn pre_hooks_get_the_details_and_can_call_the_change_off() {
+    let game = GameDir::new();
+    let install = GameInstall::new(game.root.clone(), Arc::new(RealFs));
+    let mut notifications = NotificationCenter::default();
+    let details = HookDetails::new(HookEvent::PreInstall, &install, vec!["Grapple".to_owned()]);
+
+    let hooks = Hooks::new(
+        vec![hook(
+            HookEvent::PreInstall,
+            "cat > details.json; echo \"$DRYDOCT_EVENT $DRYDOCT_PROFILE $DRYDOCT_MODS\"",
+            None,
+        )],
+        notifications.notifier(),
+    );
+    hooks.before(details.clone()).unwrap();
+    let written: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(game.root.join("details.json")).unwrap()).unwrap();
+    assert_eq!(written["event"], "pre_install");
+    assert_eq!(written["mods"], serde_json::json!(["Grapple"]));
+    notifications.collect();
+    let ran = notifications.history().next().unwrap();
+    assert_eq!(ran.message, "ran the pre_install hook sh");
+    assert_eq!(ran.output, ["pre_install R2Northstar Grapple"]);
+
+    let hooks = Hooks::new(
+        vec![hook(
+            HookEvent::PreInstall,
+            "echo not today >&2; exit 2",
+            None,
+        )],
+        notifications.notifier(),
+    );
+    let err = hooks.before(details.clone()).unwrap_err();
+    assert_eq!(
+        err.chain()
+            .map(|cause| cause.to_string())
+            .collect::<Vec<String>>(),
+        [
+            "the pre_install hook sh failed",
+            "sh exited with 2",
+            "not today"
+        ]
+    );
+
+    let hooks = Hooks::new(
+        vec![hook(HookEvent::PreInstall, "sleep 30", Some(1))],
+        notifications.notifier(),
+    );
+    let err = hooks.before(details).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "timed out after 1s");
+
+    // more details than fit in the pipe don't keep a hook that never reads them from timing out
+    let mods = (0..8000).map(|index| format!("Mod{index:05}")).collect();
+    let details = HookDetails::new(HookEvent::PreInstall, &install, mods);
+    let err = hooks.before(details).unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "timed out after 1s");
+}
+
+#[cfg(unix)]
+#[test]
+fn toggling_a_mod_runs_its_hook_in_the_background() {
+    let game = GameDir::new().with_mods(2);
+    let config = Config {
+        hooks: vec![
+            hook(HookEvent::Enable, "echo enabled $DRYDOCT_MODS", None),
+            hook(HookEvent::Disable, "echo disabled $DRYDOCT_MODS", None),
+        ],
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    let screen = test_app
+        .key(KeyCode::Char('2'))
+        .key(KeyCode::Char(' '))
+        .key(KeyCode::F(2))
+        .screen();
+    assert!(screen.contains("ran the disable hook sh"), "{screen}");
+    assert!(screen.contains("disabled Mod 01"), "{screen}");
+    assert!(!screen.contains("ran the enable hook"), "{screen}");
+}
+
+#[cfg(unix)]
+#[test]
+fn previewed_installs_only_run_their_hooks_once_confirmed() {
+    let game =
+        GameDir::new().with_file("downloads/Grapple/mod.json", &mod_json("Grapple", "1.0.0"));
+    let source = game.root.join("downloads/Grapple").display().to_string();
+    let config = Config {
+        preview_changes: true,
+        hooks: vec![
+            hook(HookEvent::PreInstall, "touch pre", None),
+            hook(HookEvent::PostInstall, "touch post", None),
+        ],
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+    let (pre, post) = (test_app.game_path("pre"), test_app.game_path("post"));
+
+    test_app.keys("2i").keys(&source).key(KeyCode::Enter);
+    assert!(test_app.screen().contains("install Grapple from Grapple?"));
+    assert!(!pre.exists(), "turning the preview down should run nothing");
+    test_app.keys("n");
+    assert!(!pre.exists() && !post.exists());
+
+    test_app
+        .keys("i")
+        .keys(&source)
+        .key(KeyCode::Enter)
+        .keys("y");
+    assert!(pre.exists());
+    assert!(post.exists());
+    assert!(test_app
+        .game_path("R2Northstar/mods/Grapple/mod.json")
+        .exists());
+}
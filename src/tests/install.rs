@@ -0,0 +1,182 @@
+//! mod management against `MemoryFs` so nothing touches the disk
+
+use std::{path::Path, sync::Arc};
+
+use super::{mod_json, ROOT};
+use crate::{
+    autoexec::{AutoExec, AutoExecKind},
+    game_fs::{Change, DryRunFs, GameFs, MemoryFs},
+    install::GameInstall,
+    mods_views::{reload_mods, reload_plugins},
+};
+
+fn game() -> MemoryFs {
+    MemoryFs::default()
+        .with_file(
+            format!("{ROOT}/R2Northstar/mods/Zipline/mod.json"),
+            mod_json("Zipline", "1.0.0"),
+        )
+        .with_file(
+            format!("{ROOT}/R2Northstar/mods/Anchor/mod.json"),
+            mod_json("Anchor", "1.0.0"),
+        )
+        .with_file(format!("{ROOT}/R2Northstar/mods/Broken/mod.json"), "{ nope")
+        .with_file(format!("{ROOT}/R2Northstar/plugins/b.dll"), [0u8; 4])
+        .with_file(format!("{ROOT}/R2Northstar/plugins/a.dll"), [0u8; 4])
+        .with_file(format!("{ROOT}/R2Northstar/plugins/readme.txt"), "")
+        .with_dir(format!("{ROOT}/R2Vanilla/mods"))
+        .with_dir(format!("{ROOT}/bin"))
+}
+
+fn install(fs: impl GameFs + 'static) -> GameInstall {
+    GameInstall::new(ROOT.into(), Arc::new(fs))
+}
+
+fn names(install: &GameInstall) -> Vec<(String, bool)> {
+    reload_mods(install)
+        .expect("mods should load")
+        .0
+        .into_iter()
+        .map(|nsmod| (nsmod.mod_json.name, nsmod.enabled))
+        .collect()
+}
+
+#[test]
+fn mods_are_sorted_and_broken_ones_skipped() {
+    let install = install(game());
+    let (mods, skipped) = reload_mods(&install).expect("mods should load");
+
+    assert_eq!(skipped, 1);
+    assert_eq!(
+        mods.iter()
+            .map(|nsmod| nsmod.mod_json.name.as_str())
+            .collect::<Vec<&str>>(),
+        ["Anchor", "Zipline"]
+    );
+    assert_eq!(
+        mods[0].path,
+        Path::new(ROOT).join("R2Northstar/mods/Anchor")
+    );
+}
+
+#[test]
+fn only_dll_files_are_plugins() {
+    let plugins = reload_plugins(&install(game())).expect("plugins should load");
+
+    assert_eq!(
+        format!("{plugins:?}"),
+        r#"[InstalledPlugins { name: "a.dll" }, InstalledPlugins { name: "b.dll" }]"#
+    );
+}
+
+#[test]
+fn a_missing_mods_dir_is_an_error() {
+    assert!(reload_mods(&install(MemoryFs::default())).is_err());
+}
+
+#[test]
+fn enabledmods_is_read_and_written() {
+    let install = install(game().with_file(
+        format!("{ROOT}/R2Northstar/enabledmods.json"),
+        r#"{ "Zipline": false, "Version": 1 }"#,
+    ));
+    assert_eq!(
+        names(&install),
+        [("Anchor".to_owned(), true), ("Zipline".to_owned(), false)]
+    );
+
+    install
+        .set_mod_enabled("Anchor", false)
+        .expect("enabledmods.json should be writable");
+    install
+        .set_mod_enabled("Zipline", true)
+        .expect("enabledmods.json should be writable");
+
+    assert_eq!(
+        names(&install),
+        [("Anchor".to_owned(), false), ("Zipline".to_owned(), true)]
+    );
+    // keys that aren't mods survive
+    let written = install
+        .read_to_string(&install.enabled_mods_path())
+        .expect("was just written");
+    assert!(written.contains(r#""Version": 1"#), "{written}");
+}
+
+#[test]
+fn profiles_are_dirs_with_mods() {
+    let install = install(game());
+    assert_eq!(
+        install.profiles().expect("the root exists"),
+        ["R2Northstar", "R2Vanilla"]
+    );
+
+    let vanilla = install.with_profile("R2Vanilla");
+    assert!(names(&vanilla).is_empty());
+    assert_eq!(vanilla.mods_dir(), Path::new(ROOT).join("R2Vanilla/mods"));
+}
+
+#[test]
+fn dry_run_records_instead_of_writing() {
+    let memory = Arc::new(game());
+    let install = GameInstall::new(ROOT.into(), Arc::new(DryRunFs::new(memory.clone())));
+
+    install
+        .set_mod_enabled("Anchor", false)
+        .expect("a dry run can't fail to write");
+    let path = AutoExecKind::Client.path(&install);
+    let mut autoexec = AutoExec::load(&install, path.clone()).expect("missing is empty");
+    autoexec.set("ns_dummy", "1");
+    autoexec
+        .save(&install)
+        .expect("a dry run can't fail to write");
+
+    assert!(install.fs.is_dry_run());
+    assert_eq!(
+        install.fs.take_changes(),
+        [
+            Change::Write {
+                path: install.enabled_mods_path(),
+                len: 21,
+            },
+            Change::CreateDir(path.parent().expect("has a parent").to_path_buf()),
+            Change::Write {
+                path: path.clone(),
+                len: 13,
+            },
+        ]
+    );
+    assert!(install.fs.take_changes().is_empty());
+
+    // reads still see the untouched game
+    assert!(!memory.exists(&install.enabled_mods_path()));
+    assert!(!memory.exists(&path));
+    assert_eq!(
+        names(&install),
+        [("Anchor".to_owned(), true), ("Zipline".to_owned(), true)]
+    );
+}
+
+#[test]
+fn memory_fs_moves_and_removes_trees() {
+    let fs = game();
+    let mods = Path::new(ROOT).join("R2Northstar/mods");
+
+    fs.rename(&mods.join("Anchor"), &mods.join("Anchor.disabled"))
+        .expect("the mod exists");
+    assert!(fs.exists(&mods.join("Anchor.disabled/mod.json")));
+    assert!(!fs.exists(&mods.join("Anchor")));
+
+    fs.remove_dir_all(&mods.join("Anchor.disabled"))
+        .expect("the mod exists");
+    assert_eq!(
+        fs.read_dir(&mods)
+            .expect("mods exists")
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect::<Vec<_>>(),
+        [mods.join("Broken"), mods.join("Zipline")]
+    );
+
+    assert!(fs.write(&mods.join("missing/mod.json"), b"").is_err());
+}
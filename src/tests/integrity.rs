@@ -0,0 +1,213 @@
+//! install records and cached archives checked against mods in `MemoryFs`
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::KeyCode;
+
+use super::{install, mod_json, zip, GameDir, TestApp, HEIGHT, ROOT, WIDTH};
+use crate::{
+    config::Config,
+    game_fs::{GameFs, MemoryFs},
+    integrity::{self, Expected, Issue},
+    local_install::LocalPackage,
+    package_cache::PackageCache,
+};
+
+const DOWNLOADS: &str = "/home/pilot/downloads";
+const CACHE: &str = "/home/pilot/.cache/drydoct/packages";
+
+fn grapple_zip() -> Vec<u8> {
+    zip(&[
+        (
+            "manifest.json",
+            r#"{ "name": "Grapple", "version_number": "1.0.0", "website_url": "", "description": "", "dependencies": [] }"#,
+        ),
+        ("mods/Grapple/mod.json", &mod_json("Grapple", "1.0.0")),
+        ("mods/Grapple/mod/scripts/grapple.nut", "swing"),
+        ("mods/Grapple/mod/scripts/rope.nut", "rope"),
+    ])
+}
+
+fn installed(relative: &str) -> PathBuf {
+    Path::new(ROOT)
+        .join("R2Northstar/mods/Grapple")
+        .join(relative)
+}
+
+fn damage(fs: &MemoryFs) {
+    fs.remove_file(&installed("mod/scripts/rope.nut")).unwrap();
+    fs.write(&installed("mod.json"), b"{ broken").unwrap();
+    fs.write(&installed("mod/scripts/cheats.nut"), b"extra")
+        .unwrap();
+}
+
+fn issues(report: &integrity::Report) -> Vec<(&str, Issue)> {
+    report
+        .issues
+        .iter()
+        .map(|(path, issue)| (path.as_str(), *issue))
+        .collect()
+}
+
+#[test]
+fn installs_are_recorded_and_repaired_from_their_zip() {
+    let (fs, install) =
+        install(MemoryFs::default().with_file(format!("{DOWNLOADS}/Grapple.zip"), grapple_zip()));
+    let source = Path::new(DOWNLOADS).join("Grapple.zip");
+    LocalPackage::load(&install, &source)
+        .and_then(|package| package.plan(&install, &source))
+        .and_then(|plan| plan.install(&install))
+        .unwrap();
+
+    let report = integrity::verify(&install, &installed(""), &PackageCache::new(CACHE)).unwrap();
+    assert_eq!(report.expected, Expected::Recorded);
+    assert_eq!(report.summary(), "ok");
+
+    damage(&fs);
+    let report = integrity::verify(&install, &installed(""), &PackageCache::new(CACHE)).unwrap();
+    assert_eq!(
+        issues(&report),
+        [
+            ("mod.json", Issue::Modified),
+            ("mod/scripts/cheats.nut", Issue::Extra),
+            ("mod/scripts/rope.nut", Issue::Missing),
+        ]
+    );
+    assert_eq!(report.summary(), "1 missing, 1 modified, 1 extra");
+
+    assert_eq!(integrity::repair(&install, &report).unwrap(), 2);
+    assert_eq!(
+        fs.read_to_string(&installed("mod.json")).unwrap(),
+        mod_json("Grapple", "1.0.0")
+    );
+    assert_eq!(
+        fs.read_to_string(&installed("mod/scripts/rope.nut"))
+            .unwrap(),
+        "rope"
+    );
+    let report = integrity::verify(&install, &installed(""), &PackageCache::new(CACHE)).unwrap();
+    assert_eq!(
+        issues(&report),
+        [("mod/scripts/cheats.nut", Issue::Extra)],
+        "extra files are left alone"
+    );
+}
+
+#[test]
+fn thunderstore_mods_are_checked_against_the_cached_archive() {
+    let (fs, install) = install(
+        MemoryFs::default()
+            .with_file(installed("manifest.json"), r#"{ "name": "Grapple", "version_number": "1.0.0", "website_url": "", "description": "", "dependencies": [] }"#)
+            .with_file(installed("thunderstore_author.txt"), "pilot")
+            .with_file(installed("mod.json"), mod_json("Grapple", "1.0.0"))
+            .with_file(installed("mod/scripts/grapple.nut"), "swing")
+            .with_file(installed("mod/scripts/rope.nut"), "rope"),
+    );
+    PackageCache::new(CACHE)
+        .put(&*fs, "pilot-Grapple-1.0.0", &grapple_zip())
+        .unwrap();
+
+    let report = integrity::verify(&install, &installed(""), &PackageCache::new(CACHE)).unwrap();
+    assert_eq!(report.expected, Expected::Archive);
+    assert_eq!(report.summary(), "ok");
+
+    fs.write(&installed("mod/scripts/grapple.nut"), b"fling")
+        .unwrap();
+    let report = integrity::verify(&install, &installed(""), &PackageCache::new(CACHE)).unwrap();
+    assert_eq!(
+        issues(&report),
+        [("mod/scripts/grapple.nut", Issue::Modified)]
+    );
+    integrity::repair(&install, &report).unwrap();
+    assert_eq!(
+        fs.read_to_string(&installed("mod/scripts/grapple.nut"))
+            .unwrap(),
+        "swing"
+    );
+}
+
+#[test]
+fn mods_without_a_record_or_archive_are_not_checked() {
+    let (_, install) =
+        install(MemoryFs::default().with_file(installed("mod.json"), mod_json("Grapple", "1.0.0")));
+
+    let reports = integrity::verify_all(&install, &PackageCache::new(CACHE)).unwrap();
+
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].expected, Expected::Unknown);
+    assert_eq!(reports[0].summary(), "nothing to check against");
+    assert!(integrity::repair(&install, &reports[0]).is_err());
+}
+
+#[test]
+fn folder_installs_repair_from_the_folder() {
+    let (fs, install) = install(
+        MemoryFs::default()
+            .with_file(
+                format!("{DOWNLOADS}/Grapple/mod.json"),
+                mod_json("Grapple", "1.0.0"),
+            )
+            .with_file(format!("{DOWNLOADS}/Grapple/mod/scripts/rope.nut"), "rope"),
+    );
+    let source = Path::new(DOWNLOADS).join("Grapple");
+    LocalPackage::load(&install, &source)
+        .and_then(|package| package.plan(&install, &source))
+        .and_then(|plan| plan.install(&install))
+        .unwrap();
+
+    fs.remove_file(&installed("mod/scripts/rope.nut")).unwrap();
+    let report = integrity::verify(&install, &installed(""), &PackageCache::new(CACHE)).unwrap();
+    integrity::repair(&install, &report).unwrap();
+
+    assert!(fs.exists(&installed("mod/scripts/rope.nut")));
+    // the source changed since, so it can't be trusted anymore
+    fs.remove_file(&installed("mod/scripts/rope.nut")).unwrap();
+    fs.write(
+        &Path::new(DOWNLOADS).join("Grapple/mod/scripts/rope.nut"),
+        b"chain",
+    )
+    .unwrap();
+    let report = integrity::verify(&install, &installed(""), &PackageCache::new(CACHE)).unwrap();
+    assert!(integrity::repair(&install, &report)
+        .unwrap_err()
+        .to_string()
+        .contains("changed since Grapple was installed"));
+}
+
+#[test]
+fn damaged_mods_are_listed_and_repaired_from_utils() {
+    let game = GameDir::new().with_mod("Anchor", "1.0.0", "installed by hand");
+    let source = game.root.join("Grapple.zip");
+    fs::write(&source, grapple_zip()).unwrap();
+    let package_cache = game.root.join("cache");
+    let mut test_app = TestApp::with_config(
+        game,
+        Config {
+            package_cache: Some(package_cache),
+            ..Config::default()
+        },
+        WIDTH,
+        HEIGHT,
+    );
+    test_app
+        .keys("2i")
+        .keys(&source.display().to_string())
+        .key(KeyCode::Enter);
+    fs::remove_file(test_app.game_path("R2Northstar/mods/Grapple/mod/scripts/rope.nut")).unwrap();
+
+    test_app
+        .keys("3")
+        .key(KeyCode::Tab)
+        .key(KeyCode::Tab)
+        .key(KeyCode::Down);
+    insta::assert_snapshot!("integrity", test_app.screen());
+
+    let screen = test_app.keys("r").screen();
+    assert!(test_app
+        .game_path("R2Northstar/mods/Grapple/mod/scripts/rope.nut")
+        .exists());
+    assert!(screen.contains("integrity (0 damaged)"), "{screen}");
+}
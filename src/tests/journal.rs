@@ -0,0 +1,343 @@
+//! transactions recorded over `MemoryFs` and undone again
+
+use std::{path::Path, sync::Arc};
+
+use anyhow::Result as AnyResult;
+use crossterm::event::KeyCode;
+
+use super::{install, GameDir, TestApp, HEIGHT, ROOT, WIDTH};
+use crate::{
+    config::Config,
+    game_fs::{Change, DryRunFs, GameFs, MemoryFs},
+    install::GameInstall,
+    journal::{Journal, Op, Written, TRASH_NAME},
+};
+
+fn game() -> (Arc<MemoryFs>, GameInstall) {
+    install(
+        MemoryFs::default()
+            .with_file(format!("{ROOT}/R2Northstar/enabledmods.json"), "{}")
+            .with_file(format!("{ROOT}/R2Northstar/mods/Old/mod.json"), "old")
+            .with_file(format!("{ROOT}/R2Northstar/mods/Old/mod/a.nut"), "a"),
+    )
+}
+
+fn path(relative: &str) -> std::path::PathBuf {
+    Path::new(ROOT).join(relative)
+}
+
+/// touches a bit of everything the journal knows about
+fn shuffle(install: &GameInstall) -> AnyResult<()> {
+    install.set_mod_enabled("Old", false)?;
+    install.write(&path("R2Northstar/mods/New/mod.json"), b"new")?;
+    install.fs.remove_dir_all(&path("R2Northstar/mods/Old"))?;
+    install.fs.rename(
+        &path("R2Northstar/mods/New"),
+        &path("R2Northstar/mods/Renamed"),
+    )?;
+
+    Ok(())
+}
+
+fn listing(fs: &MemoryFs, dir: &str) -> Vec<String> {
+    fs.read_dir(&path(dir))
+        .expect("dir exists")
+        .into_iter()
+        .filter_map(|entry| Some(entry.path.file_name()?.to_str()?.to_owned()))
+        .collect()
+}
+
+#[test]
+fn transactions_keep_the_before_and_after() {
+    let (fs, install) = game();
+    let mut journal = Journal::default();
+
+    journal
+        .run(&install, "shuffle mods", shuffle)
+        .expect("the game dir is writable");
+
+    let transaction = journal.transactions().next().expect("one was recorded");
+    assert_eq!(transaction.description, "shuffle mods");
+    assert!(!transaction.failed);
+    assert_eq!(
+        transaction.ops[0],
+        Op::Write {
+            path: path("R2Northstar/enabledmods.json"),
+            before: Some(b"{}".to_vec()),
+            after: Written::of(b"{\n  \"Old\": false\n}"),
+        }
+    );
+    assert_eq!(
+        transaction.ops[1],
+        Op::CreateDir(path("R2Northstar/mods/New"))
+    );
+    let Op::RemoveDir {
+        path: removed,
+        trash: Some(trash),
+    } = &transaction.ops[3]
+    else {
+        panic!(
+            "{:?} should have moved Old to the trash",
+            transaction.ops[3]
+        );
+    };
+    assert_eq!(*removed, path("R2Northstar/mods/Old"));
+    assert!(trash.starts_with(path(TRASH_NAME)));
+    assert_eq!(
+        fs.read_to_string(&trash.join("mod/a.nut"))
+            .expect("was moved along"),
+        "a"
+    );
+    assert_eq!(transaction.ops.len(), 5);
+}
+
+#[test]
+fn undo_restores_everything() {
+    let (fs, install) = game();
+    let mut journal = Journal::default();
+    journal
+        .run(&install, "shuffle mods", shuffle)
+        .expect("the game dir is writable");
+    journal
+        .run(&install, "enable Old", |install| {
+            install.set_mod_enabled("Old", true)
+        })
+        .expect("the game dir is writable");
+    assert_eq!(listing(&fs, "R2Northstar/mods"), ["Renamed"]);
+
+    let undone = journal.undo(&install, 5).expect("nothing changed since");
+
+    assert_eq!(undone, ["enable Old", "shuffle mods"]);
+    assert_eq!(journal.len(), 0);
+    assert_eq!(listing(&fs, "R2Northstar/mods"), ["Old"]);
+    assert_eq!(listing(&fs, "R2Northstar/mods/Old"), ["mod", "mod.json"]);
+    assert_eq!(
+        fs.read_to_string(&path("R2Northstar/mods/Old/mod/a.nut"))
+            .expect("was restored"),
+        "a"
+    );
+    assert_eq!(
+        fs.read_to_string(&path("R2Northstar/enabledmods.json"))
+            .expect("was restored"),
+        "{}"
+    );
+}
+
+#[test]
+fn removed_files_wait_in_the_trash() {
+    let (fs, install) = game();
+    let mut journal = Journal::default();
+    journal
+        .run(&install, "remove a.nut", |install| {
+            install
+                .fs
+                .remove_file(&path("R2Northstar/mods/Old/mod/a.nut"))?;
+            Ok(())
+        })
+        .expect("the game dir is writable");
+
+    assert!(listing(&fs, "R2Northstar/mods/Old/mod").is_empty());
+    let trashed = listing(&fs, TRASH_NAME);
+    assert_eq!(trashed.len(), 1);
+    assert_eq!(
+        fs.read_to_string(&path(TRASH_NAME).join(&trashed[0]))
+            .expect("was moved"),
+        "a"
+    );
+
+    journal.undo(&install, 1).expect("nothing changed since");
+    assert_eq!(
+        fs.read_to_string(&path("R2Northstar/mods/Old/mod/a.nut"))
+            .expect("was moved back"),
+        "a"
+    );
+    assert!(listing(&fs, TRASH_NAME).is_empty());
+}
+
+#[test]
+fn undo_refuses_when_the_file_changed_since() {
+    let (fs, install) = game();
+    let mut journal = Journal::default();
+    journal
+        .run(&install, "disable Old", |install| {
+            install.set_mod_enabled("Old", false)
+        })
+        .expect("the game dir is writable");
+    fs.write(
+        &path("R2Northstar/enabledmods.json"),
+        b"{ \"Other\": true }",
+    )
+    .expect("exists");
+
+    let err = journal.undo(&install, 1).expect_err("the file changed");
+
+    assert!(format!("{err:#}").contains("was changed since"), "{err:#}");
+    assert_eq!(journal.len(), 1);
+}
+
+#[test]
+fn a_failed_undo_puts_back_what_it_already_undid() {
+    let (fs, install) = game();
+    let mut journal = Journal::default();
+    journal
+        .run(&install, "add New and disable Old", |install| {
+            install.write(&path("R2Northstar/mods/New/mod.json"), b"new")?;
+            install.set_mod_enabled("Old", false)
+        })
+        .expect("the game dir is writable");
+    fs.write(&path("R2Northstar/mods/New/mod.json"), b"edited")
+        .expect("exists");
+
+    let err = journal.undo(&install, 1).expect_err("New changed");
+
+    assert!(format!("{err:#}").contains("was changed since"), "{err:#}");
+    assert_eq!(journal.len(), 1);
+    assert_eq!(
+        fs.read_to_string(&path("R2Northstar/enabledmods.json"))
+            .expect("was written again"),
+        "{\n  \"Old\": false\n}"
+    );
+}
+
+#[test]
+fn dropped_transactions_empty_the_trash() {
+    let (fs, install) = game();
+    let mut journal = Journal::default();
+    journal
+        .run(&install, "remove Old", |install| {
+            install.fs.remove_dir_all(&path("R2Northstar/mods/Old"))?;
+            Ok(())
+        })
+        .expect("the game dir is writable");
+    assert_eq!(listing(&fs, TRASH_NAME).len(), 1);
+
+    for count in 0..50 {
+        journal
+            .run(&install, "count", move |install| {
+                install.write(&path("count.txt"), count.to_string().as_bytes())
+            })
+            .expect("the game dir is writable");
+    }
+
+    assert_eq!(journal.len(), 50);
+    assert!(listing(&fs, TRASH_NAME).is_empty());
+}
+
+#[test]
+fn failed_operations_are_still_recorded() {
+    let (_, install) = game();
+    let mut journal = Journal::default();
+
+    let result = journal.run(&install, "half of it", |install| {
+        install.set_mod_enabled("Old", false)?;
+        anyhow::bail!("out of disk")
+    });
+
+    assert!(result.is_err());
+    let transaction = journal.transactions().next().expect("one was recorded");
+    assert!(transaction.failed);
+    assert_eq!(transaction.ops.len(), 1);
+}
+
+#[test]
+fn previews_and_dry_runs_change_nothing() {
+    let (fs, install) = game();
+
+    let changes = Journal::preview(&install, &shuffle).expect("a preview can't fail to write");
+    assert_eq!(
+        changes[0],
+        Change::Write {
+            path: path("R2Northstar/enabledmods.json"),
+            len: 18,
+        }
+    );
+    assert_eq!(changes.len(), 5);
+
+    let dry_run = GameInstall::new(ROOT.into(), Arc::new(DryRunFs::new(fs.clone())));
+    let mut journal = Journal::default();
+    journal
+        .run(&dry_run, "shuffle mods", shuffle)
+        .expect("a dry run can't fail to write");
+
+    assert_eq!(journal.len(), 0);
+    assert_eq!(listing(&fs, "R2Northstar/mods"), ["Old"]);
+    assert_eq!(
+        fs.read_to_string(&path("R2Northstar/enabledmods.json"))
+            .expect("exists"),
+        "{}"
+    );
+}
+
+#[test]
+fn previewed_changes_are_confirmed_and_undone() {
+    let game = GameDir::new().with_mods(2);
+    let mut test_app = TestApp::with_config(
+        game,
+        Config {
+            preview_changes: true,
+            ..Config::default()
+        },
+        WIDTH,
+        HEIGHT,
+    );
+    let enabled_mods = test_app.game_path("R2Northstar/enabledmods.json");
+
+    test_app.key(KeyCode::Char('2')).key(KeyCode::Char(' '));
+    insta::assert_snapshot!("journal_preview", test_app.screen());
+    assert!(!enabled_mods.exists());
+
+    test_app.key(KeyCode::Char('y'));
+    assert!(std::fs::read_to_string(&enabled_mods)
+        .expect("was just written")
+        .contains("\"Mod 01\": false"));
+
+    test_app.key(KeyCode::F(4));
+    // the time the transaction ran changes every run
+    let time = regex::Regex::new(r"\[\d\d:\d\d:\d\d\]").expect("valid regex");
+    insta::assert_snapshot!(
+        "journal",
+        time.replace_all(&test_app.screen(), "[hh:mm:ss]")
+    );
+
+    test_app.key(KeyCode::Char('u'));
+    assert!(!enabled_mods.exists());
+    assert!(test_app.screen().contains("journal (0)"));
+}
+
+#[test]
+fn a_cancelled_convar_keeps_the_shown_value() {
+    let game = GameDir::new().with_mod_json(
+        "Mod Settings",
+        r#"{
+    "Name": "Mod Settings",
+    "Description": "declares a convar",
+    "Version": "1.0.0",
+    "LoadPriority": 1,
+    "RequiredOnClient": false,
+    "ConVars": [{ "Name": "everything_unlocked", "DefaultValue": "1" }],
+    "Scripts": [],
+    "Localisation": []
+}"#,
+    );
+    let mut test_app = TestApp::with_config(
+        game,
+        Config {
+            preview_changes: true,
+            ..Config::default()
+        },
+        WIDTH,
+        HEIGHT,
+    );
+
+    test_app.keys("3").key(KeyCode::Tab).key(KeyCode::Enter);
+    let screen = test_app.keys("n").screen();
+    assert!(screen.contains("everything_unlocked"), "{screen}");
+    assert!(!screen.contains("bool    1                 0"), "{screen}");
+
+    test_app.key(KeyCode::Enter).keys("y");
+    let autoexec =
+        test_app.game_path("R2Northstar/mods/Northstar.Client/mod/cfg/autoexec_ns_client.cfg");
+    assert!(std::fs::read_to_string(autoexec)
+        .expect("was just written")
+        .contains("everything_unlocked \"0\""));
+}
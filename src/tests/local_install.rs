@@ -0,0 +1,204 @@
+//! installing zips and folders from outside the game over `MemoryFs`
+
+use std::path::Path;
+
+use crossterm::event::KeyCode;
+
+use super::{install, mod_json, zip, GameDir, TestApp, ROOT};
+use crate::{
+    game_fs::{GameFs, MemoryFs},
+    install::GameInstall,
+    local_install::LocalPackage,
+    mods_views::{reload_mods, reload_plugins},
+    path_input::PathInput,
+};
+
+const DOWNLOADS: &str = "/home/pilot/downloads";
+
+fn install_from(install: &GameInstall, source: &str) -> anyhow::Result<Vec<String>> {
+    let path = Path::new(DOWNLOADS).join(source);
+    let plan = LocalPackage::load(install, &path)?.plan(install, &path)?;
+    plan.install(install)?;
+
+    Ok(plan.mods.into_iter().map(|planned| planned.dir).collect())
+}
+
+fn mod_names(install: &GameInstall) -> Vec<String> {
+    reload_mods(install)
+        .expect("mods should load")
+        .0
+        .into_iter()
+        .map(|nsmod| nsmod.mod_json.name)
+        .collect()
+}
+
+#[test]
+fn a_bare_mod_folder_is_named_after_the_folder() {
+    let (fs, install) = install(
+        MemoryFs::default()
+            .with_file(
+                format!("{DOWNLOADS}/Grapple/mod.json"),
+                mod_json("Grapple", "1.0.0"),
+            )
+            .with_file(format!("{DOWNLOADS}/Grapple/mod/scripts/grapple.nut"), "x"),
+    );
+
+    assert_eq!(install_from(&install, "Grapple").unwrap(), ["Grapple"]);
+    assert_eq!(mod_names(&install), ["Grapple"]);
+    assert!(fs.exists(Path::new(&format!(
+        "{ROOT}/R2Northstar/mods/Grapple/mod/scripts/grapple.nut"
+    ))));
+}
+
+#[test]
+fn thunderstore_zips_get_their_manifest_and_plugins() {
+    let bytes = zip(&[
+        ("manifest.json", r#"{ "name": "Grapple" }"#),
+        ("README.md", "# grapple"),
+        ("mods/Pilot.Grapple/mod.json", &mod_json("Grapple", "1.0.0")),
+        ("plugins/grapple.dll", "dll"),
+        ("plugins/notes.txt", "not a plugin"),
+    ]);
+    let (fs, install) =
+        install(MemoryFs::default().with_file(format!("{DOWNLOADS}/Grapple.zip"), bytes));
+
+    assert_eq!(
+        install_from(&install, "Grapple.zip").unwrap(),
+        ["Pilot.Grapple"]
+    );
+    assert_eq!(
+        fs.read_to_string(Path::new(&format!(
+            "{ROOT}/R2Northstar/mods/Pilot.Grapple/manifest.json"
+        )))
+        .unwrap(),
+        r#"{ "name": "Grapple" }"#
+    );
+    assert_eq!(
+        format!("{:?}", reload_plugins(&install).unwrap()),
+        r#"[InstalledPlugins { name: "grapple.dll" }]"#
+    );
+}
+
+#[test]
+fn zipped_folders_of_several_mods_are_unwrapped() {
+    let bytes = zip(&[
+        ("pack/Anchor/mod.json", &mod_json("Anchor", "1.0.0")),
+        ("pack/Zipline/mod.json", &mod_json("Zipline", "1.0.0")),
+        ("pack/notes.txt", "ignored"),
+    ]);
+    let (_, install) =
+        install(MemoryFs::default().with_file(format!("{DOWNLOADS}/pack.zip"), bytes));
+
+    assert_eq!(
+        install_from(&install, "pack.zip").unwrap(),
+        ["Anchor", "Zipline"]
+    );
+    assert_eq!(mod_names(&install), ["Anchor", "Zipline"]);
+}
+
+#[test]
+fn installing_again_replaces_the_old_files() {
+    let (fs, install) = install(
+        MemoryFs::default()
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Grapple/mod.json"),
+                mod_json("Grapple", "1.0.0"),
+            )
+            .with_file(format!("{ROOT}/R2Northstar/mods/Grapple/stale.nut"), "old")
+            .with_file(
+                format!("{DOWNLOADS}/Grapple/mod.json"),
+                mod_json("Grapple", "1.0.0"),
+            ),
+    );
+
+    let path = Path::new(DOWNLOADS).join("Grapple");
+    let plan = LocalPackage::load(&install, &path)
+        .and_then(|package| package.plan(&install, &path))
+        .unwrap();
+    assert!(plan.mods[0].replaces);
+    assert_eq!(plan.describe(), "install Grapple from Grapple");
+
+    plan.install(&install).unwrap();
+    assert!(!fs.exists(Path::new(&format!(
+        "{ROOT}/R2Northstar/mods/Grapple/stale.nut"
+    ))));
+}
+
+#[test]
+fn bad_packages_say_what_is_wrong() {
+    let (_, install) = install(
+        MemoryFs::default()
+            .with_file(format!("{DOWNLOADS}/Broken/mod.json"), "{ nope")
+            .with_file(format!("{DOWNLOADS}/Empty/readme.txt"), "")
+            .with_file(format!("{DOWNLOADS}/notes.txt"), "")
+            .with_file(
+                format!("{DOWNLOADS}/evil.zip"),
+                zip(&[("../../evil/mod.json", &mod_json("Evil", "1.0.0"))]),
+            )
+            .with_file(format!("{DOWNLOADS}/corrupt.zip"), "not a zip"),
+    );
+    let error = |source| format!("{:#}", install_from(&install, source).unwrap_err());
+
+    assert!(error("Broken").starts_with("Broken/mod.json isn't a valid mod.json"));
+    assert_eq!(
+        error("Empty"),
+        format!("couldn't find a mod.json, mods/ or plugins/ in {DOWNLOADS}/Empty")
+    );
+    assert_eq!(
+        error("notes.txt"),
+        format!("{DOWNLOADS}/notes.txt is neither a folder nor a .zip")
+    );
+    assert_eq!(
+        error("missing.zip"),
+        format!("{DOWNLOADS}/missing.zip doesn't exist")
+    );
+    assert!(error("evil.zip").ends_with("../../evil/mod.json points outside of the zip"));
+    assert!(error("corrupt.zip").starts_with(&format!("couldn't open {DOWNLOADS}/corrupt.zip")));
+    assert_eq!(mod_names(&install), Vec::<String>::new());
+}
+
+#[test]
+fn tab_completes_as_far_as_the_matches_agree() {
+    let fs = MemoryFs::default()
+        .with_file(format!("{DOWNLOADS}/Grapple.zip"), "")
+        .with_dir(format!("{DOWNLOADS}/Grapple-dev"))
+        .with_file(format!("{DOWNLOADS}/Other.zip"), "");
+    let mut input = PathInput::default();
+
+    input.value = format!("{DOWNLOADS}/Gr");
+    input.complete(&fs);
+    assert_eq!(input.value, format!("{DOWNLOADS}/Grapple"));
+
+    input.value.push('-');
+    input.complete(&fs);
+    assert_eq!(input.value, format!("{DOWNLOADS}/Grapple-dev/"));
+
+    input.value = format!("{DOWNLOADS}/O");
+    input.complete(&fs);
+    assert_eq!(input.value, format!("{DOWNLOADS}/Other.zip"));
+
+    input.value = format!("{DOWNLOADS}/nothing");
+    input.complete(&fs);
+    assert_eq!(input.value, format!("{DOWNLOADS}/nothing"));
+}
+
+#[test]
+fn installing_from_the_mods_tab() {
+    let game = GameDir::new()
+        .with_mod("Anchor", "1.0.0", "already there")
+        .with_file("downloads/Grapple/mod.json", &mod_json("Grapple", "1.0.0"));
+    let source = game.root.join("downloads/Gra").display().to_string();
+    let mut test_app = TestApp::new(game);
+
+    test_app
+        .keys("2i")
+        .keys(&source)
+        .key(KeyCode::Tab)
+        .key(KeyCode::Enter);
+
+    insta::assert_snapshot!("install_from_file", test_app.screen());
+    assert!(test_app
+        .game_path("R2Northstar/mods/Grapple/mod.json")
+        .exists());
+    assert!(test_app.key(KeyCode::F(4)).screen().contains("journal (1)"));
+}
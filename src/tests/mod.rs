@@ -0,0 +1,355 @@
+//! renders the whole app against a `TestBackend` with a fake game directory,
+//! run with `INSTA_UPDATE=always` to accept changed snapshots
+
+mod cache;
+mod dedicated;
+mod dev_link;
+mod disk_usage;
+mod docs;
+mod hooks;
+mod install;
+mod integrity;
+mod journal;
+mod local_install;
+mod mod_store;
+mod mods;
+mod notifications;
+mod pack;
+mod packages;
+mod prepare;
+mod profile_diff;
+mod render;
+mod servers;
+
+use std::{
+    fs,
+    io::{Cursor, Write},
+    path::PathBuf,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::{backend::TestBackend, style::Color, Terminal};
+use tempfile::TempDir;
+use zip::{write::FileOptions, ZipWriter};
+
+use crate::{app::App, config::Config, game_fs::MemoryFs, install::GameInstall};
+
+pub const WIDTH: u16 = 120;
+pub const HEIGHT: u16 = 40;
+/// where the game is in a `MemoryFs`
+pub const ROOT: &str = "/games/Titanfall2";
+
+/// the least a mod.json needs to load
+pub fn mod_json(name: &str, version: &str) -> String {
+    mod_json_with_priority(name, version, 1)
+}
+
+pub fn mod_json_with_priority(name: &str, version: &str, load_priority: i32) -> String {
+    format!(
+        r#"{{ "Name": "{name}", "Description": "", "Version": "{version}", "LoadPriority": {load_priority} }}"#
+    )
+}
+
+/// a zip of `(path, contents)` pairs
+pub fn zip(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (path, contents) in files {
+        writer
+            .start_file(*path, FileOptions::default())
+            .expect("couldn't start a zip entry");
+        writer
+            .write_all(contents.as_bytes())
+            .expect("couldn't write a zip entry");
+    }
+
+    writer
+        .finish()
+        .expect("couldn't finish the zip")
+        .into_inner()
+}
+
+/// a thunderstore archive with a single mod
+pub fn package_zip(name: &str, version: &str) -> Vec<u8> {
+    zip(&[
+        (
+            "manifest.json",
+            &format!(
+                r#"{{ "name": "{name}", "version_number": "{version}", "website_url": "", "description": "", "dependencies": [] }}"#
+            ),
+        ),
+        (&format!("mods/{name}/mod.json"), &mod_json(name, version)),
+    ])
+}
+
+/// the game at [`ROOT`] in `fs`, which is handed back to look at what changed
+pub fn install(fs: MemoryFs) -> (Arc<MemoryFs>, GameInstall) {
+    let fs = Arc::new(fs.with_dir(format!("{ROOT}/R2Northstar/mods")));
+    let install = GameInstall::new(ROOT.into(), fs.clone());
+
+    (fs, install)
+}
+
+/// a Titanfall2 install with only the parts drydoct reads
+pub struct GameDir {
+    _dir: TempDir,
+    pub root: PathBuf,
+}
+
+impl GameDir {
+    pub fn new() -> Self {
+        let dir = TempDir::new().expect("couldn't create a temp dir");
+        // canonical so it matches the paths drydoct resolves
+        let root = dir
+            .path()
+            .canonicalize()
+            .expect("temp dir should exist")
+            .join("Titanfall2");
+
+        for sub_dir in ["mods", "plugins", "logs"] {
+            fs::create_dir_all(root.join("R2Northstar").join(sub_dir))
+                .expect("couldn't create the game dir");
+        }
+
+        Self { _dir: dir, root }
+    }
+
+    fn northstar(&self) -> PathBuf {
+        self.root.join("R2Northstar")
+    }
+
+    pub fn with_mod(self, name: &str, version: &str, description: &str) -> Self {
+        self.with_mod_json(
+            name,
+            &format!(
+                r#"{{
+    "Name": "{name}",
+    "Description": "{description}",
+    "Version": "{version}",
+    "LoadPriority": 1,
+    "RequiredOnClient": false,
+    "ConVars": [],
+    "Scripts": [],
+    "Localisation": []
+}}"#
+            ),
+        )
+    }
+
+    pub fn with_mod_json(self, name: &str, mod_json: &str) -> Self {
+        let mod_dir = self.northstar().join("mods").join(name);
+        fs::create_dir_all(&mod_dir).expect("couldn't create the mod dir");
+        fs::write(mod_dir.join("mod.json"), mod_json).expect("couldn't write mod.json");
+        fs::write(mod_dir.join("thunderstore_author.txt"), "fixture")
+            .expect("couldn't write the author");
+
+        self
+    }
+
+    /// `count` mods named `Mod 01`, `Mod 02` and so on
+    pub fn with_mods(self, count: usize) -> Self {
+        (1..=count).fold(self, |game, index| {
+            game.with_mod(
+                &format!("Mod {index:02}"),
+                &format!("1.{index}.0"),
+                &format!("fixture mod number {index}"),
+            )
+        })
+    }
+
+    pub fn with_plugin(self, name: &str) -> Self {
+        fs::write(self.northstar().join("plugins").join(name), [0u8; 4])
+            .expect("couldn't write the plugin");
+
+        self
+    }
+
+    /// any other file, `path` is relative to the game dir
+    pub fn with_file(self, path: &str, content: &str) -> Self {
+        let path = self.root.join(path);
+        fs::create_dir_all(path.parent().expect("files have a parent"))
+            .expect("couldn't create the parent dir");
+        fs::write(path, content).expect("couldn't write the file");
+
+        self
+    }
+
+    /// logs get a fixed modified time since it is shown in the logs section
+    pub fn with_log(self, name: &str, content: &str, modified: u64) -> Self {
+        let path = self.northstar().join("logs").join(name);
+        fs::write(&path, content).expect("couldn't write the log");
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(UNIX_EPOCH + Duration::from_secs(modified)))
+            .expect("couldn't set the log's modified time");
+
+        self
+    }
+}
+
+pub struct TestApp {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+    game: GameDir,
+}
+
+impl TestApp {
+    pub fn new(game: GameDir) -> Self {
+        Self::with_size(game, WIDTH, HEIGHT)
+    }
+
+    pub fn with_size(game: GameDir, width: u16, height: u16) -> Self {
+        Self::with_config(game, Config::default(), width, height)
+    }
+
+    /// `game_path` is always set to the fixture, the package index and saved filters default to
+    /// files in it so nothing goes to the network or the real config dir
+    pub fn with_config(game: GameDir, config: Config, width: u16, height: u16) -> Self {
+        let app = App::with_config(Config {
+            game_path: Some(game.root.clone()),
+            package_index: config
+                .package_index
+                .clone()
+                .or_else(|| Some(game.root.join("packages.json").display().to_string())),
+            saved_filters: config
+                .saved_filters
+                .clone()
+                .or_else(|| Some(game.root.join("filters.json"))),
+            ..config
+        });
+        let terminal =
+            Terminal::new(TestBackend::new(width, height)).expect("test backend can't fail");
+
+        let mut test_app = Self {
+            app,
+            terminal,
+            game,
+        };
+        test_app.render();
+        test_app
+    }
+
+    pub fn event(&mut self, event: Event) -> &mut Self {
+        self.app
+            .handle_event(event)
+            .expect("handling an event shouldn't fail");
+        // like the real loop every event is followed by a frame so hitboxes stay current
+        self.render();
+        self
+    }
+
+    pub fn key(&mut self, code: KeyCode) -> &mut Self {
+        self.event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    /// presses every character in order
+    pub fn keys(&mut self, keys: &str) -> &mut Self {
+        keys.chars()
+            .fold(self, |test_app, c| test_app.key(KeyCode::Char(c)))
+    }
+
+    pub fn repeat(&mut self, code: KeyCode, times: usize) -> &mut Self {
+        (0..times).fold(self, |test_app, _| test_app.key(code))
+    }
+
+    pub fn mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) -> &mut Self {
+        self.event(Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }))
+    }
+
+    pub fn click(&mut self, column: u16, row: u16) -> &mut Self {
+        self.mouse(MouseEventKind::Down(MouseButton::Left), column, row)
+    }
+
+    /// clicks the first cell of the first place `text` shows up
+    pub fn click_text(&mut self, text: &str) -> &mut Self {
+        let (column, row) = self
+            .find(text)
+            .unwrap_or_else(|| panic!("\"{text}\" isn't on screen:\n{}", self.screen()));
+        self.click(column, row)
+    }
+
+    /// renders until `text` is on screen, for anything that happens on another thread
+    pub fn wait_for(&mut self, text: &str) -> &mut Self {
+        let start = Instant::now();
+        while !self.screen().contains(text) {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "\"{text}\" never showed up:\n{}",
+                self.screen()
+            );
+            thread::sleep(Duration::from_millis(20));
+            self.render();
+        }
+
+        self
+    }
+
+    pub fn resize(&mut self, width: u16, height: u16) -> &mut Self {
+        self.terminal.backend_mut().resize(width, height);
+        self.render();
+        self
+    }
+
+    /// waits for background work first so what's on screen doesn't depend on timing
+    fn render(&mut self) {
+        self.app.tick();
+        while self.app.is_busy() {
+            thread::sleep(Duration::from_millis(5));
+            self.app.tick();
+        }
+        self.terminal
+            .draw(|frame| self.app.draw(frame))
+            .expect("test backend can't fail");
+    }
+
+    /// the symbols on screen, anything from the game dir onwards is cut off since its length
+    /// changes between machines
+    pub fn screen(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        let root = self.game.root.display().to_string();
+
+        buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|line| {
+                let line = line
+                    .iter()
+                    .map(|cell| cell.symbol.as_str())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_owned();
+
+                match line.find(&root) {
+                    Some(index) => format!("{}<game>...", &line[..index]),
+                    None => line,
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn find(&self, text: &str) -> Option<(u16, u16)> {
+        self.screen().lines().enumerate().find_map(|(row, line)| {
+            line.find(text)
+                .map(|byte| (line[..byte].chars().count() as u16, row as u16))
+        })
+    }
+
+    pub fn game_path(&self, path: &str) -> PathBuf {
+        self.game.root.join(path)
+    }
+
+    pub fn fg(&self, column: u16, row: u16) -> Color {
+        self.terminal.backend().buffer().get(column, row).fg
+    }
+}
@@ -0,0 +1,182 @@
+//! mods stored once and hard linked into the profiles that have them
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::{mod_json, GameDir};
+use crate::{
+    disk_usage::measure,
+    game_fs::{GameFs, RealFs},
+    install::GameInstall,
+    journal::Journal,
+    local_install::LocalPackage,
+    mod_store::{Deduplicated, ModStore, STORE_NAME},
+};
+
+fn store_install(game: &GameDir) -> GameInstall {
+    GameInstall {
+        mod_store: Some(game.root.join(STORE_NAME)),
+        ..GameInstall::new(game.root.clone(), Arc::new(RealFs))
+    }
+}
+
+fn install_grapple(install: &GameInstall, source: &Path) {
+    let plan = LocalPackage::load(install, source)
+        .and_then(|package| package.plan(install, source))
+        .unwrap();
+    plan.install(install).unwrap();
+}
+
+fn file_id(path: &Path) -> Option<(u64, u64)> {
+    RealFs.metadata(path).unwrap().file_id
+}
+
+#[cfg(unix)]
+#[test]
+fn profiles_share_an_installed_mod_until_the_last_one_lets_go() {
+    let game = GameDir::new()
+        .with_file("downloads/Grapple/mod.json", &mod_json("Grapple", "1.0.0"))
+        .with_file("downloads/Grapple/mod/rope.nut", "rope");
+    let install = store_install(&game);
+    let store = ModStore::of(&install).unwrap();
+    let vanilla = install.with_profile("R2Vanilla");
+    let source = game.root.join("downloads/Grapple");
+
+    install_grapple(&install, &source);
+    install_grapple(&vanilla, &source);
+
+    let entries = store.entries(&install);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(
+        entries[0].refs,
+        [
+            install.mods_dir().join("Grapple"),
+            vanilla.mods_dir().join("Grapple")
+        ]
+    );
+    let stored = store.entry_dir(&entries[0].hash).join("mod/rope.nut");
+    assert_eq!(
+        file_id(&install.mods_dir().join("Grapple/mod/rope.nut")),
+        file_id(&stored)
+    );
+    assert_eq!(
+        file_id(&vanilla.mods_dir().join("Grapple/mod/rope.nut")),
+        file_id(&stored)
+    );
+
+    // removing it from one profile leaves the other one alone
+    fs::remove_dir_all(install.mods_dir().join("Grapple")).unwrap();
+    store
+        .release(&install, &install.mods_dir().join("Grapple"))
+        .unwrap();
+    assert_eq!(store.entries(&install)[0].refs.len(), 1);
+    assert_eq!(
+        fs::read_to_string(vanilla.mods_dir().join("Grapple/mod/rope.nut")).unwrap(),
+        "rope"
+    );
+
+    store.release(&vanilla, &vanilla.profile_dir()).unwrap();
+    assert!(store.entries(&install).is_empty());
+    assert!(!stored.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn writing_a_linked_file_leaves_the_other_profiles_alone() {
+    let game = GameDir::new()
+        .with_file("downloads/Grapple/mod.json", &mod_json("Grapple", "1.0.0"))
+        .with_file("downloads/Grapple/mod/rope.nut", "rope");
+    let install = store_install(&game);
+    let vanilla = install.with_profile("R2Vanilla");
+    let source = game.root.join("downloads/Grapple");
+    install_grapple(&install, &source);
+    install_grapple(&vanilla, &source);
+    let store = ModStore::of(&install).unwrap();
+    let stored = store
+        .entry_dir(&store.entries(&install)[0].hash)
+        .join("mod/rope.nut");
+
+    let edited = install.mods_dir().join("Grapple/mod/rope.nut");
+    install.write(&edited, b"frayed rope").unwrap();
+
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "frayed rope");
+    assert_ne!(file_id(&edited), file_id(&stored));
+    assert_eq!(
+        fs::read_to_string(vanilla.mods_dir().join("Grapple/mod/rope.nut")).unwrap(),
+        "rope"
+    );
+    assert_eq!(fs::read_to_string(&stored).unwrap(), "rope");
+    assert_eq!(
+        fs::read_dir(edited.parent().unwrap()).unwrap().count(),
+        1,
+        "no temp file is left behind"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn undoing_an_install_takes_it_out_of_the_store() {
+    let game =
+        GameDir::new().with_file("downloads/Grapple/mod.json", &mod_json("Grapple", "1.0.0"));
+    let install = store_install(&game);
+    let source = game.root.join("downloads/Grapple");
+    let mut journal = Journal::default();
+
+    journal
+        .run(&install, "install Grapple", |install| {
+            install_grapple(install, &source);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(ModStore::of(&install).unwrap().entries(&install).len(), 1);
+
+    journal.undo(&install, 1).unwrap();
+    assert!(!install.mods_dir().join("Grapple").exists());
+    assert!(!game.root.join(STORE_NAME).exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn deduplicating_links_the_copies_every_profile_has() {
+    let game = GameDir::new()
+        .with_mod("Grapple", "1.0.0", "a grapple")
+        .with_file("R2Northstar/mods/Grapple/rope.bin", &"r".repeat(1000))
+        .with_mod("Skins", "1.0.0", "only in one profile");
+    let install = store_install(&game);
+    let copy = |file: &str| {
+        let to = game.root.join("R2Vanilla/mods/Grapple").join(file);
+        fs::create_dir_all(to.parent().unwrap()).unwrap();
+        fs::copy(game.root.join("R2Northstar/mods/Grapple").join(file), to).unwrap();
+    };
+    ["mod.json", "thunderstore_author.txt", "rope.bin"]
+        .into_iter()
+        .for_each(copy);
+    let size = measure(&install)
+        .unwrap()
+        .profile("R2Vanilla")
+        .unwrap()
+        .total;
+    assert_eq!(measure(&install).unwrap().saved(), 0);
+
+    let store = ModStore::of(&install).unwrap();
+    assert_eq!(
+        store.deduplicate(&install).unwrap(),
+        Deduplicated {
+            mods: 3,
+            saved: size
+        }
+    );
+    assert_eq!(store.entries(&install).len(), 2);
+    assert_eq!(measure(&install).unwrap().saved(), size);
+    let linked: PathBuf = game.root.join("R2Vanilla/mods/Grapple/rope.bin");
+    assert_eq!(fs::read_to_string(linked).unwrap(), "r".repeat(1000));
+
+    // everything is linked already
+    assert_eq!(
+        store.deduplicate(&install).unwrap(),
+        Deduplicated::default()
+    );
+}
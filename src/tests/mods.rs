@@ -0,0 +1,57 @@
+//! toggling mods and switching profiles from the mods tab
+
+use std::fs;
+
+use crossterm::event::KeyCode;
+
+use super::{mod_json, GameDir, TestApp};
+
+fn enabled_mods(test_app: &TestApp, profile: &str) -> serde_json::Value {
+    let path = test_app.game_path(&format!("{profile}/enabledmods.json"));
+    serde_json::from_str(&fs::read_to_string(path).expect("enabledmods.json should be written"))
+        .expect("enabledmods.json should be json")
+}
+
+#[test]
+fn space_toggles_the_selected_mod() {
+    let mut test_app = TestApp::new(GameDir::new().with_mods(2));
+
+    let screen = test_app.keys("2").key(KeyCode::Right).keys(" ").screen();
+    assert!(screen.contains("disabled Mod 02"), "{screen}");
+    assert_eq!(enabled_mods(&test_app, "R2Northstar")["Mod 02"], false);
+    assert_ne!(enabled_mods(&test_app, "R2Northstar")["Mod 01"], false);
+
+    let screen = test_app.keys(" ").screen();
+    assert!(screen.contains("enabled Mod 02"), "{screen}");
+    assert_eq!(enabled_mods(&test_app, "R2Northstar")["Mod 02"], true);
+}
+
+#[test]
+fn only_mods_can_be_toggled() {
+    let mut test_app = TestApp::new(GameDir::new().with_plugin("DiscordRPC.dll"));
+
+    test_app.keys("2").key(KeyCode::Tab).keys(" ");
+    assert!(!test_app.game_path("R2Northstar/enabledmods.json").exists());
+}
+
+#[test]
+fn the_profile_key_cycles_through_the_profiles() {
+    let game = GameDir::new()
+        .with_mod("Grapple", "1.0.0", "a grapple")
+        .with_file("R2Vanilla/mods/Skins/mod.json", &mod_json("Skins", "1.0.0"));
+    let mut test_app = TestApp::new(game);
+
+    let screen = test_app.keys("2P").screen();
+    assert!(screen.contains("using the R2Vanilla profile"), "{screen}");
+    assert!(screen.contains("Skins"), "{screen}");
+    assert!(!screen.contains("Grapple"), "{screen}");
+
+    // toggles go to the profile in use
+    test_app.keys(" ");
+    assert_eq!(enabled_mods(&test_app, "R2Vanilla")["Skins"], false);
+    assert!(!test_app.game_path("R2Northstar/enabledmods.json").exists());
+
+    let screen = test_app.keys("P").screen();
+    assert!(screen.contains("using the R2Northstar profile"), "{screen}");
+    assert!(screen.contains("Grapple"), "{screen}");
+}
@@ -0,0 +1,28 @@
+//! toasts and the notification history
+
+use std::time::{Duration, SystemTime};
+
+use crate::notifications::{Level, Notification, NotificationCenter};
+
+fn aged(level: Level, message: &str, age: u64) -> Notification {
+    Notification {
+        created: SystemTime::now() - Duration::from_secs(age),
+        ..Notification::new(level, message)
+    }
+}
+
+#[test]
+fn an_expired_info_doesnt_hide_an_older_error() {
+    let mut notifications = NotificationCenter::default();
+    notifications.push(aged(Level::Error, "still live", 5));
+    notifications.push(aged(Level::Info, "expired", 4));
+    notifications.push(aged(Level::Warn, "also expired", 6));
+    notifications.push(aged(Level::Info, "new", 0));
+
+    let toasts = notifications
+        .toasts()
+        .map(|toast| toast.message.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(toasts, ["new", "still live"]);
+    assert_eq!(notifications.history().count(), 4);
+}
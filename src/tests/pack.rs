@@ -0,0 +1,198 @@
+//! thunderstore packages built from mod dirs in `MemoryFs`
+
+use std::{
+    fs,
+    io::{Cursor, Read},
+    path::Path,
+};
+
+use zip::ZipArchive;
+
+use super::{GameDir, TestApp, HEIGHT, WIDTH};
+use crate::{
+    config::Config,
+    game_fs::{GameFs, MemoryFs, RealFs},
+    pack::{pack, run_cli},
+};
+
+const MOD_DIR: &str = "/home/pilot/src/Grapple";
+
+/// just the header, that's all that is checked
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut png = vec![
+        0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', 0, 0, 0, 13,
+    ];
+    png.extend(b"IHDR");
+    png.extend(width.to_be_bytes());
+    png.extend(height.to_be_bytes());
+    png.extend([8, 6, 0, 0, 0]);
+
+    png
+}
+
+fn mod_dir() -> MemoryFs {
+    MemoryFs::default()
+        .with_file(
+            format!("{MOD_DIR}/mod.json"),
+            r#"{ "Name": "Pilot Grapple", "Description": "swing", "Version": "1.2.0", "LoadPriority": 1 }"#,
+        )
+        .with_file(format!("{MOD_DIR}/icon.png"), png(256, 256))
+        .with_file(format!("{MOD_DIR}/mod/scripts/grapple.nut"), "x")
+        .with_file(format!("{MOD_DIR}/mod/scripts/grapple.nut.bak"), "old")
+        .with_file(format!("{MOD_DIR}/docs/notes.txt"), "todo")
+        .with_file(format!("{MOD_DIR}/.git/HEAD"), "ref: refs/heads/main")
+        .with_file(format!("{MOD_DIR}/.packignore"), "# not shipped\n*.bak\ndocs/\n")
+}
+
+fn entries(bytes: &[u8]) -> Vec<(String, String)> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).expect("packages are zips");
+    (0..archive.len())
+        .map(|index| {
+            let mut file = archive.by_index(index).unwrap();
+            let mut contents = String::new();
+            _ = file.read_to_string(&mut contents);
+            (file.name().to_owned(), contents)
+        })
+        .collect()
+}
+
+#[test]
+fn missing_package_files_are_made_from_mod_json() {
+    let package = pack(&mod_dir(), Path::new(MOD_DIR)).unwrap();
+
+    assert_eq!(package.file_name, "Pilot_Grapple-1.2.0.zip");
+    assert_eq!(package.generated, ["manifest.json", "README.md"]);
+    let entries = entries(&package.bytes);
+    assert_eq!(
+        entries
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<&str>>(),
+        [
+            "manifest.json",
+            "README.md",
+            "icon.png",
+            "mods/Grapple/mod.json",
+            "mods/Grapple/mod/scripts/grapple.nut",
+        ]
+    );
+    assert_eq!(
+        entries[0].1,
+        r#"{
+  "dependencies": [],
+  "description": "swing",
+  "name": "Pilot_Grapple",
+  "version_number": "1.2.0",
+  "website_url": ""
+}"#
+    );
+    assert_eq!(entries[1].1, "# Pilot Grapple\n\nswing\n");
+}
+
+#[test]
+fn every_problem_points_at_its_file_and_field() {
+    let fs = mod_dir()
+        .with_file(
+            format!("{MOD_DIR}/manifest.json"),
+            r#"{ "name": "Pilot Grapple", "version_number": "1.2", "description": "swing",
+                 "dependencies": ["northstar-Northstar-1.0.0", "nope"] }"#,
+        )
+        .with_file(format!("{MOD_DIR}/icon.png"), png(128, 256));
+
+    assert_eq!(
+        pack(&fs, Path::new(MOD_DIR)).unwrap_err().to_string(),
+        format!(
+            "Grapple can't be packed:
+  {MOD_DIR}/manifest.json: `website_url` is missing
+  {MOD_DIR}/manifest.json: `name` \"Pilot Grapple\" can only have letters, numbers and underscores
+  {MOD_DIR}/manifest.json: `version_number` \"1.2\" isn't MAJOR.MINOR.PATCH
+  {MOD_DIR}/manifest.json: `dependencies[1]` \"nope\" isn't Author-Name-MAJOR.MINOR.PATCH
+  {MOD_DIR}/icon.png: is 128x256, it has to be 256x256"
+        )
+    );
+}
+
+#[test]
+fn icons_have_to_be_there_and_be_pngs() {
+    let fs = mod_dir();
+    fs.remove_file(&Path::new(MOD_DIR).join("icon.png"))
+        .unwrap();
+    assert!(pack(&fs, Path::new(MOD_DIR))
+        .unwrap_err()
+        .to_string()
+        .ends_with("icon.png is missing, thunderstore needs a 256x256 png"));
+
+    let fs = mod_dir().with_file(format!("{MOD_DIR}/icon.png"), "GIF89a");
+    assert!(pack(&fs, Path::new(MOD_DIR))
+        .unwrap_err()
+        .to_string()
+        .ends_with("icon.png: isn't a png"));
+}
+
+#[test]
+fn the_cli_writes_a_versioned_zip() {
+    let fs = mod_dir();
+
+    assert_eq!(
+        run_cli(
+            &[MOD_DIR.to_owned(), "--out".to_owned(), "/out".to_owned()],
+            &fs
+        )
+        .unwrap(),
+        "packed 2 files into /out/Pilot_Grapple-1.2.0.zip
+generated manifest.json and README.md from mod.json, add them to the mod to change them"
+    );
+    assert!(fs.exists(Path::new("/out/Pilot_Grapple-1.2.0.zip")));
+    assert!(run_cli(&[], &fs).is_err());
+}
+
+#[test]
+fn the_cli_packs_a_relative_dir_by_its_real_name() {
+    let game = GameDir::new().with_mod("Grapple", "1.0.0", "swing");
+    let out = game.root.join("out");
+    // what `drydoct pack ..` from `Grapple/mod` looks like
+    let mod_dir = game.root.join("R2Northstar/mods/Grapple/mod/..");
+    fs::create_dir_all(game.root.join("R2Northstar/mods/Grapple/mod")).unwrap();
+    fs::write(
+        game.root.join("R2Northstar/mods/Grapple/icon.png"),
+        png(256, 256),
+    )
+    .unwrap();
+
+    let report = run_cli(
+        &[
+            mod_dir.display().to_string(),
+            "--out".to_owned(),
+            out.display().to_string(),
+        ],
+        &RealFs,
+    )
+    .unwrap();
+    assert!(report.contains("Grapple-1.0.0.zip"), "{report}");
+    assert!(out.join("Grapple-1.0.0.zip").exists());
+}
+
+#[test]
+fn the_selected_mod_is_packed_from_the_mods_tab() {
+    let game = GameDir::new().with_mod("Grapple", "1.2.0", "swing");
+    fs::write(
+        game.root.join("R2Northstar/mods/Grapple/icon.png"),
+        png(256, 256),
+    )
+    .unwrap();
+    let pack_dir = game.root.join("packages");
+    let mut test_app = TestApp::with_config(
+        game,
+        Config {
+            pack_dir: Some(pack_dir.clone()),
+            ..Config::default()
+        },
+        WIDTH,
+        HEIGHT,
+    );
+
+    let screen = test_app.keys("2z").screen();
+
+    assert!(pack_dir.join("Grapple-1.2.0.zip").exists());
+    assert!(screen.contains("packed 2 files into"), "{screen}");
+}
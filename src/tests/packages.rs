@@ -0,0 +1,182 @@
+//! the query language of the packages view and the filters saved from it
+
+use std::collections::BTreeMap;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use super::{GameDir, TestApp};
+use crate::{
+    package_filter::{civil_date, load_saved, PackageFilter},
+    thunderstore::Package,
+};
+
+/// 2024-01-01
+const TODAY: u64 = 19_723;
+
+const PACKAGES: &str = r#"[
+    { "name": "Holo_Pilots", "owner": "someone", "categories": ["Skins", "Client-side"],
+      "date_updated": "2023-12-20T12:00:00Z",
+      "versions": [{ "version_number": "2.0.0", "download_url": "", "description": "pilots in holo", "downloads": 40000 }] },
+    { "name": "Grapple_Plus", "owner": "pilot", "categories": ["Mods", "Server-side"],
+      "date_updated": "2023-02-01T12:00:00Z",
+      "versions": [
+        { "version_number": "1.1.0", "download_url": "", "description": "a longer grapple", "downloads": 900 },
+        { "version_number": "1.0.0", "download_url": "", "description": "", "downloads": 200 }
+      ] },
+    { "name": "Old_Thing", "owner": "someone", "categories": ["Mods"], "is_deprecated": true,
+      "date_updated": "2021-01-01T12:00:00Z",
+      "versions": [{ "version_number": "0.1.0", "download_url": "", "description": "gone", "downloads": 10 }] },
+    { "name": "Spicy_Skins", "owner": "other", "categories": ["Skins"], "has_nsfw_content": true,
+      "date_updated": "2023-12-31T12:00:00Z",
+      "versions": [{ "version_number": "1.0.0", "download_url": "", "description": "", "downloads": 5000 }] }
+]"#;
+
+fn packages() -> Vec<Package> {
+    serde_json::from_str(PACKAGES).unwrap()
+}
+
+/// the names of the packages the query keeps, only `Holo_Pilots` counts as installed
+fn filtered(query: &str, saved: &BTreeMap<String, String>) -> Vec<String> {
+    let filter = PackageFilter::parse(query, saved, TODAY).unwrap();
+    packages()
+        .into_iter()
+        .filter(|package| filter.matches(package, package.name == "Holo_Pilots"))
+        .map(|package| package.name)
+        .collect()
+}
+
+#[test]
+fn every_key_narrows_the_packages_down() {
+    let saved = BTreeMap::new();
+
+    assert_eq!(filtered("", &saved), ["Holo_Pilots", "Grapple_Plus"]);
+    assert_eq!(
+        filtered("deprecated:show nsfw:show", &saved),
+        ["Holo_Pilots", "Grapple_Plus", "Old_Thing", "Spicy_Skins"]
+    );
+    assert_eq!(filtered("cat:skins cat:client", &saved), ["Holo_Pilots"]);
+    assert_eq!(
+        filtered("author:pilot author:someone deprecated:show", &saved),
+        ["Holo_Pilots", "Grapple_Plus", "Old_Thing"]
+    );
+    assert_eq!(filtered("updated:2023-06-01", &saved), ["Holo_Pilots"]);
+    assert_eq!(
+        filtered("updated:30d nsfw:show", &saved),
+        ["Holo_Pilots", "Spicy_Skins"]
+    );
+    assert_eq!(filtered("downloads:1.2k", &saved), ["Holo_Pilots"]);
+    assert_eq!(
+        filtered("downloads:1100", &saved),
+        ["Holo_Pilots", "Grapple_Plus"]
+    );
+    assert_eq!(filtered("installed:no", &saved), ["Grapple_Plus"]);
+    assert_eq!(filtered("\"longer grapple\"", &saved), ["Grapple_Plus"]);
+    assert_eq!(filtered("HOLO", &saved), ["Holo_Pilots"]);
+}
+
+#[test]
+fn saved_filters_expand_and_combine_with_the_rest_of_the_query() {
+    let saved = BTreeMap::from([
+        ("skins".to_owned(), "cat:skins nsfw:show".to_owned()),
+        ("popular".to_owned(), "@skins downloads:10k".to_owned()),
+        ("loop".to_owned(), "@again".to_owned()),
+        ("again".to_owned(), "@loop".to_owned()),
+    ]);
+
+    assert_eq!(filtered("@skins", &saved), ["Holo_Pilots", "Spicy_Skins"]);
+    assert_eq!(filtered("@popular", &saved), ["Holo_Pilots"]);
+    assert_eq!(filtered("@skins installed:no", &saved), ["Spicy_Skins"]);
+
+    let err = |query: &str| {
+        PackageFilter::parse(query, &saved, TODAY)
+            .unwrap_err()
+            .to_string()
+    };
+    assert_eq!(err("@loop"), "saved filters use each other in a loop");
+    assert_eq!(err("@nope"), "there is no saved filter called nope");
+    assert!(err("size:big").starts_with("unknown filter \"size\""));
+    assert!(err("updated:yesterday").contains("not \"yesterday\""));
+    assert!(err("installed:maybe").contains("yes or no"));
+}
+
+#[test]
+fn relative_dates_count_back_from_today() {
+    assert_eq!(civil_date(0), "1970-01-01");
+    assert_eq!(civil_date(TODAY), "2024-01-01");
+    assert_eq!(civil_date(TODAY + 59), "2024-02-29");
+    assert_eq!(
+        PackageFilter::parse("updated:1d", &BTreeMap::new(), TODAY)
+            .unwrap()
+            .updated_since
+            .as_deref(),
+        Some("2023-12-31")
+    );
+}
+
+#[test]
+fn a_filter_is_typed_saved_and_cycled_through() {
+    let game = GameDir::new()
+        .with_mod("Holo Pilots", "2.0.0", "")
+        .with_file("packages.json", PACKAGES);
+    let filters = game.root.join("filters.json");
+    let mut test_app = TestApp::new(game);
+
+    let screen = test_app.keys("2").repeat(KeyCode::Tab, 2).screen();
+    assert!(screen.contains("2/4 packages"), "{screen}");
+
+    test_app.keys("fcat:skins nsfw:show");
+    insta::assert_snapshot!("packages_filter", test_app.screen());
+
+    let screen = test_app
+        .key(KeyCode::Enter)
+        .keys("sskins")
+        .key(KeyCode::Enter)
+        .screen();
+    assert!(screen.contains("saved the filter as skins"), "{screen}");
+    assert_eq!(
+        load_saved(&filters).unwrap(),
+        BTreeMap::from([("skins".to_owned(), "cat:skins nsfw:show".to_owned())])
+    );
+
+    let screen = test_app.keys("c").screen();
+    assert!(screen.contains("2/4 packages"), "{screen}");
+    let screen = test_app.keys("F").screen();
+    assert!(screen.contains("filter @skins"), "{screen}");
+    assert!(screen.contains("Spicy_Skins"), "{screen}");
+
+    // typing follows along, escape puts the old query back
+    let screen = test_app.keys("f installed:no").screen();
+    assert!(screen.contains("1/4 packages"), "{screen}");
+    let screen = test_app.key(KeyCode::Esc).screen();
+    assert!(
+        screen.contains("filter @skins  search none  2/4 packages"),
+        "{screen}"
+    );
+}
+
+#[test]
+fn a_broken_index_is_only_fetched_again_on_reload() {
+    let game = GameDir::new().with_file("packages.json", "<html>");
+    let mut test_app = TestApp::new(game);
+
+    let screen = test_app.keys("2").repeat(KeyCode::Tab, 2).screen();
+    assert!(
+        screen.contains("couldn't load the package index"),
+        "{screen}"
+    );
+
+    std::fs::write(test_app.game_path("packages.json"), PACKAGES).unwrap();
+    let screen = test_app.keys("12").screen();
+    assert!(
+        screen.contains("couldn't load the package index"),
+        "{screen}"
+    );
+
+    let screen = test_app
+        .event(Event::Key(KeyEvent::new(
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL,
+        )))
+        .screen();
+    assert!(screen.contains("2/4 packages"), "{screen}");
+}
@@ -0,0 +1,385 @@
+//! plans for joining a server from a local package index and archives
+
+use std::{fs, path::Path, sync::Arc};
+
+use crossterm::event::KeyCode;
+
+use super::{install, mod_json, package_zip, zip, GameDir, TestApp, HEIGHT, ROOT, WIDTH};
+use crate::{
+    config::Config,
+    game_fs::{GameFs, MemoryFs, RealFs},
+    install::GameInstall,
+    master_server::ServerMod,
+    mods_views::reload_mods,
+    package_cache::PackageCache,
+    prepare::{parse_required_mods, PreparePlan, THROWAWAY_PROFILE},
+    thunderstore::{Package, PackageVersion},
+};
+
+const CACHE: &str = "/cache/packages";
+
+fn package(owner: &str, name: &str, versions: &[(&str, &[&str])]) -> Package {
+    Package {
+        name: name.to_owned(),
+        owner: owner.to_owned(),
+        versions: versions
+            .iter()
+            .map(|(version, dependencies)| PackageVersion {
+                version_number: (*version).to_owned(),
+                download_url: format!("/downloads/{owner}-{name}-{version}.zip"),
+                dependencies: dependencies.iter().map(|dep| (*dep).to_owned()).collect(),
+                ..PackageVersion::default()
+            })
+            .collect(),
+        ..Package::default()
+    }
+}
+
+fn index() -> Vec<Package> {
+    vec![
+        package(
+            "pilot",
+            "Grapple",
+            &[
+                ("2.0.0", &["northstar-Northstar-1.19.0", "pilot-Rope-1.0.0"]),
+                ("1.0.0", &[]),
+            ],
+        ),
+        package("pilot", "Rope", &[("1.0.0", &[])]),
+        package("pilot", "Anchor", &[("1.0.0", &[])]),
+    ]
+}
+
+fn required(mods: &[(&str, &str)]) -> Vec<ServerMod> {
+    mods.iter()
+        .map(|(name, version)| ServerMod {
+            name: (*name).to_owned(),
+            version: (*version).to_owned(),
+            required_on_client: true,
+            author: None,
+        })
+        .collect()
+}
+
+fn game() -> (Arc<MemoryFs>, GameInstall) {
+    let (fs, install) = install(
+        MemoryFs::default()
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Northstar.Custom/mod.json"),
+                mod_json("Northstar.Custom", "1.19.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Grapple/mod.json"),
+                mod_json("Grapple", "1.0.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Anchor/mod.json"),
+                mod_json("Anchor", "1.0.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Unrelated/mod.json"),
+                mod_json("Unrelated", "1.0.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Northstar/enabledmods.json"),
+                r#"{ "Anchor": false }"#,
+            ),
+    );
+    let cache = PackageCache::new(CACHE);
+    for (name, version) in [("Grapple", "2.0.0"), ("Rope", "1.0.0")] {
+        cache
+            .put(
+                &*fs,
+                &format!("pilot-{name}-{version}"),
+                &package_zip(name, version),
+            )
+            .unwrap();
+    }
+
+    (fs, install)
+}
+
+fn plan(install: &GameInstall) -> PreparePlan {
+    let installed = reload_mods(install).unwrap().0;
+    PreparePlan::new(
+        "Grapple Parkour",
+        &required(&[
+            ("Northstar.Custom", "1.19.0"),
+            ("Grapple", "2.0.0"),
+            ("Anchor", "1.0.0"),
+            ("Northstar.Coop", "1.0.0"),
+            ("Secret Sauce", "1.0.0"),
+        ]),
+        &installed,
+        &index(),
+    )
+}
+
+#[test]
+fn pasted_mods_can_be_a_server_its_mod_info_or_a_list() {
+    let mods = r#"[{ "Name": "Grapple", "Version": "2.0.0", "RequiredOnClient": true },
+                   { "Name": "Server.Only", "Version": "1.0.0", "RequiredOnClient": false }]"#;
+
+    assert_eq!(
+        parse_required_mods(&format!(
+            r#"{{ "name": "x", "modInfo": {{ "Mods": {mods} }} }}"#
+        ))
+        .unwrap(),
+        required(&[("Grapple", "2.0.0")])
+    );
+    assert_eq!(
+        parse_required_mods(&format!(r#"{{ "Mods": {mods} }}"#)).unwrap(),
+        required(&[("Grapple", "2.0.0")])
+    );
+    assert_eq!(
+        parse_required_mods(r#"[{ "Name": "Grapple", "Version": "2.0.0" }]"#).unwrap()[0].name,
+        "Grapple"
+    );
+    assert!(parse_required_mods(r#""Grapple""#).is_err());
+    assert!(parse_required_mods(r#"[{ "Version": "2.0.0" }]"#).is_err());
+}
+
+#[test]
+fn every_required_mod_gets_a_step() {
+    let (_, install) = game();
+
+    let steps = plan(&install)
+        .steps
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>();
+
+    assert_eq!(
+        steps,
+        [
+            "Northstar.Custom 1.19.0: ready",
+            "Grapple 2.0.0: install pilot-Grapple over 1.0.0",
+            "Anchor 1.0.0: enable",
+            "Northstar.Coop 1.0.0: comes with northstar, update it to get 1.0.0",
+            "Secret Sauce 1.0.0: not on thunderstore",
+            "Rope 1.0.0: install pilot-Rope for Grapple",
+        ]
+    );
+}
+
+#[test]
+fn plans_install_and_enable_in_the_current_profile() {
+    let (fs, install) = game();
+
+    plan(&install)
+        .fetch(&install, &PackageCache::new(CACHE), &*fs, false)
+        .unwrap()
+        .apply(&install)
+        .unwrap();
+
+    let mods = reload_mods(&install).unwrap().0;
+    let version = |name: &str| {
+        mods.iter()
+            .find(|nsmod| nsmod.mod_json.name == name)
+            .map(|nsmod| (nsmod.mod_json.version.as_str(), nsmod.enabled))
+    };
+    assert_eq!(version("Grapple"), Some(("2.0.0", true)));
+    assert_eq!(version("Rope"), Some(("1.0.0", true)));
+    assert_eq!(version("Anchor"), Some(("1.0.0", true)));
+    assert_eq!(
+        fs.read_to_string(Path::new(&format!(
+            "{ROOT}/R2Northstar/mods/Rope/thunderstore_author.txt"
+        )))
+        .unwrap(),
+        "pilot"
+    );
+}
+
+#[test]
+fn throwaway_profiles_leave_the_current_one_alone() {
+    let (fs, install) = game();
+
+    plan(&install)
+        .fetch(&install, &PackageCache::new(CACHE), &*fs, true)
+        .unwrap()
+        .apply(&install)
+        .unwrap();
+
+    let throwaway = install.with_profile(THROWAWAY_PROFILE);
+    let names = reload_mods(&throwaway)
+        .unwrap()
+        .0
+        .into_iter()
+        .map(|nsmod| (nsmod.mod_json.name, nsmod.mod_json.version))
+        .collect::<Vec<(String, String)>>();
+    assert_eq!(
+        names,
+        [
+            ("Anchor".to_owned(), "1.0.0".to_owned()),
+            ("Grapple".to_owned(), "2.0.0".to_owned()),
+            ("Northstar.Custom".to_owned(), "1.19.0".to_owned()),
+            ("Rope".to_owned(), "1.0.0".to_owned()),
+        ]
+    );
+    assert_eq!(
+        fs.read_to_string(Path::new(&format!(
+            "{ROOT}/R2Northstar/mods/Grapple/mod.json"
+        )))
+        .unwrap(),
+        mod_json("Grapple", "1.0.0")
+    );
+}
+
+#[test]
+fn archives_that_cant_be_downloaded_stop_the_plan() {
+    let (fs, install) = game();
+    PackageCache::new(CACHE)
+        .remove(&*fs, "pilot-Rope-1.0.0")
+        .unwrap();
+
+    let err = plan(&install)
+        .fetch(&install, &PackageCache::new(CACHE), &*fs, false)
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "couldn't download pilot-Rope-1.0.0.zip");
+}
+
+#[test]
+fn a_server_is_prepared_from_the_servers_tab() {
+    let game = GameDir::new()
+        .with_mod("Northstar.Custom", "1.19.0", "")
+        .with_mod("Grapple", "1.0.0", "")
+        .with_file(
+            "servers.json",
+            r#"[{ "name": "Grapple Parkour", "map": "mp_glitch", "playerCount": 1, "maxPlayers": 8,
+                  "modInfo": { "Mods": [
+                    { "Name": "Northstar.Custom", "Version": "1.19.0", "RequiredOnClient": true },
+                    { "Name": "Grapple", "Version": "2.0.0", "RequiredOnClient": true }
+                  ] } }]"#,
+        );
+    let downloads = game.root.join("downloads");
+    fs::create_dir_all(&downloads).unwrap();
+    fs::write(
+        downloads.join("Grapple-2.0.0.zip"),
+        package_zip("Grapple", "2.0.0"),
+    )
+    .unwrap();
+    fs::write(
+        game.root.join("index.json"),
+        format!(
+            r#"[{{ "name": "Grapple", "owner": "pilot", "is_deprecated": false, "versions": [
+                  {{ "version_number": "2.0.0", "download_url": "{}", "dependencies": [] }}
+                ] }}]"#,
+            downloads.join("Grapple-2.0.0.zip").display()
+        ),
+    )
+    .unwrap();
+    let config = Config {
+        master_server: Some(game.root.join("servers.json").display().to_string()),
+        package_index: Some(game.root.join("index.json").display().to_string()),
+        package_cache: Some(game.root.join("cache")),
+        ..Config::default()
+    };
+    let cache = PackageCache::new(game.root.join("cache"));
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    test_app.keys("4p");
+    insta::assert_snapshot!("prepare", test_app.screen());
+
+    let screen = test_app.keys("t").key(KeyCode::Enter).screen();
+    assert!(
+        screen.contains(&format!("using the {THROWAWAY_PROFILE} profile")),
+        "{screen}"
+    );
+    let cached = cache.entries(&RealFs);
+    assert_eq!(cached[0].key, "pilot-Grapple-2.0.0");
+    assert!(cache.archive_path(&cached[0]).exists());
+    assert!(test_app
+        .game_path(&format!("{THROWAWAY_PROFILE}/mods/Grapple/mod.json"))
+        .exists());
+    assert!(
+        !screen.contains("1 missing"),
+        "the throwaway profile has everything: {screen}"
+    );
+}
+
+#[test]
+fn a_failed_download_keeps_the_plan_up() {
+    let game = GameDir::new()
+        .with_mod("Northstar.Custom", "1.19.0", "")
+        .with_file(
+            "servers.json",
+            r#"[{ "name": "Grapple Parkour", "map": "mp_glitch", "playerCount": 1, "maxPlayers": 8,
+                  "modInfo": { "Mods": [
+                    { "Name": "Grapple", "Version": "2.0.0", "RequiredOnClient": true }
+                  ] } }]"#,
+        )
+        .with_file(
+            "index.json",
+            r#"[{ "name": "Grapple", "owner": "pilot", "is_deprecated": false, "versions": [
+                  { "version_number": "2.0.0", "download_url": "/nowhere/Grapple-2.0.0.zip", "dependencies": [] }
+                ] }]"#,
+        );
+    let config = Config {
+        master_server: Some(game.root.join("servers.json").display().to_string()),
+        package_index: Some(game.root.join("index.json").display().to_string()),
+        package_cache: Some(game.root.join("cache")),
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    let screen = test_app.keys("4p").key(KeyCode::Enter).screen();
+    assert!(screen.contains("prepare for Grapple Parkour"), "{screen}");
+    assert!(
+        screen.contains("couldn't download pilot-Grapple-2.0.0.zip"),
+        "{screen}"
+    );
+    assert!(!test_app.game_path("R2Northstar/mods/Grapple").exists());
+}
+
+#[test]
+fn an_update_in_another_dir_replaces_the_old_one() {
+    let (fs, install) = game();
+    PackageCache::new(CACHE)
+        .put(
+            &*fs,
+            "pilot-Grapple-2.0.0",
+            &zip(&[("mods/pilot.Grapple/mod.json", &mod_json("Grapple", "2.0.0"))]),
+        )
+        .unwrap();
+
+    plan(&install)
+        .fetch(&install, &PackageCache::new(CACHE), &*fs, false)
+        .unwrap()
+        .apply(&install)
+        .unwrap();
+
+    let grapples = reload_mods(&install)
+        .unwrap()
+        .0
+        .into_iter()
+        .filter(|nsmod| nsmod.mod_json.name == "Grapple")
+        .map(|nsmod| nsmod.mod_json.version)
+        .collect::<Vec<String>>();
+    assert_eq!(grapples, ["2.0.0"]);
+    assert!(!fs.exists(Path::new(&format!("{ROOT}/R2Northstar/mods/Grapple"))));
+}
+
+#[test]
+fn a_listed_author_picks_the_package() {
+    let (_, install) = game();
+    let installed = reload_mods(&install).unwrap().0;
+    let mut index = index();
+    index.insert(0, package("copycat", "Grapple", &[("2.0.0", &[])]));
+    let step = |author: Option<&str>| {
+        let required = ServerMod {
+            author: author.map(str::to_owned),
+            ..required(&[("Grapple", "2.0.0")]).remove(0)
+        };
+        PreparePlan::new("Grapple Parkour", &[required], &installed, &index).steps[0].to_string()
+    };
+
+    assert_eq!(
+        step(None),
+        "Grapple 2.0.0: install copycat-Grapple over 1.0.0"
+    );
+    assert_eq!(
+        step(Some("pilot")),
+        "Grapple 2.0.0: install pilot-Grapple over 1.0.0"
+    );
+}
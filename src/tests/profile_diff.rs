@@ -0,0 +1,230 @@
+//! two profiles or a profile and a mod list compared and made to agree
+
+use super::{
+    install, mod_json, mod_json_with_priority, package_zip, GameDir, TestApp, HEIGHT, ROOT, WIDTH,
+};
+use crate::{
+    config::Config,
+    game_fs::{GameFs, MemoryFs},
+    profile_diff::{copy_between, diff, DiffKind, Difference, ListedMod, ListedPlugin, ModList},
+};
+
+fn listed(name: &str, version: &str, enabled: bool, load_priority: i32) -> ListedMod {
+    ListedMod {
+        name: name.to_owned(),
+        version: version.to_owned(),
+        enabled,
+        load_priority: Some(load_priority),
+    }
+}
+
+fn difference(kind: DiffKind, name: &str, a: Option<&str>, b: Option<&str>) -> Difference {
+    Difference {
+        kind,
+        name: name.to_owned(),
+        a: a.map(str::to_owned),
+        b: b.map(str::to_owned),
+    }
+}
+
+#[test]
+fn every_kind_of_difference_is_listed() {
+    let a = ModList {
+        profile: "a".to_owned(),
+        mods: vec![
+            listed("Grapple", "1.0.0", true, 1),
+            listed("Skins", "2.0.0", true, 1),
+        ],
+        plugins: vec![ListedPlugin {
+            name: "Discord.dll".to_owned(),
+            sha256: "abc".to_owned(),
+        }],
+    };
+    let b = ModList {
+        profile: "b".to_owned(),
+        mods: vec![
+            listed("Grapple", "1.1.0", false, 5),
+            listed("Anchor", "1.0.0", true, 1),
+        ],
+        plugins: Vec::new(),
+    };
+
+    assert_eq!(
+        diff(&a, &b),
+        [
+            difference(DiffKind::Mod, "Anchor", None, Some("1.0.0")),
+            difference(DiffKind::Version, "Grapple", Some("1.0.0"), Some("1.1.0")),
+            difference(
+                DiffKind::Enabled,
+                "Grapple",
+                Some("enabled"),
+                Some("disabled")
+            ),
+            difference(DiffKind::LoadPriority, "Grapple", Some("1"), Some("5")),
+            difference(DiffKind::Mod, "Skins", Some("2.0.0"), None),
+            difference(DiffKind::Plugin, "Discord.dll", Some("abc"), None),
+        ]
+    );
+    assert!(diff(&a, &a).is_empty());
+}
+
+#[test]
+fn copying_every_difference_makes_the_profiles_agree() {
+    let (fs, install) = install(
+        MemoryFs::default()
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Grapple/mod.json"),
+                mod_json_with_priority("Grapple", "1.1.0", 2),
+            )
+            .with_file(
+                format!("{ROOT}/R2Northstar/mods/Skins/mod.json"),
+                mod_json("Skins", "1.0.0"),
+            )
+            .with_file(format!("{ROOT}/R2Northstar/plugins/Discord.dll"), "new")
+            .with_file(
+                format!("{ROOT}/R2Vanilla/mods/Grapple/mod.json"),
+                mod_json("Grapple", "1.0.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Vanilla/mods/Anchor/mod.json"),
+                mod_json("Anchor", "1.0.0"),
+            )
+            .with_file(
+                format!("{ROOT}/R2Vanilla/enabledmods.json"),
+                r#"{ "Grapple": false }"#,
+            )
+            .with_file(format!("{ROOT}/R2Vanilla/plugins/Discord.dll"), "old"),
+    );
+    let vanilla = install.with_profile("R2Vanilla");
+
+    let differences = diff(
+        &ModList::of(&install).unwrap(),
+        &ModList::of(&vanilla).unwrap(),
+    );
+    assert_eq!(differences.len(), 6, "{differences:#?}");
+    for difference in &differences {
+        copy_between(&install, &vanilla, difference).unwrap();
+    }
+
+    assert!(diff(
+        &ModList::of(&install).unwrap(),
+        &ModList::of(&vanilla).unwrap()
+    )
+    .is_empty());
+    assert!(!fs.exists(&vanilla.mods_dir().join("Anchor")));
+    assert_eq!(
+        fs.read_to_string(&vanilla.plugins_dir().join("Discord.dll"))
+            .unwrap(),
+        "new"
+    );
+}
+
+#[test]
+fn the_diff_section_compares_with_other_profiles_and_exported_lists() {
+    let game = GameDir::new()
+        .with_mod("Grapple", "1.0.0", "a grapple")
+        .with_mod("Skins", "1.0.0", "skins")
+        .with_file(
+            "R2Vanilla/mods/Grapple/mod.json",
+            &mod_json("Grapple", "1.2.0"),
+        );
+    let pack_dir = game.root.join("lists");
+    let config = Config {
+        pack_dir: Some(pack_dir.clone()),
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    test_app.keys("3").repeat(crossterm::event::KeyCode::Tab, 6);
+    insta::assert_snapshot!("profile_diff", test_app.screen());
+
+    // the newer grapple comes over from the other profile
+    let screen = test_app.keys("h").screen();
+    assert!(!screen.contains("1.2.0"), "{screen}");
+    assert!(screen.contains("Skins"), "{screen}");
+
+    test_app.keys("e");
+    assert!(pack_dir.join("R2Northstar.modlist.json").exists());
+    let screen = test_app.keys("P").screen();
+    assert!(
+        screen.contains("R2Northstar.modlist.json (2/2)"),
+        "{screen}"
+    );
+    assert!(screen.contains("no differences"), "{screen}");
+}
+
+#[test]
+fn mods_copied_from_a_list_are_downloaded_in_the_background() {
+    let game = GameDir::new()
+        .with_file(
+            "lists/friend.modlist.json",
+            r#"{ "profile": "friend", "mods": [{ "name": "Grapple", "version": "2.0.0", "enabled": true }] }"#,
+        )
+        .with_file(
+            "index.json",
+            r#"[{ "name": "Grapple", "owner": "pilot", "is_deprecated": false, "versions": [
+                  { "version_number": "2.0.0", "download_url": "/nowhere/Grapple-2.0.0.zip", "dependencies": [] }
+                ] }]"#,
+        );
+    let config = Config {
+        pack_dir: Some(game.root.join("lists")),
+        package_index: Some(game.root.join("index.json").display().to_string()),
+        package_cache: Some(game.root.join("cache")),
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    let screen = test_app
+        .keys("3")
+        .repeat(crossterm::event::KeyCode::Tab, 6)
+        .keys("h")
+        .screen();
+    assert!(screen.contains("couldn't copy the"), "{screen}");
+    assert!(screen.contains("Grapple"), "the difference stays: {screen}");
+    assert!(!test_app.game_path("R2Northstar/mods/Grapple").exists());
+}
+
+#[test]
+fn mods_copied_from_a_list_are_installed_with_their_listed_state() {
+    let game = GameDir::new().with_file(
+        "lists/friend.modlist.json",
+        r#"{ "profile": "friend", "mods": [{ "name": "Grapple", "version": "2.0.0", "enabled": false }] }"#,
+    );
+    let archive = game.root.join("downloads/Grapple-2.0.0.zip");
+    std::fs::create_dir_all(archive.parent().unwrap()).unwrap();
+    std::fs::write(&archive, package_zip("Grapple", "2.0.0")).unwrap();
+    let game = game.with_file(
+        "index.json",
+        &format!(
+            r#"[{{ "name": "Grapple", "owner": "pilot", "is_deprecated": false, "versions": [
+                  {{ "version_number": "2.0.0", "download_url": "{}", "dependencies": [] }}
+                ] }}]"#,
+            archive.display()
+        ),
+    );
+    let config = Config {
+        pack_dir: Some(game.root.join("lists")),
+        package_index: Some(game.root.join("index.json").display().to_string()),
+        package_cache: Some(game.root.join("cache")),
+        ..Config::default()
+    };
+    let mut test_app = TestApp::with_config(game, config, WIDTH, HEIGHT);
+
+    let screen = test_app
+        .keys("3")
+        .repeat(crossterm::event::KeyCode::Tab, 6)
+        .keys("h")
+        .screen();
+    // a mod list doesn't know load priorities
+    assert!(screen.contains("load priority"), "{screen}");
+    assert!(!screen.contains("missing"), "{screen}");
+    assert!(test_app
+        .game_path("R2Northstar/mods/Grapple/mod.json")
+        .exists());
+    let enabled_mods =
+        std::fs::read_to_string(test_app.game_path("R2Northstar/enabledmods.json")).unwrap();
+    assert!(
+        enabled_mods.contains(r#""Grapple": false"#),
+        "{enabled_mods}"
+    );
+}
@@ -0,0 +1,226 @@
+use crossterm::event::{KeyCode, MouseEventKind};
+use insta::assert_snapshot;
+
+use super::{GameDir, TestApp};
+use crate::theme::theme;
+
+const LOG: &str = "\
+[12:00:00] [NORTHSTAR] [info] NorthstarLauncher version: 1.20.0
+[12:00:01] [NORTHSTAR] [info] Loaded mod Mod 01
+[12:00:02] [SCRIPT UI] [error] SCRIPT ERROR: [UI] Index 'foo' does not exist
+    at function Broken() in mod/scripts/vscripts/mod01.nut
+[12:00:03] [NORTHSTAR] [warning] something looks off
+";
+
+const PACKAGES: &str = r#"[
+    { "name": "Mod_01", "owner": "fixture", "categories": ["Mods"], "date_updated": "2024-03-01T12:00:00Z",
+      "versions": [{ "version_number": "1.1.0", "download_url": "", "description": "the first fixture mod", "downloads": 1200 }] },
+    { "name": "Holo_Pilots", "owner": "someone", "categories": ["Skins", "Client-side"], "date_updated": "2023-06-15T12:00:00Z",
+      "versions": [{ "version_number": "2.0.0", "download_url": "", "description": "pilots in holo", "downloads": 40000 }] },
+    { "name": "Old_Thing", "owner": "someone", "is_deprecated": true, "date_updated": "2021-01-01T12:00:00Z",
+      "versions": [{ "version_number": "0.1.0", "download_url": "", "description": "gone", "downloads": 10 }] }
+]"#;
+
+fn game() -> GameDir {
+    GameDir::new()
+        .with_mods(30)
+        .with_plugin("DiscordRPC.dll")
+        .with_plugin("NorthstarRpc.dll")
+        .with_file("packages.json", PACKAGES)
+}
+
+/// whether the card whose title starts with `title` has the selected border
+fn is_selected(test_app: &TestApp, title: &str) -> bool {
+    let (column, row) = test_app
+        .find(title)
+        .unwrap_or_else(|| panic!("\"{title}\" isn't on screen:\n{}", test_app.screen()));
+
+    // the title sits one cell right of the card's corner
+    test_app.fg(column - 1, row) == theme().selected
+}
+
+#[test]
+fn main_menu() {
+    let mut test_app = TestApp::new(game());
+    assert_snapshot!("main_menu", test_app.screen());
+
+    test_app.keys("p");
+    assert_snapshot!("main_menu_playing", test_app.screen());
+}
+
+#[test]
+fn mods_views() {
+    let mut test_app = TestApp::new(game());
+    test_app.keys("2");
+    assert_snapshot!("mods_mods", test_app.screen());
+
+    test_app.key(KeyCode::Tab);
+    assert_snapshot!("mods_plugins", test_app.screen());
+
+    test_app.key(KeyCode::Tab);
+    assert_snapshot!("mods_packages", test_app.screen());
+
+    test_app.key(KeyCode::Tab);
+    assert_snapshot!("mods_cta", test_app.screen());
+
+    test_app.key(KeyCode::Tab);
+    assert_eq!(
+        test_app.screen(),
+        TestApp::new(game()).keys("2").screen(),
+        "the views should wrap around"
+    );
+}
+
+#[test]
+fn mods_paging_stops_at_the_last_page() {
+    let mut test_app = TestApp::new(game());
+    test_app.keys("2n");
+    assert_snapshot!("mods_second_page", test_app.screen());
+    assert!(is_selected(&test_app, "Mod 25"));
+
+    let last_page = test_app.screen();
+    test_app.keys("n");
+    assert_eq!(test_app.screen(), last_page);
+
+    test_app.keys("pp");
+    assert!(test_app.find("Mod 01").is_some());
+    assert!(is_selected(&test_app, "Mod 01"));
+}
+
+#[test]
+fn mods_selection_moves_between_pages() {
+    let mut test_app = TestApp::new(game());
+    test_app.keys("2");
+    let first_page = test_app.screen();
+
+    // 4 columns and 6 rows at 120x40
+    test_app.repeat(KeyCode::Right, 23);
+    assert_eq!(test_app.screen(), first_page);
+    assert!(is_selected(&test_app, "Mod 24"));
+
+    test_app.key(KeyCode::Right);
+    assert!(is_selected(&test_app, "Mod 25"));
+    assert!(test_app.find("Mod 01").is_none());
+
+    // moving down past the last mod stops at it
+    test_app.repeat(KeyCode::Down, 3);
+    assert!(is_selected(&test_app, "Mod 30"));
+
+    test_app.key(KeyCode::Up);
+    assert!(is_selected(&test_app, "Mod 26"));
+
+    test_app.repeat(KeyCode::Up, 1);
+    assert!(is_selected(&test_app, "Mod 22"));
+    assert!(test_app.find("Mod 01").is_some());
+}
+
+#[test]
+fn mods_grid_follows_the_terminal_size() {
+    let mut test_app = TestApp::new(game());
+    test_app.keys("2").repeat(KeyCode::Right, 20);
+    assert!(is_selected(&test_app, "Mod 21"));
+
+    // 2 columns and 2 rows, the selection stays on screen
+    test_app.resize(70, 20);
+    assert_snapshot!("mods_small", test_app.screen());
+    assert!(is_selected(&test_app, "Mod 21"));
+
+    test_app.resize(50, 20);
+    assert_snapshot!("mods_compact", test_app.screen());
+
+    // shorter than a card used to underflow the side bar
+    test_app.resize(120, 5);
+    test_app.resize(120, 3);
+}
+
+#[test]
+fn mods_details() {
+    let mut test_app = TestApp::new(game());
+    test_app.keys("2").key(KeyCode::Right).key(KeyCode::Enter);
+    assert_snapshot!("mods_details", test_app.screen());
+
+    test_app.key(KeyCode::Enter);
+    assert!(test_app.find("load priority").is_none());
+}
+
+#[test]
+fn mouse() {
+    let mut test_app = TestApp::new(game());
+    test_app.click_text("MODS(2)");
+    assert_eq!(test_app.screen(), TestApp::new(game()).keys("2").screen());
+
+    test_app.click_text("Mod 06");
+    assert!(is_selected(&test_app, "Mod 06"));
+
+    test_app.mouse(MouseEventKind::ScrollDown, 60, 20);
+    assert!(is_selected(&test_app, "Mod 25"));
+    test_app.mouse(MouseEventKind::ScrollUp, 60, 20);
+    assert!(is_selected(&test_app, "Mod 01"));
+
+    test_app.click_text("Plugins");
+    assert!(test_app.find("DiscordRPC.dll").is_some());
+
+    test_app.click_text("UTILS(3)");
+    test_app.click_text("ConVars");
+    assert!(test_app.find("convars (").is_some());
+}
+
+#[test]
+fn utils_logs() {
+    let mut test_app = TestApp::new(
+        game()
+            .with_file(
+                "R2Northstar/mods/Mod 01/mod/scripts/vscripts/mod01.nut",
+                "void function Broken() {}",
+            )
+            .with_log("nslog2023-11-20 12-00-00.txt", LOG, 1_700_481_600),
+    );
+    test_app.keys("3");
+    assert_snapshot!("utils_logs", test_app.screen());
+
+    test_app.key(KeyCode::Enter);
+    assert_snapshot!("utils_logs_opened", test_app.screen());
+}
+
+#[test]
+fn utils_convars() {
+    let mut test_app = TestApp::new(game().with_mod_json(
+        "Mod Settings",
+        r#"{
+    "Name": "Mod Settings",
+    "Description": "declares convars",
+    "Version": "1.0.0",
+    "LoadPriority": 1,
+    "RequiredOnClient": false,
+    "ConVars": [
+        { "Name": "everything_unlocked", "DefaultValue": "1", "HelpString": "unlocks it all" },
+        { "Name": "fixture_enabled", "DefaultValue": "1", "HelpString": "turns it on" },
+        { "Name": "fixture_speed", "DefaultValue": "1.5" },
+        { "Name": "fixture_name", "DefaultValue": "pilot" }
+    ],
+    "Scripts": [],
+    "Localisation": []
+}"#,
+    ));
+    test_app.keys("3").key(KeyCode::Tab);
+    assert_snapshot!("utils_convars", test_app.screen());
+
+    // toggling a bool writes it to the client autoexec
+    test_app.key(KeyCode::Enter);
+    assert_snapshot!("utils_convars_toggled", test_app.screen());
+
+    // a 0 or 1 default doesn't make a convar a bool unless it's a known switch
+    let screen = test_app.keys("j").key(KeyCode::Enter).keys("5").screen();
+    assert!(screen.contains("editing fixture_enabled (int)"), "{screen}");
+    assert!(test_app.key(KeyCode::Enter).screen().contains("15"));
+
+    // a typo keeps what was typed
+    let screen = test_app
+        .keys("jj")
+        .key(KeyCode::Enter)
+        .keys(".5")
+        .key(KeyCode::Enter)
+        .screen();
+    assert!(screen.contains("isn't a valid float"), "{screen}");
+    assert!(screen.contains("1.5.5_"), "{screen}");
+}
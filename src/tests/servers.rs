@@ -0,0 +1,179 @@
+//! the server browser reading a local stand-in for the master server
+
+use std::sync::Arc;
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use super::{GameDir, TestApp, HEIGHT, WIDTH};
+use crate::{
+    config::Config,
+    game_fs::RealFs,
+    install::GameInstall,
+    master_server::{fetch_servers, ModStatus, ServerMod},
+    mods_views::reload_mods,
+};
+
+const SERVERS: &str = r#"[
+  {
+    "id": "1",
+    "name": "Frontier Defense",
+    "description": "bring friends",
+    "map": "mp_forwardbase_kodai",
+    "playlist": "fd",
+    "region": "EU West",
+    "playerCount": 2,
+    "maxPlayers": 4,
+    "hasPassword": false,
+    "modInfo": {
+      "Mods": [
+        { "Name": "Northstar.Custom", "Version": "1.19.0", "RequiredOnClient": true },
+        { "Name": "Northstar.CustomServers", "Version": "1.19.0", "RequiredOnClient": false }
+      ]
+    }
+  },
+  {
+    "id": "2",
+    "name": "Grapple Parkour",
+    "description": "swing from the anchors",
+    "map": "mp_glitch",
+    "playlist": "ps",
+    "region": "US East",
+    "playerCount": 12,
+    "maxPlayers": 16,
+    "hasPassword": false,
+    "modInfo": {
+      "Mods": [
+        { "Name": "Northstar.Custom", "Version": "1.19.0", "RequiredOnClient": true },
+        { "Name": "Grapple", "Version": "2.0.0", "RequiredOnClient": true },
+        { "Name": "Anchor", "Version": "1.0.0", "RequiredOnClient": true }
+      ]
+    }
+  },
+  {
+    "id": "3",
+    "name": "Private Scrims",
+    "description": "",
+    "map": "mp_angel_city",
+    "playlist": "aitdm",
+    "region": "AU",
+    "playerCount": 8,
+    "maxPlayers": 8,
+    "hasPassword": true,
+    "lastHeartbeat": 1700000000
+  }
+]"#;
+
+fn game() -> GameDir {
+    GameDir::new()
+        .with_mod("Northstar.Custom", "1.19.0", "")
+        .with_mod("Grapple", "1.0.0", "")
+        .with_file("servers.json", SERVERS)
+}
+
+fn test_app(game: GameDir) -> TestApp {
+    let master_server = game.root.join("servers.json").display().to_string();
+    TestApp::with_config(
+        game,
+        Config {
+            master_server: Some(master_server),
+            ..Config::default()
+        },
+        WIDTH,
+        HEIGHT,
+    )
+}
+
+#[test]
+fn required_mods_are_compared_to_the_profile() {
+    let game = game();
+    let servers = fetch_servers(&game.root.join("servers.json").display().to_string()).unwrap();
+    let installed = reload_mods(&GameInstall::new(game.root.clone(), Arc::new(RealFs)))
+        .unwrap()
+        .0;
+
+    assert_eq!(servers.len(), 3);
+    assert_eq!(
+        servers[1]
+            .required_mods()
+            .map(|server_mod| ModStatus::of(server_mod, &installed))
+            .collect::<Vec<ModStatus>>(),
+        [
+            ModStatus::Installed,
+            ModStatus::OtherVersion("1.0.0".to_owned()),
+            ModStatus::Missing
+        ]
+    );
+    assert_eq!(servers[0].required_mods().count(), 1);
+    assert!(servers[2].is_full());
+    assert_eq!(
+        ModStatus::of(
+            &ServerMod {
+                name: "Nope".to_owned(),
+                ..ServerMod::default()
+            },
+            &installed
+        ),
+        ModStatus::Missing
+    );
+}
+
+#[test]
+fn a_broken_server_list_is_an_error() {
+    let game = GameDir::new().with_file("servers.json", "<html>");
+
+    let err = fetch_servers(&game.root.join("servers.json").display().to_string()).unwrap_err();
+
+    assert!(
+        err.to_string().ends_with("didn't return a server list"),
+        "{err}"
+    );
+}
+
+#[test]
+fn servers_are_listed_with_missing_mods() {
+    let mut test_app = test_app(game());
+
+    test_app.keys("4");
+
+    insta::assert_snapshot!("servers", test_app.screen());
+}
+
+#[test]
+fn a_failed_list_is_shown_and_only_fetched_again_on_reload() {
+    let mut test_app = test_app(game().with_file("servers.json", "<html>"));
+
+    let screen = test_app.keys("4").screen();
+    assert!(screen.contains("servers - couldn't load them"), "{screen}");
+    assert!(screen.contains("couldn't load the servers"), "{screen}");
+
+    std::fs::write(test_app.game_path("servers.json"), SERVERS).unwrap();
+    let screen = test_app.keys("14").screen();
+    assert!(screen.contains("couldn't load them"), "{screen}");
+
+    let screen = test_app
+        .event(Event::Key(KeyEvent::new(
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL,
+        )))
+        .screen();
+    assert!(screen.contains("servers (3/3)"), "{screen}");
+}
+
+#[test]
+fn servers_are_sorted_and_filtered() {
+    let mut test_app = test_app(game());
+    test_app.keys("4");
+
+    let screen = test_app.keys("s").screen();
+    assert!(screen.contains("sorted by name"), "{screen}");
+    let frontier = screen.find("Frontier Defense").unwrap();
+    assert!(frontier < screen.find("Grapple Parkour").unwrap());
+
+    let screen = test_app.keys("f").screen();
+    assert!(screen.contains("servers (2/3)"), "{screen}");
+    assert!(!screen.contains("Private Scrims"), "{screen}");
+
+    let screen = test_app.keys("/swing").key(KeyCode::Enter).screen();
+    assert!(screen.contains("servers (1/3)"), "{screen}");
+    assert!(screen.contains("Grapple Parkour"), "{screen}");
+}
@@ -0,0 +1,327 @@
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr};
+
+use anyhow::{anyhow, bail, Context, Ok, Result as AnyResult};
+use once_cell::sync::OnceCell;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::config::config_dir;
+
+pub const BUILTIN_THEMES: [&str; 3] = ["dark", "light", "high-contrast"];
+
+static THEME: OnceCell<Theme> = OnceCell::new();
+
+/// the colors every `render_ui` picks from, named by what they are used for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Color,
+    /// bars and highlighted rows drawn on top of the background
+    pub surface: Color,
+    pub text: Color,
+    /// secondary text like paths, timestamps and types
+    pub muted: Color,
+    pub border: Color,
+    /// titles and headers
+    pub accent: Color,
+    pub selected: Color,
+    pub error: Color,
+    pub warn: Color,
+    pub info: Color,
+    pub enabled: Color,
+    pub disabled: Color,
+    pub outdated: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            background: Color::Rgb(41, 41, 41),
+            surface: Color::Rgb(24, 24, 24),
+            text: Color::Rgb(235, 235, 235),
+            muted: Color::Gray,
+            border: Color::Rgb(69, 69, 69),
+            accent: Color::LightRed,
+            selected: Color::Red,
+            error: Color::Red,
+            warn: Color::Yellow,
+            info: Color::LightBlue,
+            enabled: Color::Green,
+            disabled: Color::DarkGray,
+            outdated: Color::Yellow,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            background: Color::Rgb(245, 245, 245),
+            surface: Color::Rgb(222, 222, 222),
+            text: Color::Rgb(30, 30, 30),
+            muted: Color::Rgb(110, 110, 110),
+            border: Color::Rgb(180, 180, 180),
+            accent: Color::Rgb(200, 60, 60),
+            selected: Color::Rgb(170, 20, 20),
+            error: Color::Rgb(190, 0, 0),
+            warn: Color::Rgb(170, 110, 0),
+            info: Color::Rgb(20, 90, 200),
+            enabled: Color::Rgb(20, 130, 40),
+            disabled: Color::Rgb(150, 150, 150),
+            outdated: Color::Rgb(180, 110, 0),
+        }
+    }
+
+    /// only uses the 16 basic colors so it looks the same everywhere
+    pub fn high_contrast() -> Self {
+        Self {
+            background: Color::Black,
+            surface: Color::Black,
+            text: Color::White,
+            muted: Color::Gray,
+            border: Color::White,
+            accent: Color::LightYellow,
+            selected: Color::LightCyan,
+            error: Color::LightRed,
+            warn: Color::LightYellow,
+            info: Color::LightCyan,
+            enabled: Color::LightGreen,
+            disabled: Color::DarkGray,
+            outdated: Color::LightMagenta,
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// a built-in theme or `<config dir>/themes/<name>.json5`
+    pub fn load(name: &str) -> AnyResult<Self> {
+        if let Some(theme) = Self::builtin(name) {
+            return Ok(theme);
+        }
+
+        let path = user_theme_path(name);
+        if !path.exists() {
+            bail!(
+                "no theme called \"{name}\", expected one of {} or {}",
+                BUILTIN_THEMES.join(", "),
+                path.display()
+            );
+        }
+
+        Self::parse(
+            &fs::read_to_string(&path)
+                .with_context(|| format!("couldn't read {}", path.display()))?,
+        )
+        .with_context(|| format!("invalid theme in {}", path.display()))
+    }
+
+    /// a theme file is a built-in `base` with any of the roles replaced
+    pub fn parse(content: &str) -> AnyResult<Self> {
+        let file = json5::from_str::<ThemeFile>(content)?;
+        let base = file.base.as_deref().unwrap_or("dark");
+        let mut theme = Self::builtin(base).ok_or_else(|| {
+            anyhow!(
+                "unknown base theme \"{base}\", expected one of {}",
+                BUILTIN_THEMES.join(", ")
+            )
+        })?;
+
+        for (role, value) in file.roles {
+            let color = Color::from_str(&value)
+                .map_err(|_| anyhow!("\"{value}\" isn't a color, use a name, an index or #rrggbb"))
+                .with_context(|| format!("invalid color for {role}"))?;
+            *theme
+                .roles_mut()
+                .into_iter()
+                .find_map(|(name, slot)| (name == role).then_some(slot))
+                .ok_or_else(|| anyhow!("unknown role \"{role}\""))? = color;
+        }
+
+        Ok(theme)
+    }
+
+    fn roles_mut(&mut self) -> [(&'static str, &mut Color); 13] {
+        [
+            ("background", &mut self.background),
+            ("surface", &mut self.surface),
+            ("text", &mut self.text),
+            ("muted", &mut self.muted),
+            ("border", &mut self.border),
+            ("accent", &mut self.accent),
+            ("selected", &mut self.selected),
+            ("error", &mut self.error),
+            ("warn", &mut self.warn),
+            ("info", &mut self.info),
+            ("enabled", &mut self.enabled),
+            ("disabled", &mut self.disabled),
+            ("outdated", &mut self.outdated),
+        ]
+    }
+
+    /// replaces the colors the terminal can't show with the closest ones it can
+    pub fn downsample(mut self, depth: ColorDepth) -> Self {
+        for (_, color) in self.roles_mut() {
+            *color = depth.convert(*color);
+        }
+
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct ThemeFile {
+    base: Option<String>,
+    #[serde(flatten)]
+    roles: BTreeMap<String, String>,
+}
+
+pub fn user_theme_path(name: &str) -> PathBuf {
+    config_dir().join("themes").join(format!("{name}.json5"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl FromStr for ColorDepth {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "truecolor" | "24bit" => Ok(ColorDepth::TrueColor),
+            "256" => Ok(ColorDepth::Ansi256),
+            "16" => Ok(ColorDepth::Ansi16),
+            _ => Err(anyhow!(
+                "unknown color depth \"{s}\", expected truecolor, 256 or 16"
+            )),
+        }
+    }
+}
+
+impl ColorDepth {
+    /// guesses from the environment the same way most terminal apps do
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+
+        if colorterm.contains("truecolor")
+            || colorterm.contains("24bit")
+            || std::env::var_os("WT_SESSION").is_some()
+        {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else if cfg!(windows) && term.is_empty() {
+            // the windows 10+ console understands rgb
+            ColorDepth::TrueColor
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+
+    pub fn convert(&self, color: Color) -> Color {
+        match (self, color) {
+            (ColorDepth::TrueColor, color) => color,
+            (ColorDepth::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(nearest_indexed(r, g, b)),
+            (ColorDepth::Ansi256, color) => color,
+            (ColorDepth::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi(r, g, b),
+            (ColorDepth::Ansi16, Color::Indexed(index)) if index >= 16 => {
+                let (r, g, b) = indexed_rgb(index);
+                nearest_ansi(r, g, b)
+            }
+            (ColorDepth::Ansi16, color) => color,
+        }
+    }
+}
+
+/// sets the theme used for the rest of the run, only the first call does anything
+pub fn set_theme(theme: Theme) {
+    _ = THEME.set(theme);
+}
+
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(|| Theme::default().downsample(ColorDepth::detect()))
+}
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+const ANSI_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(r1, r2) + channel(g1, g2) + channel(b1, b2)
+}
+
+/// the closest entry of the 6x6x6 cube or the grayscale ramp of the 256 color palette
+fn nearest_indexed(r: u8, g: u8, b: u8) -> u8 {
+    let level = |value: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&index| (CUBE_LEVELS[index] as i32 - value as i32).abs())
+            .unwrap_or_default() as u8
+    };
+    let cube = 16 + 36 * level(r) + 6 * level(g) + level(b);
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = 232 + (average.saturating_sub(8) / 10).min(23) as u8;
+
+    [cube, gray]
+        .into_iter()
+        .min_by_key(|&index| distance(indexed_rgb(index), (r, g, b)))
+        .unwrap_or(cube)
+}
+
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_COLORS[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (index - 232);
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_ansi(r: u8, g: u8, b: u8) -> Color {
+    ANSI_COLORS
+        .into_iter()
+        .min_by_key(|(_, rgb)| distance(*rgb, (r, g, b)))
+        .map(|(color, _)| color)
+        .unwrap_or(Color::Reset)
+}
@@ -0,0 +1,98 @@
+use anyhow::{Context, Result as AnyResult};
+use serde::Deserialize;
+
+use crate::net;
+
+pub const PACKAGE_INDEX_URL: &str = "https://northstar.thunderstore.io/c/northstar/api/v1/package/";
+
+/// a package in the thunderstore index
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub owner: String,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    #[serde(default)]
+    pub has_nsfw_content: bool,
+    /// like `2024-01-31T12:00:00.000000Z`
+    #[serde(default)]
+    pub date_updated: String,
+    /// newest first
+    pub versions: Vec<PackageVersion>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct PackageVersion {
+    pub version_number: String,
+    pub download_url: String,
+    /// `Author-Name-Version`
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub downloads: u64,
+}
+
+impl Package {
+    pub fn version(&self, version: &str) -> Option<&PackageVersion> {
+        self.versions
+            .iter()
+            .find(|package_version| package_version.version_number == version)
+    }
+
+    /// packages are named by their authors, a mod.json name like `Pilot Grapple` or `Pilot.Grapple`
+    /// is usually the package `Pilot_Grapple` so only letters and numbers are compared
+    pub fn is_mod(&self, mod_name: &str) -> bool {
+        simplify(&self.name) == simplify(mod_name)
+    }
+
+    /// every version together like thunderstore counts them
+    pub fn downloads(&self) -> u64 {
+        self.versions.iter().map(|version| version.downloads).sum()
+    }
+
+    /// `<author>-<name>-<version>`, what the archive is found by in the package cache
+    pub fn cache_key(&self, version: &str) -> String {
+        format!("{}-{}-{version}", self.owner, self.name)
+    }
+}
+
+pub fn fetch_index(url: &str) -> AnyResult<Vec<Package>> {
+    serde_json::from_slice(&net::fetch(url)?)
+        .with_context(|| format!("{url} didn't return a package index"))
+}
+
+/// the package a mod most likely comes from, without an author the first one with its name
+pub fn find_package<'a>(
+    index: &'a [Package],
+    mod_name: &str,
+    author: Option<&str>,
+) -> Option<&'a Package> {
+    index.iter().find(|package| {
+        package.is_mod(mod_name) && author.is_none_or(|author| author == package.owner)
+    })
+}
+
+/// `Author-Name-Version` from a manifest's dependencies
+pub fn find_dependency<'a>(
+    index: &'a [Package],
+    dependency: &str,
+) -> Option<(&'a Package, &'a PackageVersion)> {
+    let mut parts = dependency.splitn(3, '-');
+    let (owner, name, version) = (parts.next()?, parts.next()?, parts.next()?);
+    let package = index
+        .iter()
+        .find(|package| package.owner == owner && package.name == name)?;
+
+    Some((package, package.version(version)?))
+}
+
+fn simplify(name: &str) -> String {
+    name.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
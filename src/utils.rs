@@ -0,0 +1,150 @@
+use anyhow::Result as AnyResult;
+use crossterm::event;
+use ratatui::{prelude::*, widgets::*};
+
+use crate::{
+    app::{GlobalState, TabUi},
+    cache_view::Cache,
+    convars::ConVars,
+    dedicated_view::Dedicated,
+    disk_usage_view::DiskUsageView,
+    integrity_view::Integrity,
+    keymap::{Action, KeyContext, Keymap},
+    mouse::{is_left_click, Hitboxes},
+    nslogs::NsLogs,
+    profile_diff_view::ProfileDiffView,
+    theme::theme,
+};
+
+pub struct Utils {
+    sections: Vec<(&'static str, Box<dyn TabUi>)>,
+    selected_section: usize,
+    labels: Hitboxes<usize>,
+}
+
+impl Default for Utils {
+    fn default() -> Self {
+        Self {
+            sections: vec![
+                ("Logs", Box::new(NsLogs::default())),
+                ("ConVars", Box::new(ConVars::default())),
+                ("Integrity", Box::new(Integrity::default())),
+                ("Dedicated", Box::new(Dedicated::default())),
+                ("Cache", Box::new(Cache::default())),
+                ("Disk", Box::new(DiskUsageView::default())),
+                ("Diff", Box::new(ProfileDiffView::default())),
+            ],
+            selected_section: 0,
+            labels: Hitboxes::default(),
+        }
+    }
+}
+
+impl Utils {
+    fn section(&self) -> &dyn TabUi {
+        self.sections
+            .get(self.selected_section)
+            .expect("selected section should exist")
+            .1
+            .as_ref()
+    }
+
+    fn section_mut(&mut self) -> &mut dyn TabUi {
+        self.sections
+            .get_mut(self.selected_section)
+            .expect("selected section should exist")
+            .1
+            .as_mut()
+    }
+
+    fn select_section(&mut self, section: usize, state: &mut GlobalState) -> AnyResult<()> {
+        self.selected_section = section;
+        self.section_mut().on_focus(state)
+    }
+}
+
+impl TabUi for Utils {
+    fn render_ui(&self, frame: &mut Frame<'_>, rect: Rect) {
+        let layout = Layout::new()
+            .constraints([Constraint::Length(12), Constraint::Min(0)])
+            .direction(Direction::Horizontal)
+            .split(rect);
+
+        let side_layout = Layout::new()
+            .constraints(
+                self.sections
+                    .iter()
+                    .map(|_| Constraint::Length(2))
+                    .chain([Constraint::Min(0)])
+                    .collect::<Vec<Constraint>>(),
+            )
+            .split(layout[0]);
+        frame.render_widget(Block::new().bg(theme().background), layout[0]);
+        self.labels.clear();
+        self.sections
+            .iter()
+            .enumerate()
+            .zip(side_layout.iter().cloned())
+            .for_each(|((e, (label, _)), rect)| {
+                frame.render_widget(
+                    Paragraph::new(*label)
+                        .style(if e == self.selected_section {
+                            Style::default().fg(theme().selected)
+                        } else {
+                            Style::default().fg(theme().text)
+                        })
+                        .alignment(Alignment::Center)
+                        .bg(theme().background),
+                    rect,
+                );
+                self.labels.push(rect, e);
+            });
+
+        self.section().render_ui(frame, layout[1]);
+    }
+
+    fn render_binds(&self, keymap: &Keymap) -> Vec<String> {
+        let mut binds = keymap.hints(KeyContext::Utils);
+        binds.extend(self.section().render_binds(keymap));
+        binds
+    }
+
+    fn update(&mut self, crosstermevent: event::Event, state: &mut GlobalState) -> AnyResult<()> {
+        match crosstermevent {
+            event::Event::Key(key_event)
+                if key_event.kind == event::KeyEventKind::Press
+                    && !state.is_typing
+                    && state.keymap.action(KeyContext::Utils, &key_event)
+                        == Some(Action::NextSection) =>
+            {
+                return self
+                    .select_section((self.selected_section + 1) % self.sections.len(), state);
+            }
+            event::Event::Mouse(mouse) if is_left_click(&mouse) && !state.is_typing => {
+                if let Some(section) = self.labels.hit(&mouse) {
+                    return self.select_section(section, state);
+                }
+            }
+            _ => {}
+        }
+
+        self.section_mut().update(crosstermevent, state)
+    }
+
+    fn on_focus(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.section_mut().on_focus(state)
+    }
+
+    fn tick(&mut self, state: &mut GlobalState) -> AnyResult<()> {
+        self.section_mut().tick(state)
+    }
+
+    fn on_disk_usage(&mut self, state: &GlobalState) {
+        self.section_mut().on_disk_usage(state)
+    }
+
+    #[cfg(test)]
+    fn is_busy(&self) -> bool {
+        self.section().is_busy()
+    }
+}